@@ -4,6 +4,7 @@ use std::marker::PhantomData;
 
 use bevy::{ecs::schedule::SystemConfigs, prelude::*};
 
+pub mod actions;
 pub mod actors;
 pub mod actors_list;
 pub mod assets;
@@ -11,22 +12,37 @@ pub mod audio;
 pub mod components;
 pub mod constants;
 pub mod construction;
+pub mod contracts;
 pub mod cycle;
+pub mod daily;
 pub mod deployment;
+pub mod difficulty;
 pub mod economy;
 pub mod events;
+pub mod game_mode;
 pub mod game_params;
 pub mod inventory;
 pub mod item;
+pub mod jobs;
+pub mod ledger;
 pub mod level;
 pub mod map;
+pub mod mutators;
 mod picking;
 pub mod rendering;
 pub mod resources;
+pub mod rng;
+pub mod score;
 pub mod selection;
+pub mod shelter;
+pub mod structure_actions;
 pub mod systems;
 pub mod tile_set;
+pub mod transfer;
+pub mod turn_order;
+pub mod undo;
 pub mod vfx;
+pub mod visitors;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
@@ -42,6 +58,24 @@ pub(super) fn plugin(app: &mut App) {
         item::ItemPlugin,
         rendering::MapRenderingPlugin,
         vfx::VfxPlugin,
+        structure_actions::StructureActionsPlugin,
+        (
+            jobs::JobsPlugin,
+            shelter::ShelterPlugin,
+            ledger::LedgerPlugin,
+            visitors::VisitorsPlugin,
+            transfer::TransferPlugin,
+            contracts::ContractsPlugin,
+            actions::ActionsPlugin,
+            undo::UndoPlugin,
+            turn_order::TurnOrderPlugin,
+            difficulty::DifficultyPlugin,
+            game_mode::GameModePlugin,
+            score::ScorePlugin,
+            daily::DailyPlugin,
+            rng::RngPlugin,
+            mutators::MutatorsPlugin,
+        ),
     ));
 }
 
@@ -58,10 +92,21 @@ impl<R: Resource> Default for WatchRes<R> {
     }
 }
 
+/// A resource holding a value per key, labels watching it show the value of their key.
+pub trait KeyedResource: Resource {
+    type Key: Component;
+
+    fn label(&self, key: &Self::Key) -> String;
+}
+
 pub fn update_resource_label<R: Resource + ToString>() -> SystemConfigs {
     set_resource_label::<R>.run_if(resource_changed::<R>)
 }
 
+pub fn update_keyed_resource_label<R: KeyedResource>() -> SystemConfigs {
+    set_keyed_resource_label::<R>.run_if(resource_changed::<R>)
+}
+
 fn update_resource_label_system<R: Resource>(system: SystemConfigs) -> SystemConfigs {
     system.run_if(resource_changed::<R>)
 }
@@ -75,6 +120,15 @@ fn set_resource_label<R: Resource + ToString>(
     }
 }
 
+fn set_keyed_resource_label<R: KeyedResource>(
+    mut q_texts: Query<(&mut Text, &R::Key), With<WatchRes<R>>>,
+    value: Res<R>,
+) {
+    for (mut text, key) in q_texts.iter_mut() {
+        text.sections[0].value = value.label(key);
+    }
+}
+
 pub const INVENTORY_CAPACITY: usize = 5;
 
 pub const MODAL_Z_LAYER: i32 = 100;
@@ -0,0 +1,583 @@
+//! Actions every hero can take in battle next to using items, picked from the action bar.
+
+use bevy::color::palettes::css;
+use bevy::prelude::*;
+use sickle_ui::prelude::*;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+use crate::path_finding::find_all_within_distance_unweighted;
+use crate::path_finding::tiles::{Tile, TileDim, TileDir};
+use crate::screen::playing::GameState;
+use crate::screen::Screen;
+use crate::ui::icon_set::IconSet;
+use crate::ui::prelude::InteractionPalette;
+
+use super::actors::class::{Experience, ShieldBlock};
+use super::actors::enemy::spring_trap;
+use super::actors::stats::Health;
+use super::actors::{
+    ActorTurnState, ClearUndoEvent, EnemyActor, IsAirborne, PlayerActor, Structure,
+};
+use super::components::Trap;
+use super::constants::{ACTION_HEAL, KICK_DAMAGE, TEXT_SIZE, UNIT_LIST_ZINDEX};
+use super::inventory::Inventory;
+use super::item::InventorySelection;
+use super::level::Terrain;
+use super::map::VillageMap;
+use super::mutators::Damage;
+use super::selection::{self, SelectedActor, SelectedTiles, SelectionEvent};
+use super::tile_set::tile_coord_translation;
+use super::turn_order::Side;
+use super::vfx::FireOneShotVfx;
+
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelectedAction>()
+            .add_systems(OnEnter(Screen::Playing), action_bar_layout)
+            .add_systems(
+                OnExit(GameState::BattleTurn),
+                |mut selected_action: ResMut<SelectedAction>| selected_action.0 = None,
+            )
+            .add_systems(
+                Update,
+                (
+                    set_action_bar_display,
+                    (
+                        action_btn_interaction,
+                        apply_hero_action.after(selection::set_selected_unit),
+                        update_action_bar,
+                    )
+                        .chain()
+                        .run_if(in_state(GameState::BattleTurn)),
+                )
+                    .run_if(in_state(Screen::Playing)),
+            );
+    }
+}
+
+/// Actions every hero knows, no item needed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, EnumIter)]
+pub enum InnateAction {
+    Block,
+    Kick,
+    FirstAid,
+    Recover,
+}
+
+/// Who an action can be used on.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ActionTarget {
+    /// Used on the hero right away.
+    Hero,
+    /// Another hero in range.
+    Ally,
+    /// Anything in range that isn't a structure.
+    Unit,
+}
+
+impl InnateAction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            InnateAction::Block => "Block",
+            InnateAction::Kick => "Kick",
+            InnateAction::FirstAid => "First aid",
+            InnateAction::Recover => "Recover",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            InnateAction::Block => "Block the next hit.",
+            InnateAction::Kick => {
+                "Knock an adjacent unit back, into water or whatever is behind it."
+            }
+            InnateAction::FirstAid => "Heal an adjacent hero.",
+            InnateAction::Recover => "Heal yourself, only before moving.",
+        }
+    }
+
+    pub fn target(&self) -> ActionTarget {
+        match self {
+            InnateAction::Block | InnateAction::Recover => ActionTarget::Hero,
+            InnateAction::Kick => ActionTarget::Unit,
+            InnateAction::FirstAid => ActionTarget::Ally,
+        }
+    }
+
+    pub fn range(&self) -> u32 {
+        match self {
+            InnateAction::Block | InnateAction::Recover => 0,
+            InnateAction::Kick | InnateAction::FirstAid => 1,
+        }
+    }
+
+    pub fn directions(&self) -> &'static [TileDir] {
+        match self {
+            InnateAction::Kick => &TileDir::EDGES,
+            _ => &TileDir::ALL,
+        }
+    }
+
+    /// Tiles the action can reach from `tile`.
+    pub fn reach(&self, tile: Tile) -> bevy::utils::HashSet<Tile> {
+        let directions = self.directions();
+        find_all_within_distance_unweighted(tile, self.range(), |t| {
+            directions.iter().copied().map(move |d| t.step(d))
+        })
+    }
+}
+
+/// Entry of the action bar.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ActionButton {
+    Innate(InnateAction),
+    /// Inventory slot of the item to use.
+    Item(usize),
+}
+
+/// Action waiting for a target and the hero performing it.
+#[derive(Resource, Default, Debug)]
+pub struct SelectedAction(pub Option<(InnateAction, Entity)>);
+
+#[derive(Component)]
+pub struct ActionBar;
+
+#[derive(Component)]
+pub struct ActionBarButtons;
+
+#[derive(Component)]
+pub struct ActionDescriptionLabel;
+
+/// Where a kicked unit ends up.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Knockback {
+    Pushed(Tile),
+    /// Land units kicked into water are lost.
+    Drowned,
+    /// Knocked into the map's edge or into another actor, which gets hurt as well.
+    Blocked(Option<Entity>),
+}
+
+/// Push the unit at `target` one tile away from `from`.
+pub fn resolve_knockback(map: &VillageMap, from: Tile, target: Tile, airborne: bool) -> Knockback {
+    let destination = Tile(
+        target.x() + (target.x() - from.x()).signum(),
+        target.y() + (target.y() - from.y()).signum(),
+    );
+
+    if map.is_out_of_bounds(destination) {
+        return Knockback::Blocked(None);
+    }
+    if let Some(other) = map.actors.get(destination) {
+        return Knockback::Blocked(Some(other));
+    }
+    if map.get_terrain(destination) == Some(Terrain::Water) && !airborne {
+        return Knockback::Drowned;
+    }
+    Knockback::Pushed(destination)
+}
+
+fn action_bar_layout(mut commands: Commands) {
+    commands
+        .ui_builder(UiRoot)
+        .column(|ui| {
+            ui.label(LabelConfig::from(""))
+                .insert(ActionDescriptionLabel)
+                .style()
+                .font_size(TEXT_SIZE);
+            ui.row(|ui| {
+                ui.insert(ActionBarButtons)
+                    .style()
+                    .column_gap(Val::Px(4.))
+                    .padding(UiRect::all(Val::Px(4.)))
+                    .background_color(Color::BLACK.with_alpha(0.6));
+            });
+        })
+        .insert((ActionBar, StateScoped(Screen::Playing)))
+        .style()
+        .z_index(UNIT_LIST_ZINDEX)
+        .position_type(PositionType::Absolute)
+        .bottom(Val::Px(16.))
+        .width(Val::Percent(100.))
+        .align_items(AlignItems::Center)
+        .row_gap(Val::Px(4.));
+}
+
+fn set_action_bar_display(
+    game_state: Res<State<GameState>>,
+    selected_actor: Res<SelectedActor>,
+    q_heroes: Query<(), With<PlayerActor>>,
+    mut q_bars: Query<&mut Style, With<ActionBar>>,
+) {
+    let hero_selected = selected_actor
+        .entity
+        .is_some_and(|entity| q_heroes.contains(entity));
+    let display = match *game_state.get() == GameState::BattleTurn && hero_selected {
+        true => Display::Flex,
+        false => Display::None,
+    };
+
+    for mut style in q_bars.iter_mut() {
+        if style.display != display {
+            style.display = display;
+        }
+    }
+}
+
+/// Rebuild the action bar for the selected hero.
+fn update_action_bar(
+    mut commands: Commands,
+    selected_actor: Res<SelectedActor>,
+    selected_action: Res<SelectedAction>,
+    q_heroes: Query<(Ref<Inventory>, Ref<ActorTurnState>), With<PlayerActor>>,
+    q_bars: Query<Entity, With<ActionBarButtons>>,
+    mut q_description_label: Query<&mut Text, With<ActionDescriptionLabel>>,
+    icon_set: Res<IconSet>,
+) {
+    let Some((inventory, turn_state)) = selected_actor
+        .entity
+        .and_then(|entity| q_heroes.get(entity).ok())
+    else {
+        return;
+    };
+
+    if !selected_actor.is_changed()
+        && !selected_action.is_changed()
+        && !inventory.is_changed()
+        && !turn_state.is_changed()
+    {
+        return;
+    }
+
+    let description = match selected_action.0 {
+        Some((action, _)) => action.description(),
+        None if turn_state.used_action => "Action already used this turn.",
+        None => "",
+    };
+    for mut text in q_description_label.iter_mut() {
+        text.sections[0].value = description.to_string();
+    }
+
+    let text_color = match turn_state.used_action {
+        true => css::GRAY.into(),
+        false => Color::WHITE,
+    };
+
+    for entity in q_bars.iter() {
+        commands.entity(entity).despawn_descendants();
+        let mut ui = commands.ui_builder(entity);
+
+        for action in InnateAction::iter() {
+            let border_color = match selected_action.0 {
+                Some((selected, _)) if selected == action => css::YELLOW.into(),
+                _ => Color::WHITE,
+            };
+
+            ui.container(ButtonBundle::default(), |ui| {
+                ui.label(LabelConfig::from(action.name()))
+                    .style()
+                    .font_size(TEXT_SIZE)
+                    .font_color(text_color);
+            })
+            .insert((
+                ActionButton::Innate(action),
+                InteractionPalette {
+                    none: css::TEAL.into(),
+                    hovered: css::DARK_CYAN.into(),
+                    pressed: css::DARK_SLATE_GRAY.into(),
+                },
+            ))
+            .style()
+            .padding(UiRect::all(Val::Px(6.)))
+            .border(UiRect::all(Val::Px(2.)))
+            .border_color(border_color)
+            .border_radius(BorderRadius::all(Val::Px(4.)));
+        }
+
+        for slot in 0..inventory.slot_count() {
+            let Some(item) = inventory.get(slot) else {
+                continue;
+            };
+
+            let border_color = match inventory.selected_item == Some(slot) {
+                true => css::YELLOW.into(),
+                false => Color::WHITE,
+            };
+
+            ui.container(
+                ButtonBundle {
+                    image: UiImage::new(icon_set.get(item.icon)),
+                    ..default()
+                },
+                |_| {},
+            )
+            .insert((
+                ActionButton::Item(slot),
+                InteractionPalette {
+                    none: Color::BLACK,
+                    hovered: Color::BLACK.lighter(0.4),
+                    pressed: Color::BLACK.lighter(0.2),
+                },
+            ))
+            .style()
+            .border(UiRect::all(Val::Px(2.)))
+            .border_color(border_color)
+            .border_radius(BorderRadius::all(Val::Px(4.)))
+            .width(Val::Px(32.))
+            .height(Val::Px(32.));
+        }
+    }
+}
+
+fn action_btn_interaction(
+    mut commands: Commands,
+    q_interactions: Query<(&Interaction, &ActionButton), Changed<Interaction>>,
+    mut q_heroes: Query<(&mut Inventory, &mut ActorTurnState, &mut Health), With<PlayerActor>>,
+    selected_actor: Res<SelectedActor>,
+    village_map: Res<VillageMap>,
+    mut selected_action: ResMut<SelectedAction>,
+    mut inventory_selection: ResMut<InventorySelection>,
+    mut selected_tiles: ResMut<SelectedTiles>,
+    mut clear_undo_event: EventWriter<ClearUndoEvent>,
+) {
+    let Some(hero) = selected_actor.entity else {
+        return;
+    };
+    let Ok((mut inventory, mut turn_state, mut health)) = q_heroes.get_mut(hero) else {
+        return;
+    };
+
+    for (_, button) in q_interactions
+        .iter()
+        .filter(|(&i, _)| i == Interaction::Pressed)
+    {
+        let action = match *button {
+            ActionButton::Item(slot) => {
+                // Item actions go through the inventory selection
+                selected_action.0 = None;
+                if inventory.get(slot).is_some() {
+                    inventory.selected_item = Some(slot);
+                }
+                continue;
+            }
+            ActionButton::Innate(action) => action,
+        };
+
+        if turn_state.used_action {
+            continue;
+        }
+
+        inventory.selected_item = None;
+        inventory_selection.selection = None;
+        selected_tiles.clear();
+
+        match action {
+            InnateAction::Block => {
+                commands.entity(hero).insert(ShieldBlock);
+            }
+            InnateAction::Recover => {
+                if turn_state.used_move || health.is_full() {
+                    continue;
+                }
+                health.value = health.heal(ACTION_HEAL);
+                turn_state.used_move = true;
+            }
+            action => {
+                selected_action.0 = Some((action, hero));
+                if let Some(tile) = village_map.actors.locate(hero) {
+                    selected_tiles.tiles = action.reach(tile);
+                    selected_tiles.color = css::LIGHT_SKY_BLUE.into();
+                }
+                continue;
+            }
+        }
+
+        selected_action.0 = None;
+        turn_state.used_action = true;
+        clear_undo_event.send(ClearUndoEvent);
+    }
+}
+
+/// Use the selected action on the next unit selected on the map.
+fn apply_hero_action(
+    mut commands: Commands,
+    mut selection_events: EventReader<SelectionEvent>,
+    mut q_turn_states: Query<(&mut ActorTurnState, Option<&mut Experience>)>,
+    mut q_healths: Query<&mut Health>,
+    mut q_transforms: Query<&mut Transform, Without<Trap>>,
+    q_traps: Query<(&Trap, &Transform)>,
+    q_heroes: Query<(), With<PlayerActor>>,
+    q_enemies: Query<(), With<EnemyActor>>,
+    q_structures: Query<(), With<Structure>>,
+    q_airborne: Query<(), With<IsAirborne>>,
+    selected_actor: Res<SelectedActor>,
+    mut selected_action: ResMut<SelectedAction>,
+    mut village_map: ResMut<VillageMap>,
+    damage: Damage,
+    mut clear_undo_event: EventWriter<ClearUndoEvent>,
+    mut evw_oneshot_vfx: EventWriter<FireOneShotVfx>,
+) {
+    if selection_events.is_empty() {
+        return;
+    }
+    selection_events.clear();
+
+    // Any selection uses up or cancels the action
+    let Some((action, hero)) = selected_action.0.take() else {
+        return;
+    };
+    let Some(target) = selected_actor.entity.filter(|target| *target != hero) else {
+        return;
+    };
+    let (Some(hero_tile), Some(target_tile)) = (
+        village_map.actors.locate(hero),
+        village_map.actors.locate(target),
+    ) else {
+        return;
+    };
+    if !action.reach(hero_tile).contains(&target_tile) {
+        return;
+    }
+
+    let valid_target = match action.target() {
+        ActionTarget::Hero => false,
+        ActionTarget::Ally => q_heroes.contains(target),
+        ActionTarget::Unit => {
+            !q_structures.contains(target) && village_map.actors.footprint(target) == TileDim::ONE
+        }
+    };
+    if !valid_target {
+        return;
+    }
+
+    let Ok((mut turn_state, experience)) = q_turn_states.get_mut(hero) else {
+        return;
+    };
+    if turn_state.used_action {
+        return;
+    }
+
+    match action {
+        InnateAction::FirstAid => {
+            let Ok(mut health) = q_healths.get_mut(target) else {
+                return;
+            };
+            health.value = health.heal(ACTION_HEAL);
+        }
+        InnateAction::Kick => {
+            let knockback = resolve_knockback(
+                &village_map,
+                hero_tile,
+                target_tile,
+                q_airborne.contains(target),
+            );
+
+            let target_side = Side::of(q_enemies.contains(target));
+            let mut hit = damage.dealt(Side::Heroes, target_side, KICK_DAMAGE);
+            let mut kills = 0;
+            match knockback {
+                Knockback::Pushed(tile) => {
+                    village_map.actors.set(tile, target);
+                    let translation = tile_coord_translation(tile.x() as f32, tile.y() as f32, 2.);
+                    if let Ok(mut transform) = q_transforms.get_mut(target) {
+                        transform.translation = translation;
+                    }
+                    // Enemies land on hidden traps like they walked onto them
+                    if !q_heroes.contains(target) && !q_airborne.contains(target) {
+                        if let Some(trap) = spring_trap(
+                            &mut commands,
+                            &mut village_map,
+                            &q_traps,
+                            &mut evw_oneshot_vfx,
+                            tile,
+                            translation,
+                        ) {
+                            hit = hit.saturating_add(damage.dealt(
+                                Side::Heroes,
+                                target_side,
+                                trap.damage,
+                            ));
+                        }
+                    }
+                }
+                Knockback::Drowned => hit = u32::MAX,
+                Knockback::Blocked(other) => {
+                    hit = damage.dealt(Side::Heroes, target_side, KICK_DAMAGE * 2);
+                    if let Some(other) = other {
+                        let other_side = Side::of(q_enemies.contains(other));
+                        if let Ok(mut health) = q_healths.get_mut(other) {
+                            health.value =
+                                health.hurt(damage.dealt(Side::Heroes, other_side, KICK_DAMAGE));
+                            if health.value == 0 && !q_heroes.contains(other) {
+                                kills += 1;
+                            }
+                        }
+                    }
+                }
+            }
+
+            let Ok(mut health) = q_healths.get_mut(target) else {
+                return;
+            };
+            health.value = health.hurt(hit);
+            if health.value == 0 && !q_heroes.contains(target) {
+                kills += 1;
+            }
+            if let Some(mut experience) = experience {
+                for _ in 0..kills {
+                    experience.gain_kill();
+                }
+            }
+        }
+        InnateAction::Block | InnateAction::Recover => return,
+    }
+
+    turn_state.used_action = true;
+    clear_undo_event.send(ClearUndoEvent);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_knockback() {
+        let mut map = VillageMap::new(TileDim(5, 5));
+        let hero = Tile(1, 2);
+        let target = Tile(2, 2);
+
+        assert_eq!(
+            resolve_knockback(&map, hero, target, false),
+            Knockback::Pushed(Tile(3, 2))
+        );
+
+        map.set_terrain(Tile(3, 2), Terrain::Water);
+        assert_eq!(
+            resolve_knockback(&map, hero, target, false),
+            Knockback::Drowned
+        );
+        assert_eq!(
+            resolve_knockback(&map, hero, target, true),
+            Knockback::Pushed(Tile(3, 2))
+        );
+
+        let other = Entity::from_raw(7);
+        map.actors.set(Tile(3, 2), other);
+        assert_eq!(
+            resolve_knockback(&map, hero, target, false),
+            Knockback::Blocked(Some(other))
+        );
+    }
+
+    #[test]
+    fn test_knockback_into_map_edge() {
+        let map = VillageMap::new(TileDim(5, 5));
+        assert_eq!(
+            resolve_knockback(&map, Tile(1, 0), Tile(0, 0), false),
+            Knockback::Blocked(None)
+        );
+    }
+}
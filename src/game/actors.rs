@@ -2,14 +2,15 @@
 //! They represent any substantial game objects: player controlled characters, buildings, monsters, trees, etc
 //! Each map tile can hold a maximum of one actor.
 
-use crate::path_finding::tiles::{Tile, TileDir};
+use crate::path_finding::tiles::TileDir;
 use crate::screen::Screen;
 use bevy::prelude::*;
+use class::ClassPlugin;
 use enemy::EnemyActorsPlugin;
 use rand::prelude::SliceRandom;
-use rand::thread_rng;
+use rand::Rng;
 use spawn::SpawnActorsPlugin;
-use stats::{ActorName, Health, Movement};
+use stats::{ActorName, Health, Movement, Speed};
 
 use self::spawn::DespawnAnimation;
 
@@ -17,6 +18,7 @@ use super::components::{ActorTileLayer, PopulationCapacity};
 use super::constants::HOUSE_POPULATION_CAPACITY;
 use super::map::VillageMap;
 
+pub mod class;
 pub mod enemy;
 pub mod player;
 pub mod spawn;
@@ -76,22 +78,20 @@ pub const NAMES: &[&str] = &[
     "Ashish Bhattacharya",
 ];
 
-#[derive(Resource)]
+/// Names left for new heroes, shuffled again at the start of every run.
+#[derive(Resource, Default)]
 pub struct AvailableActorNames(pub Vec<&'static str>);
 
-impl Default for AvailableActorNames {
-    fn default() -> Self {
-        let mut names_vec: Vec<&'static str> = NAMES.to_vec();
-        let mut rng = thread_rng();
-        names_vec.shuffle(&mut rng);
-        AvailableActorNames(names_vec)
-    }
-}
-
 #[derive(Event)]
 pub struct ClearUndoEvent;
 
 impl AvailableActorNames {
+    pub fn shuffled(rng: &mut impl Rng) -> Self {
+        let mut names_vec: Vec<&'static str> = NAMES.to_vec();
+        names_vec.shuffle(rng);
+        AvailableActorNames(names_vec)
+    }
+
     pub fn next_name(&mut self) -> String {
         self.0
             .pop()
@@ -103,13 +103,14 @@ pub struct ActorPlugin;
 
 impl Plugin for ActorPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((EnemyActorsPlugin, SpawnActorsPlugin))
+        app.add_plugins((EnemyActorsPlugin, SpawnActorsPlugin, ClassPlugin))
             .add_event::<ClearUndoEvent>()
             .add_systems(Update, health_ui.run_if(in_state(Screen::Playing)));
     }
 }
 
-fn health_ui(
+/// Take everything that ran out of health off the map, several can fall in the same frame.
+pub fn health_ui(
     mut commands: Commands,
     mut q_hit_points: Query<(Entity, &Health, &Transform), Changed<Health>>,
     q_is_player: Query<(), With<PlayerActor>>,
@@ -132,7 +133,6 @@ fn health_ui(
             }
             commands.entity(entity).insert(despawn_animation);
             village_map.actors.remove_entity(entity);
-            return;
         }
     }
 }
@@ -159,8 +159,6 @@ pub struct Directions(pub Vec<TileDir>);
 pub struct ActorTurnState {
     pub used_move: bool,
     pub used_action: bool,
-    /// position for undo
-    pub previous_position: Option<Tile>,
 }
 
 impl ActorTurnState {
@@ -174,6 +172,7 @@ pub struct ActorBundle<T: Component> {
     pub name: ActorName,
     pub health: Health,
     pub movement: Movement,
+    pub speed: Speed,
     pub turn_state: ActorTurnState,
     pub actor: T,
     pub layer_marker: ActorTileLayer,
@@ -190,6 +189,7 @@ where
             name: ActorName(String::from(name)),
             health: Health::new(2),
             movement: Movement(2),
+            speed: Speed(2),
             turn_state: ActorTurnState::default(),
             actor: T::default(),
             layer_marker: ActorTileLayer,
@@ -208,6 +208,11 @@ impl<T: Component> ActorBundle<T> {
         self.movement = Movement(movement);
         self
     }
+
+    pub fn with_speed(mut self, speed: u32) -> Self {
+        self.speed = Speed(speed);
+        self
+    }
 }
 
 /// Marker component for a building
@@ -232,3 +237,15 @@ impl Default for StructureBundle {
         }
     }
 }
+
+impl StructureBundle {
+    pub fn with_health(mut self, value: u32) -> Self {
+        self.health = Health::new(value);
+        self
+    }
+
+    pub fn with_population_capacity(mut self, capacity: u32) -> Self {
+        self.population_capacity = PopulationCapacity(capacity);
+        self
+    }
+}
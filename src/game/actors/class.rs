@@ -0,0 +1,250 @@
+//! Hero classes, tied to the hero's sprite, and the experience heroes earn from kills.
+
+use bevy::prelude::*;
+
+use crate::game::constants::{MAX_UPGRADED_STAT, XP_PER_KILL, XP_PER_LEVEL};
+use crate::game::cycle::TimeOfDay;
+use crate::game::inventory::{Item, ITEM_TEMPLATES};
+use crate::screen::Screen;
+
+use super::player::PlayerSprite;
+use super::stats::Health;
+
+pub struct ClassPlugin;
+
+impl Plugin for ClassPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(TimeOfDay::Night),
+            prepare_class_abilities.run_if(in_state(Screen::Playing)),
+        );
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ClassAbility {
+    /// Regains 1 health as night falls.
+    SecondWind,
+    /// Every level up spent also adds 1 max health.
+    BattleHardened,
+    /// Blocks the first hit of every night.
+    ShieldBlock,
+    /// Deals 1 more damage while wounded.
+    Berserk,
+}
+
+impl ClassAbility {
+    pub fn description(&self) -> &'static str {
+        match self {
+            ClassAbility::SecondWind => "Second wind: regains 1 health as night falls.",
+            ClassAbility::BattleHardened => "Battle hardened: +1 max health with every level up.",
+            ClassAbility::ShieldBlock => "Shield: blocks the first hit every night.",
+            ClassAbility::Berserk => "Berserk: +1 damage while wounded.",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct HeroClass {
+    pub name: &'static str,
+    pub health: u32,
+    pub movement: u32,
+    /// Initiative when turns go by initiative.
+    pub speed: u32,
+    /// Name of the item template the hero starts with.
+    pub starting_item: &'static str,
+    /// Items that deal 1 more damage or healing in the hands of this class.
+    pub proficiencies: &'static [&'static str],
+    pub ability: ClassAbility,
+}
+
+pub const HERO_CLASSES: &[HeroClass] = &[
+    HeroClass {
+        name: "Fighter",
+        health: 3,
+        movement: 3,
+        speed: 3,
+        starting_item: "sword",
+        proficiencies: &["sword", "dagger"],
+        ability: ClassAbility::SecondWind,
+    },
+    HeroClass {
+        name: "Warrior",
+        health: 4,
+        movement: 2,
+        speed: 2,
+        starting_item: "axe",
+        proficiencies: &["axe", "mace"],
+        ability: ClassAbility::BattleHardened,
+    },
+    HeroClass {
+        name: "Spartan",
+        health: 4,
+        movement: 2,
+        speed: 3,
+        starting_item: "sword",
+        proficiencies: &["sword", "whip"],
+        ability: ClassAbility::ShieldBlock,
+    },
+    HeroClass {
+        name: "Viking",
+        health: 3,
+        movement: 4,
+        speed: 4,
+        starting_item: "axe",
+        proficiencies: &["axe", "bow"],
+        ability: ClassAbility::Berserk,
+    },
+];
+
+impl PlayerSprite {
+    pub fn class(&self) -> &'static HeroClass {
+        match self {
+            PlayerSprite::Fighter => &HERO_CLASSES[0],
+            PlayerSprite::Warrior => &HERO_CLASSES[1],
+            PlayerSprite::Spartan => &HERO_CLASSES[2],
+            PlayerSprite::Viking => &HERO_CLASSES[3],
+        }
+    }
+}
+
+impl HeroClass {
+    pub fn starting_item(&self) -> Option<Item> {
+        ITEM_TEMPLATES
+            .iter()
+            .find(|item| item.name == self.starting_item)
+            .copied()
+    }
+
+    /// Highest max health level ups take a hero of this class to, battle hardened heroes go
+    /// one further for every level up.
+    pub fn max_health(&self) -> u32 {
+        match self.ability {
+            ClassAbility::BattleHardened => MAX_UPGRADED_STAT * 2,
+            _ => MAX_UPGRADED_STAT,
+        }
+    }
+
+    /// Extra damage or healing the item gets in the hands of a hero of this class.
+    pub fn item_bonus(&self, item: &Item, health: Health) -> u32 {
+        let mut bonus = 0;
+        if self.proficiencies.contains(&item.name) {
+            bonus += 1;
+        }
+        if self.ability == ClassAbility::Berserk && item.health_effect < 0 && !health.is_full() {
+            bonus += 1;
+        }
+        bonus
+    }
+}
+
+/// The hero blocks the next hit, used up when hit.
+#[derive(Component, Debug)]
+pub struct ShieldBlock;
+
+/// Experience and levels of a hero, each level up is a point to spend at the tavern.
+#[derive(Component, Default, Debug, Clone, PartialEq)]
+pub struct Experience {
+    pub xp: u32,
+    pub level: u32,
+    /// Level ups that haven't been spent yet.
+    pub unspent: u32,
+}
+
+impl Experience {
+    pub fn next_level_xp(&self) -> u32 {
+        XP_PER_LEVEL * (self.level + 1)
+    }
+
+    /// Returns the number of levels gained.
+    pub fn gain(&mut self, xp: u32) -> u32 {
+        self.xp += xp;
+        let mut levels = 0;
+        while self.xp >= self.next_level_xp() {
+            self.xp -= self.next_level_xp();
+            self.level += 1;
+            levels += 1;
+        }
+        self.unspent += levels;
+        levels
+    }
+
+    pub fn gain_kill(&mut self) -> u32 {
+        self.gain(XP_PER_KILL)
+    }
+}
+
+fn prepare_class_abilities(
+    mut commands: Commands,
+    mut q_heroes: Query<(Entity, &PlayerSprite, &mut Health)>,
+) {
+    for (entity, sprite, mut health) in q_heroes.iter_mut() {
+        match sprite.class().ability {
+            ClassAbility::SecondWind if health.value > 0 => {
+                health.value = health.heal(1);
+            }
+            ClassAbility::ShieldBlock => {
+                commands.entity(entity).insert(ShieldBlock);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    #[test]
+    fn test_every_class_has_a_starting_item() {
+        for sprite in PlayerSprite::iter() {
+            let class = sprite.class();
+            assert!(
+                class.starting_item().is_some(),
+                "{} has no item",
+                class.name
+            );
+            assert!(class.health > 0 && class.movement > 0);
+            assert!(class.max_health() > class.health);
+        }
+    }
+
+    #[test]
+    fn test_classes_have_distinct_stats() {
+        let stats = |class: &HeroClass| {
+            (
+                class.health,
+                class.movement,
+                class.speed,
+                class.starting_item,
+            )
+        };
+        for (i, class) in HERO_CLASSES.iter().enumerate() {
+            for other in HERO_CLASSES.iter().skip(i + 1) {
+                assert_ne!(
+                    stats(class),
+                    stats(other),
+                    "{} and {} share their stats",
+                    class.name,
+                    other.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_experience_levels_up() {
+        let mut experience = Experience::default();
+        assert_eq!(experience.gain(XP_PER_LEVEL - 1), 0);
+        assert_eq!(experience.gain(1), 1);
+        assert_eq!(experience.level, 1);
+        assert_eq!(experience.xp, 0);
+
+        // Enough for two more levels at once
+        assert_eq!(experience.gain(XP_PER_LEVEL * 5), 2);
+        assert_eq!(experience.unspent, 3);
+        assert_eq!(experience.xp, 0);
+    }
+}
@@ -1,22 +1,36 @@
+use bevy::ecs::query::QueryFilter;
+use bevy::ecs::system::SystemParam;
 use bevy::math::uvec2;
 use bevy::prelude::*;
 use bevy_trauma_shake::TraumaCommands;
+use rand::seq::SliceRandom;
+use rand::Rng;
 
 use crate::game::actors::spawn::SpawnAnimation;
 use crate::game::actors::ActorBundle;
 use crate::game::actors_list::PlayerActorList;
 use crate::game::assets::SfxKey;
 use crate::game::audio::sfx::PlaySfx;
+use crate::game::components::Trap;
 use crate::game::constants::*;
-use crate::game::cycle::{DayCycle, Season, TimeOfDay, Turn};
+use crate::game::cycle::calendar::Calendar;
+use crate::game::cycle::{DayClock, Season, TimeOfDay, Turn};
+use crate::game::daily::DailyChallenge;
+use crate::game::difficulty::Difficulty;
+use crate::game::game_mode::GameMode;
 use crate::game::level::Terrain;
 use crate::game::map::VillageMap;
+use crate::game::mutators::{ActiveMutators, Damage};
+use crate::game::rng::{GameRng, RngStream};
 use crate::game::tile_set::{tile_coord_translation, TileSet, TILE_ANCHOR};
+use crate::game::turn_order::{Side, TurnOrder};
 use crate::game::vfx::{FireOneShotVfx, OneShotVfx};
-use crate::path_finding::tiles::{Tile, TileDir};
+use crate::game::visitors::NightModifiers;
+use crate::path_finding::tiles::{Tile, TileDir, TileRect};
 use crate::screen::playing::GameState;
 use crate::screen::Screen;
 
+use super::class::ShieldBlock;
 use super::spawn::DespawnAnimation;
 use super::{Directions, EnemyActor, Health, IsAirborne, Movement};
 
@@ -25,6 +39,11 @@ pub struct EnemyActorsPlugin;
 impl Plugin for EnemyActorsPlugin {
     fn build(&self, app: &mut App) {
         app.init_state::<EnemyActionState>()
+            .init_resource::<NightWave>()
+            .add_systems(
+                OnEnter(Screen::Playing),
+                |mut night_wave: ResMut<NightWave>| night_wave.0 = None,
+            )
             .add_systems(OnEnter(TimeOfDay::Night), spawn_enemies)
             .add_systems(OnEnter(GameState::EnemyTurn), find_movement_path)
             .add_systems(
@@ -45,12 +64,19 @@ fn perform_attack(
     mut q_enemy_attacks: Query<(Entity, &mut EnemyAttack), With<EnemyActor>>,
     q_not_enemy_units: Query<(), Without<EnemyActor>>,
     mut q_health: Query<&mut Health>,
+    q_shields: Query<(), With<ShieldBlock>>,
     village_map: Res<VillageMap>,
     mut next_enemy_action_state: ResMut<NextState<EnemyActionState>>,
     time: Res<Time>,
     mut evw_oneshot_vfx: EventWriter<FireOneShotVfx>,
+    turn_order: Res<TurnOrder>,
+    damage: Damage,
 ) {
-    let Some((entity, mut enemy_attack)) = q_enemy_attacks.iter_mut().next() else {
+    // Enemies waiting for a later slot attack when it's their turn
+    let Some((entity, mut enemy_attack)) = q_enemy_attacks
+        .iter_mut()
+        .find(|(entity, _)| turn_order.is_acting(*entity))
+    else {
         next_enemy_action_state.set(EnemyActionState::Move);
         return;
     };
@@ -84,14 +110,15 @@ fn perform_attack(
     enemy_attack.factor += time.delta_seconds();
     if enemy_attack.factor >= ATK_ANIM_DURATION {
         // Deal damage
-        if let Some(mut health) = village_map
+        let target = village_map
             .actors
             .get(enemy_attack.tile)
             // Can only deal damage to non enemy units
-            .filter(|e| q_not_enemy_units.contains(*e))
-            .and_then(|e| q_health.get_mut(e).ok())
-        {
-            health.value = health.value.saturating_sub(1);
+            .filter(|e| q_not_enemy_units.contains(*e));
+        if let Some(target) = target.filter(|e| q_shields.contains(*e)) {
+            commands.entity(target).remove::<ShieldBlock>();
+        } else if let Some(mut health) = target.and_then(|e| q_health.get_mut(e).ok()) {
+            health.value = health.hurt(damage.dealt(Side::Enemies, Side::Heroes, 1));
         }
 
         commands.entity(entity).remove::<EnemyAttack>();
@@ -101,20 +128,30 @@ fn perform_attack(
 fn move_enemies(
     mut commands: Commands,
     mut q_enemy_units: Query<
-        (Entity, &mut Transform, &Directions, Option<&mut TilePath>),
+        (
+            Entity,
+            &mut Transform,
+            &mut Health,
+            &Directions,
+            Option<&IsAirborne>,
+            Option<&mut TilePath>,
+        ),
         With<EnemyActor>,
     >,
     q_not_enemy_units: Query<(), Without<EnemyActor>>,
     q_transforms: Query<&Transform, Without<EnemyActor>>,
+    q_traps: Query<(&Trap, &Transform), Without<EnemyActor>>,
+    mut evw_oneshot_vfx: EventWriter<FireOneShotVfx>,
     mut next_game_state: ResMut<NextState<GameState>>,
     mut next_enemy_action_state: ResMut<NextState<EnemyActionState>>,
     mut village_map: ResMut<VillageMap>,
     player_unit_list: Res<PlayerActorList>,
     turn: Res<Turn>,
     time: Res<Time>,
-    day_cycle: Res<DayCycle>,
+    clock: Res<DayClock>,
+    damage: Damage,
 ) {
-    if turn.0 != 0 && turn.0 % day_cycle.turns_per_day() == 0 {
+    if turn.0 != 0 && clock.turn_in_day(*turn) == 0 {
         // Next day starts, clear all enemy units
         for (enemy_entity, transform, ..) in q_enemy_units.iter() {
             commands
@@ -140,7 +177,7 @@ fn move_enemies(
         return;
     }
 
-    let Some((entity, mut transform, directions, path)) =
+    let Some((entity, mut transform, mut health, directions, is_airborne, path)) =
         q_enemy_units.iter_mut().find(|(.., path)| path.is_some())
     else {
         next_enemy_action_state.set(EnemyActionState::Attack);
@@ -156,22 +193,28 @@ fn move_enemies(
 
         if let Some(enemy_tile) = path.path.last() {
             // Already in the best tile, find surroundings to attack!
-            // for direction in enemy.
-            for direction in directions.0.iter() {
-                let attack_tile = enemy_tile.step(*direction);
-                let Some(attack_entity) = village_map.actors.get(attack_tile) else {
-                    continue;
-                };
-
-                if q_not_enemy_units.contains(attack_entity) {
-                    // Mark tile for attack in the next enemy turn.
-                    commands
-                        .entity(entity)
-                        .insert(EnemyAttack::new(attack_tile));
-
-                    // Only 1 object can be attacked at the same time
-                    break;
-                }
+            let footprint = village_map.actors.footprint(entity);
+            let attack_tile = TileRect::from_origin(*enemy_tile, footprint)
+                .into_iter()
+                .flat_map(|tile| {
+                    directions
+                        .0
+                        .iter()
+                        .map(move |direction| tile.step(*direction))
+                })
+                .find(|attack_tile| {
+                    village_map
+                        .actors
+                        .get(*attack_tile)
+                        .is_some_and(|attack_entity| q_not_enemy_units.contains(attack_entity))
+                });
+
+            // Only 1 object can be attacked at the same time
+            if let Some(attack_tile) = attack_tile {
+                // Mark tile for attack in the next enemy turn.
+                commands
+                    .entity(entity)
+                    .insert(EnemyAttack::new(attack_tile));
             }
         }
         return;
@@ -200,9 +243,75 @@ fn move_enemies(
         // Increment the index to move towards the next path
         path.index += 1;
         path.factor = 0.0;
+
+        // Flying enemies don't trigger traps
+        if is_airborne.is_some() {
+            return;
+        }
+
+        let tile = path.path[path.index];
+        let Some(trap) = spring_trap(
+            &mut commands,
+            &mut village_map,
+            &q_traps,
+            &mut evw_oneshot_vfx,
+            tile,
+            transform.translation,
+        ) else {
+            return;
+        };
+
+        health.value = health.hurt(damage.dealt(Side::Heroes, Side::Enemies, trap.damage));
+        if health.value == 0 {
+            commands.entity(entity).remove::<TilePath>();
+            return;
+        }
+
+        // Stop on the trap if nobody else is heading there
+        let footprint = village_map.actors.footprint(entity);
+        if trap.immobilize
+            && TileRect::from_origin(tile, footprint)
+                .into_iter()
+                .all(|tile| {
+                    village_map
+                        .actors
+                        .get(tile)
+                        .is_none_or(|occupant| occupant == entity)
+                })
+        {
+            let len = path.index + 1;
+            path.path.truncate(len);
+            village_map.actors.set(tile, entity);
+        }
     }
 }
 
+/// Spring the trap hidden on the tile, if there is one, under the enemy standing at `translation`.
+pub fn spring_trap<F: QueryFilter>(
+    commands: &mut Commands,
+    village_map: &mut VillageMap,
+    q_traps: &Query<(&Trap, &Transform), F>,
+    evw_oneshot_vfx: &mut EventWriter<FireOneShotVfx>,
+    tile: Tile,
+    translation: Vec3,
+) -> Option<Trap> {
+    let trap_entity = village_map.traps.remove(&tile)?;
+    let Ok((trap, trap_transform)) = q_traps.get(trap_entity) else {
+        return None;
+    };
+
+    commands
+        .entity(trap_entity)
+        .insert(DespawnAnimation::new(trap_transform.translation));
+    evw_oneshot_vfx.send(FireOneShotVfx(
+        OneShotVfx::BloodSplash,
+        Transform::from_translation(translation),
+    ));
+    commands.trigger(PlaySfx::Key(SfxKey::Hit));
+
+    Some(*trap)
+}
+
 fn find_movement_path(
     mut commands: Commands,
     mut q_enemy_units: Query<
@@ -210,11 +319,16 @@ fn find_movement_path(
         With<EnemyActor>,
     >,
     mut village_map: ResMut<VillageMap>,
+    turn_order: Res<TurnOrder>,
 ) {
     // Regenerate heat map to check for player units as well.
     village_map.generate_heat_map(|e| q_enemy_units.contains(e));
 
     for (entity, movement, directions, airborne) in q_enemy_units.iter_mut() {
+        if !turn_order.is_acting(entity) {
+            continue;
+        }
+
         let Some(enemy_tile) = village_map.actors.locate(entity) else {
             continue;
         };
@@ -226,15 +340,22 @@ fn find_movement_path(
             continue;
         };
 
-        let Some((path, _)) =
-            village_map.pathfind(&enemy_tile, &best_tile, &directions.0, is_airborne)
-        else {
+        let footprint = village_map.actors.footprint(entity);
+        let Some((path, _)) = village_map.pathfind_with_footprint(
+            &enemy_tile,
+            &best_tile,
+            &directions.0,
+            is_airborne,
+            footprint,
+        ) else {
             continue;
         };
 
         commands.entity(entity).insert(TilePath::new(path));
         village_map.actors.remove(enemy_tile);
-        village_map.actors.set(best_tile, entity);
+        village_map
+            .actors
+            .set_with_footprint(best_tile, entity, footprint);
     }
 }
 
@@ -243,6 +364,15 @@ fn spawn_enemies(
     mut village_map: ResMut<VillageMap>,
     season: Res<Season>,
     tile_set: Res<TileSet>,
+    mut night_modifiers: ResMut<NightModifiers>,
+    mut night_wave: ResMut<NightWave>,
+    difficulty: Res<Difficulty>,
+    clock: Res<DayClock>,
+    game_mode: Res<GameMode>,
+    calendar: Res<Calendar>,
+    wave_budget: WaveBudget,
+    mutators: Res<ActiveMutators>,
+    mut rng: ResMut<GameRng>,
 ) {
     debug_assert!(
         village_map.size.x() == village_map.size.y(),
@@ -255,35 +385,20 @@ fn spawn_enemies(
         return;
     }
 
-    let enemies = match *season {
-        Season::Summer => {
-            // 2 werewolfs
-            vec![EnemySpawn::WEREWOLF; 2]
-        }
-        Season::Autumn => {
-            // 2 werewolfs, 1 slime
-            vec![
-                EnemySpawn::WEREWOLF,
-                EnemySpawn::WEREWOLF,
-                EnemySpawn::SLIME,
-            ]
-        }
-        Season::Winter => {
-            // 2 werewolfs, 1 slime, 2 bats
-            vec![
-                EnemySpawn::WEREWOLF,
-                EnemySpawn::WEREWOLF,
-                EnemySpawn::SLIME,
-                EnemySpawn::BAT,
-                EnemySpawn::BAT,
-            ]
-        }
-    };
-
-    for enemy in enemies {
+    let modifiers = std::mem::take(&mut *night_modifiers);
+    let preset = difficulty.preset();
+    let rng = rng.stream(RngStream::Enemies);
+    night_wave.get_or_roll(rng, *season, &wave_budget);
+    let health_bonus = game_mode.enemy_health_bonus(clock.day, &calendar);
+
+    for enemy in night_wave.0.take().unwrap_or_default() {
+        let hit_points = mutators
+            .enemy_health(preset.enemy_health(enemy.hit_points + health_bonus))
+            .saturating_add_signed(modifiers.enemy_health)
+            .max(1);
         let mut tile_coord = Tile::ZERO;
         for _ in 0..SPAWN_TRIAL {
-            tile_coord = random_border_tile_coord(width as u32, ENEMY_SPAWN_RANGE)
+            tile_coord = random_border_tile_coord(rng, width as u32, ENEMY_SPAWN_RANGE)
                 .as_ivec2()
                 .into();
 
@@ -315,9 +430,9 @@ fn spawn_enemies(
                 ..default()
             },
             ActorBundle::<EnemyActor>::new(enemy.name, enemy.directions.to_vec())
-                .with_health(enemy.hit_points)
-                .with_health(enemy.hit_points)
-                .with_movement(enemy.movement),
+                .with_health(hit_points)
+                .with_movement(enemy.movement + preset.enemy_movement_bonus)
+                .with_speed(enemy.speed),
             SpawnAnimation::new(translation),
             StateScoped(Screen::Playing),
         ));
@@ -328,18 +443,91 @@ fn spawn_enemies(
     }
 }
 
+/// Enemies that can attack the village in the season and how likely each one is.
+pub fn wave_weights(season: Season) -> &'static [(EnemySpawn, u32)] {
+    match season {
+        Season::Summer => &[(EnemySpawn::WEREWOLF, 1)],
+        Season::Autumn => &[(EnemySpawn::WEREWOLF, 3), (EnemySpawn::SLIME, 1)],
+        Season::Winter => &[
+            (EnemySpawn::WEREWOLF, 3),
+            (EnemySpawn::SLIME, 2),
+            (EnemySpawn::BAT, 2),
+        ],
+    }
+}
+
+/// Spend the budget on enemies of the season until none is affordable anymore.
+pub fn roll_wave(
+    rng: &mut impl Rng,
+    season: Season,
+    mut budget: u32,
+    max_size: usize,
+) -> Vec<EnemySpawn> {
+    let mut wave = Vec::new();
+    while wave.len() < max_size {
+        let affordable: Vec<_> = wave_weights(season)
+            .iter()
+            .filter(|(enemy, _)| enemy.cost <= budget)
+            .collect();
+        let Ok((enemy, _)) = affordable.choose_weighted(rng, |(_, weight)| *weight) else {
+            break;
+        };
+
+        budget -= enemy.cost;
+        wave.push(enemy.clone());
+    }
+    wave
+}
+
+/// Everything deciding how many points are spent on tonight's wave.
+#[derive(SystemParam)]
+pub struct WaveBudget<'w> {
+    difficulty: Res<'w, Difficulty>,
+    clock: Res<'w, DayClock>,
+    challenge: Res<'w, DailyChallenge>,
+    mutators: Res<'w, ActiveMutators>,
+    game_mode: Res<'w, GameMode>,
+    calendar: Res<'w, Calendar>,
+}
+
+impl WaveBudget<'_> {
+    pub fn get(&self) -> u32 {
+        let budget = self.difficulty.preset().wave_budget(self.clock.day);
+        self.mutators
+            .wave_budget(self.challenge.wave_budget(budget))
+    }
+
+    pub fn max_size(&self) -> usize {
+        self.game_mode.max_wave_size(self.clock.day, &self.calendar)
+    }
+}
+
+/// Enemies attacking tonight, rolled once so that they can be foretold during the day.
+#[derive(Resource, Default, Debug)]
+pub struct NightWave(pub Option<Vec<EnemySpawn>>);
+
+impl NightWave {
+    pub fn get_or_roll(
+        &mut self,
+        rng: &mut impl Rng,
+        season: Season,
+        wave_budget: &WaveBudget,
+    ) -> &[EnemySpawn] {
+        self.0.get_or_insert_with(|| {
+            roll_wave(rng, season, wave_budget.get(), wave_budget.max_size())
+        })
+    }
+}
+
 /// Get a random coordinate that is at the border of the grid.
-pub fn random_border_tile_coord(width: u32, range: u32) -> UVec2 {
+pub fn random_border_tile_coord(rng: &mut impl Rng, width: u32, range: u32) -> UVec2 {
     let max_index = width - 1;
-    let side = rand::random::<u32>() % 4;
+    let side = rng.gen_range(0..4);
     // |---------------| -> width
     //             |---| -> range
     // == == == == .. ..
     // == == == == .. ..
-    let side_coord = uvec2(
-        rand::random::<u32>() % (width - range),
-        rand::random::<u32>() % range,
-    );
+    let side_coord = uvec2(rng.gen_range(0..width - range), rng.gen_range(0..range));
 
     // Convert side coordinate into tile coordinate by performing 2d rotations based on side.
     match side {
@@ -356,6 +544,9 @@ pub struct EnemySpawn {
     pub name: &'static str,
     pub hit_points: u32,
     pub movement: u32,
+    pub speed: u32,
+    /// Points of the wave budget the enemy takes up.
+    pub cost: u32,
     pub is_airborne: bool,
     pub directions: &'static [TileDir],
 }
@@ -365,6 +556,8 @@ impl EnemySpawn {
         name: "werewolf",
         hit_points: 3,
         movement: 3,
+        speed: 3,
+        cost: 2,
         is_airborne: false,
         directions: &TileDir::EDGES,
     };
@@ -372,6 +565,8 @@ impl EnemySpawn {
         name: "slime",
         hit_points: 4,
         movement: 2,
+        speed: 1,
+        cost: 3,
         is_airborne: false,
         directions: &TileDir::EDGES,
     };
@@ -379,6 +574,8 @@ impl EnemySpawn {
         name: "bat",
         hit_points: 2,
         movement: 4,
+        speed: 5,
+        cost: 2,
         is_airborne: true,
         directions: &TileDir::ALL,
     };
@@ -1,22 +1,34 @@
 use bevy::prelude::*;
+use rand::Rng;
 use strum::{EnumCount, IntoEnumIterator};
 use strum_macros::{AsRefStr, EnumCount, EnumIter};
 
 use crate::game::actors_list::PlayerActorList;
+use crate::game::components::Gate;
 use crate::game::cycle::EndTurn;
 use crate::game::inventory::{Inventory, MaxInventorySize};
+use crate::game::item::InventorySelection;
 use crate::game::map::VillageMap;
 use crate::game::picking::TilePressedEvent;
+use crate::game::rng::{GameRng, RngStream};
 use crate::game::selection::SelectedActor;
 use crate::game::tile_set::tile_coord_translation;
+use crate::game::undo::{TurnCommand, UndoStack};
 use crate::path_finding::tiles::TileDir;
 use crate::screen::playing::GameState;
 
+use super::class::Experience;
 use super::*;
 
 pub const INITIAL_PLAYER_UNITS: usize = 2;
 
-pub fn spawn_player_unit(commands: &mut Commands, name: String) -> Entity {
+pub fn spawn_player_unit(commands: &mut Commands, name: String, sprite: PlayerSprite) -> Entity {
+    let class = sprite.class();
+    let inventory = class
+        .starting_item()
+        .map(Inventory::with_item)
+        .unwrap_or_default();
+
     commands
         .spawn((
             SpatialBundle {
@@ -24,11 +36,13 @@ pub fn spawn_player_unit(commands: &mut Commands, name: String) -> Entity {
                 ..default()
             },
             ActorBundle::<PlayerActor>::new(&name, TileDir::ALL.into())
-                .with_health(3)
-                .with_movement(3),
+                .with_health(class.health)
+                .with_movement(class.movement)
+                .with_speed(class.speed),
             MaxInventorySize(3),
-            Inventory::default(),
-            PlayerSprite::random(),
+            inventory,
+            Experience::default(),
+            sprite,
         ))
         .id()
 }
@@ -36,20 +50,23 @@ pub fn spawn_player_unit(commands: &mut Commands, name: String) -> Entity {
 pub fn add_starting_player_units(
     mut available_names: ResMut<AvailableActorNames>,
     mut player_unit_list: ResMut<PlayerActorList>,
+    mut rng: ResMut<GameRng>,
     mut commands: Commands,
 ) {
+    let rng = rng.stream(RngStream::Heroes);
     player_unit_list.0.clear();
+    *available_names = AvailableActorNames::shuffled(rng);
     for _ in 0..INITIAL_PLAYER_UNITS {
         let name = available_names.next_name();
-        let id = spawn_player_unit(&mut commands, name);
+        let id = spawn_player_unit(&mut commands, name, PlayerSprite::random(rng));
         player_unit_list.push(id);
     }
 }
 
 pub fn move_unit(
-    reset_event_reader: EventReader<ClearUndoEvent>,
     mut event_reader: EventReader<TilePressedEvent>,
-    selected_unit: Res<SelectedActor>,
+    mut selected_unit: ResMut<SelectedActor>,
+    inventory_selection: Res<InventorySelection>,
     mut village_map: ResMut<VillageMap>,
     mut turn_state_query: Query<
         (
@@ -62,17 +79,17 @@ pub fn move_unit(
         With<PlayerActor>,
     >,
     player_actors: Query<Entity, With<PlayerActor>>,
+    gates: Query<Entity, With<Gate>>,
+    mut undo_stack: ResMut<UndoStack>,
 ) {
-    if !reset_event_reader.is_empty() {
-        for (mut turn_state, ..) in turn_state_query.iter_mut() {
-            turn_state.previous_position = None;
-        }
-    }
-
     if let Some(TilePressedEvent(target)) = event_reader.read().last() {
         let Some(selected) = selected_unit.entity else {
             return;
         };
+        // The press lands the aimed item instead
+        if inventory_selection.is_throwing() {
+            return;
+        }
 
         let Ok((mut turn_state, movement, mut vis, mut sprite, mut transform)) =
             turn_state_query.get_mut(selected)
@@ -80,16 +97,15 @@ pub fn move_unit(
             return;
         };
 
-        if (turn_state.used_move && turn_state.previous_position.is_none()) || movement.0 == 0 {
+        if turn_state.used_move || movement.0 == 0 {
             return;
         }
 
-        let Some(current_pos) = turn_state.previous_position.or_else(|| {
-            village_map
-                .actors
-                .locate(selected)
-                .filter(|pos| *pos != *target)
-        }) else {
+        let Some(current_pos) = village_map
+            .actors
+            .locate(selected)
+            .filter(|pos| *pos != *target)
+        else {
             return;
         };
 
@@ -97,7 +113,8 @@ pub fn move_unit(
             return;
         }
 
-        let allied_actors: Vec<Entity> = player_actors.iter().collect();
+        // Heroes can walk through gates
+        let allied_actors: Vec<Entity> = player_actors.iter().chain(gates.iter()).collect();
 
         if village_map
             .flood(
@@ -109,7 +126,11 @@ pub fn move_unit(
             )
             .contains(target)
         {
-            turn_state.previous_position = Some(current_pos);
+            undo_stack.push(TurnCommand::Move {
+                hero: selected,
+                from: current_pos,
+                to: *target,
+            });
             village_map.actors.set(*target, selected);
             turn_state.used_move = true;
             transform.translation =
@@ -118,6 +139,8 @@ pub fn move_unit(
 
             *vis = Visibility::Inherited;
             sprite.color.set_alpha(1.0);
+            // The hero's movement range is used up
+            selected_unit.set_changed();
         }
     }
 }
@@ -151,8 +174,8 @@ impl PlayerSprite {
         self.as_ref().to_lowercase()
     }
 
-    pub fn random() -> Self {
-        let index = rand::random::<usize>() % Self::COUNT;
+    pub fn random(rng: &mut impl Rng) -> Self {
+        let index = rng.gen_range(0..Self::COUNT);
         Self::iter().nth(index).unwrap()
     }
 }
@@ -43,6 +43,10 @@ impl Health {
 #[derive(Component, Copy, Clone, Debug, Deref, DerefMut, PartialEq)]
 pub struct Movement(pub u32);
 
+/// Initiative of the actor, faster actors act first when turns go by initiative.
+#[derive(Component, Copy, Clone, Debug, Deref, DerefMut, PartialEq)]
+pub struct Speed(pub u32);
+
 // pub struct StatsBundle {
 //     health: Health,
 //     movement: Movement,
@@ -99,6 +99,9 @@ pub struct ItemSlotIndex(pub usize);
 #[derive(Component)]
 pub struct SellItemButton;
 
+#[derive(Component)]
+pub struct GiveItemButton;
+
 pub fn inventory_list_layout(ui: &mut UiBuilder<Entity>) -> Vec<Entity> {
     let mut out = vec![];
     ui.column(|ui| {
@@ -134,6 +137,7 @@ pub fn inventory_list_layout(ui: &mut UiBuilder<Entity>) -> Vec<Entity> {
             ui.row(|ui| {
                 ui.style()
                     .position_type(PositionType::Absolute)
+                    .column_gap(Val::Px(4.))
                     .margin(UiRect::top(Val::Percent(50.)));
                 ui.container(ButtonBundle::default(), |ui| {
                     ui.label(LabelConfig::from("Sell item"))
@@ -151,6 +155,22 @@ pub fn inventory_list_layout(ui: &mut UiBuilder<Entity>) -> Vec<Entity> {
                 .style()
                 .padding(UiRect::all(Val::Px(10.)))
                 .border_radius(BorderRadius::all(Val::Px(5.)));
+                ui.container(ButtonBundle::default(), |ui| {
+                    ui.label(LabelConfig::from("Give item"))
+                        .style()
+                        .font_size(LABEL_SIZE);
+                })
+                .insert((
+                    InteractionPalette {
+                        none: css::TEAL.into(),
+                        hovered: css::DARK_CYAN.into(),
+                        pressed: css::DARK_SLATE_GRAY.into(),
+                    },
+                    GiveItemButton,
+                ))
+                .style()
+                .padding(UiRect::all(Val::Px(10.)))
+                .border_radius(BorderRadius::all(Val::Px(5.)));
             });
         });
     });
@@ -239,7 +259,7 @@ pub fn update_inventory_icons(
                     commands
                         .ui_builder(*c)
                         .style()
-                        .image(ImageSource::Path(format!("icons/{}.png", item.name)));
+                        .image(ImageSource::Path(format!("icons/{}.png", item.icon)));
                 } else {
                     commands
                         .ui_builder(*c)
@@ -1,6 +1,8 @@
 use bevy::prelude::*;
 use bevy::utils::HashSet;
 
+use super::resources::ResourceBundle;
+
 #[derive(Debug, Copy, Clone)]
 pub enum Ability {
     /// Intagible actors can move through all other actors, walls and buildings.
@@ -38,8 +40,13 @@ pub struct GroundTileLayer;
 #[derive(Component, Debug)]
 pub struct ActorTileLayer;
 
+/// Resources a structure adds to the stock every dawn.
+#[derive(Component, Debug)]
+pub struct Production(pub ResourceBundle);
+
+/// Resources a structure takes from the stock every dawn.
 #[derive(Component, Debug)]
-pub struct Income(pub u32);
+pub struct Consumption(pub ResourceBundle);
 
 #[derive(Component, Debug)]
 pub struct PopulationCapacity(pub u32);
@@ -47,6 +54,22 @@ pub struct PopulationCapacity(pub u32);
 #[derive(Component, Debug)]
 pub struct RemainingConstructionTurns(pub u32);
 
+/// Structure is torn down once this reaches zero.
+#[derive(Component, Debug)]
+pub struct RemainingDemolitionTurns(pub u32);
+
+/// Number of upgrades applied to a structure, missing means none.
+#[derive(Component, Debug, Default, Copy, Clone)]
+pub struct StructureTier(pub u32);
+
+/// Structure moves to the next [`StructureTier`] once this reaches zero.
+#[derive(Component, Debug)]
+pub struct RemainingUpgradeTurns(pub u32);
+
+/// Workers busy repairing a structure, freed at dawn.
+#[derive(Component, Debug)]
+pub struct RepairWorkers(pub u32);
+
 #[derive(Component)]
 pub struct BuildingProgressLabel;
 
@@ -63,3 +86,24 @@ pub struct Blacksmith;
 
 #[derive(Component)]
 pub struct ArcherTower;
+
+/// Marker for cheap defensive structures that don't count as village buildings.
+#[derive(Component)]
+pub struct Defence;
+
+#[derive(Component)]
+pub struct Wall;
+
+/// Blocks enemies like a wall, but player actors can move through it.
+#[derive(Component)]
+pub struct Gate;
+
+/// A hidden trap that is triggered when a grounded enemy enters its tile.
+/// Traps live in [`VillageMap::traps`](super::map::VillageMap::traps) instead of the actor layer
+/// so that enemy pathfinding and heat maps ignore them.
+#[derive(Component, Debug, Copy, Clone)]
+pub struct Trap {
+    pub damage: u32,
+    /// Stops the enemy's movement on the trap tile.
+    pub immobilize: bool,
+}
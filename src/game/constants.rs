@@ -1,15 +1,50 @@
-use bevy::color::palettes::css::GREEN_YELLOW;
+use bevy::color::palettes::css::{GRAY, GREEN_YELLOW, ORANGE_RED};
 use bevy::color::palettes::tailwind::YELLOW_300;
 use bevy::ui::Val;
 use bevy::ui::ZIndex;
 
-//pub const INITIAL_GOLD: u32 = 50;
-pub const INITIAL_GOLD: u32 = 1000;
-//pub const INITIAL_POPULATION: u32 = 25;
-pub const INITIAL_POPULATION: u32 = 125;
-
 pub const HOUSE_POPULATION_CAPACITY: u32 = 10;
 
+pub const WALL_HEALTH: u32 = 4;
+
+/// Percentage of the resource cost returned after demolishing a structure.
+pub const DEMOLISH_REFUND_PERCENT: u32 = 50;
+
+pub const REPAIR_COST_PER_HEALTH: u32 = 10;
+
+pub const REPAIR_WORKERS: u32 = 5;
+
+/// Farmers needed to fully staff a farm.
+pub const FARM_JOB_SLOTS: u32 = 4;
+/// Gatherers needed to fully staff a lumber camp or quarry.
+pub const GATHERING_JOB_SLOTS: u32 = 3;
+/// Villagers needed to man an archer tower.
+pub const TOWER_GUARDS: u32 = 2;
+/// Rook distance a manned tower can shoot.
+pub const TOWER_RANGE: i32 = 3;
+pub const TOWER_DAMAGE: u32 = 1;
+/// Villagers arriving every dawn when nobody is starving.
+pub const POPULATION_GROWTH: std::ops::Range<u32> = 5..15;
+/// One extra villager arrives at dawn for every this many resting villagers.
+pub const RESTING_PER_NEWCOMER: u32 = 5;
+
+/// Days between merchant visits.
+pub const MERCHANT_VISIT_INTERVAL: u32 = 3;
+/// Days the merchant stays in the village, including the day of arrival.
+pub const MERCHANT_STAY_DAYS: u32 = 2;
+/// Merchant prices vary by up to this much from the item cost.
+pub const MERCHANT_PRICE_FLUCTUATION_PERCENT: i32 = 25;
+/// Cost of the first reroll of the merchant's stock, every reroll adds to it.
+pub const MERCHANT_REROLL_COST: u32 = 15;
+/// Items the village can keep for heroes without room to carry them.
+pub const VILLAGE_STASH_SIZE: usize = 6;
+
+/// Chance of a visitor showing up at dawn.
+pub const VISITOR_CHANCE_PERCENT: u32 = 50;
+
+/// Days kept in the ledger history.
+pub const LEDGER_HISTORY_DAYS: usize = 7;
+
 pub const HOUSE_COST: u32 = 25;
 pub const TAVERN_COST: u32 = 25;
 
@@ -20,23 +55,51 @@ pub const BUILDING_COSTS_PANEL_Z_INDEX: ZIndex = ZIndex::Global(10);
 pub const TEXT_SIZE: f32 = 14.;
 pub const BIG_TEXT_SIZE: f32 = 30.;
 
-pub const UPGRADE_COST: u32 = 20;
+/// Experience a hero gets for finishing off an enemy.
+pub const XP_PER_KILL: u32 = 10;
+/// Experience needed for the first level up, every level needs this much more.
+pub const XP_PER_LEVEL: u32 = 20;
+
+/// Damage of a kick, a unit kicked into something else takes it again.
+pub const KICK_DAMAGE: u32 = 1;
+/// Health restored by first aid and recovering.
+pub const ACTION_HEAL: u32 = 1;
 
 pub const TAVERN_FONT_SIZE: f32 = 20.;
 
-pub const RECRUIT_COST: u32 = 40;
+/// Heroes the village can have at once.
+pub const MAX_HEROES: usize = 5;
+/// Mercenaries waiting at the tavern each day.
+pub const TAVERN_ROSTER_SIZE: usize = 3;
+/// Unpaid nights a mercenary puts up with, paid nights win it back.
+pub const MAX_LOYALTY: u32 = 3;
 
 pub const SLOT_COST: u32 = 0;
+/// Highest movement, health or item slots a level up can raise a hero to.
+pub const MAX_UPGRADED_STAT: u32 = 5;
+
+/// Blacksmith price to restore a single use of an item.
+pub const ITEM_RECHARGE_COST: u32 = 10;
+/// Blacksmith price to upgrade an item's damage or range.
+pub const ITEM_UPGRADE_COST: u32 = 30;
 
 pub const UNIT_LIST_ZINDEX: ZIndex = ZIndex::Global(150);
 
 pub const CURSOR_COLOR: bevy::prelude::Srgba = YELLOW_300;
 
 pub const DEPLOYMENT_ZONE_COLOR: bevy::prelude::Srgba = GREEN_YELLOW;
+/// Tiles within an item's range that something stands in the way of.
+pub const BLOCKED_TILE_COLOR: bevy::prelude::Srgba = GRAY;
+/// Tiles an item would affect if used on the tile under the cursor.
+pub const AREA_PREVIEW_COLOR: bevy::prelude::Srgba = ORANGE_RED;
 
 /// Distance from border that the enemy will spawn in.
 pub const ENEMY_SPAWN_RANGE: u32 = 2;
 /// Claw animation extra duration.
 pub const ATK_ANIM_DURATION: f32 = 1.0;
 pub const SPAWN_TRIAL: usize = 10;
+/// Enemies in a wave however big the budget, the map border only fits so many.
+pub const MAX_WAVE_SIZE: usize = 12;
+/// Room for more enemies in endless waves with every year survived.
+pub const ENDLESS_WAVE_GROWTH: usize = 4;
 pub const ENEMY_MOVE_SPEED: f32 = 4.0;
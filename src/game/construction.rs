@@ -1,10 +1,14 @@
 use bevy::color::palettes::css;
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy::utils::HashMap;
 use sickle_ui::prelude::*;
 use strum::IntoEnumIterator;
 use strum_macros::{AsRefStr, EnumIter};
 
+use crate::path_finding::tiles::Tile;
+use crate::path_finding::tiles::TileDim;
+use crate::path_finding::tiles::TileRect;
 use crate::screen::Screen;
 use crate::ui::prelude::InteractionPalette;
 
@@ -17,25 +21,44 @@ use super::components::ArcherTower;
 use super::components::Blacksmith;
 use super::components::BuildingProgressLabel;
 use super::components::ConstructionWorkers;
+use super::components::Consumption;
+use super::components::Defence;
+use super::components::Gate;
 use super::components::House;
+use super::components::Production;
 use super::components::RemainingConstructionTurns;
 use super::components::Tavern;
+use super::components::Trap;
+use super::components::Wall;
 use super::constants::BIG_TEXT_SIZE;
+use super::constants::FARM_JOB_SLOTS;
+use super::constants::GATHERING_JOB_SLOTS;
+use super::constants::HOUSE_POPULATION_CAPACITY;
 use super::constants::ICON_SIZE;
 use super::constants::TEXT_SIZE;
+use super::constants::TOWER_GUARDS;
+use super::constants::WALL_HEALTH;
 use super::events::EndDayTurn;
 use super::events::SelectStructureTypeEvent;
 use super::game_params::Game;
+use super::ledger::Ledger;
+use super::ledger::LedgerButton;
+use super::ledger::LedgerSource;
 use super::map::VillageMap;
 use super::picking::PickableTile;
 use super::picking::TilePressedEvent;
+use super::resources::ResourceBundle;
+use super::resources::ResourceKind;
 use super::resources::SelectedStructueType;
-use super::resources::VillageEmployment;
-use super::resources::VillageGold;
+use super::resources::VillageJobs;
 use super::resources::VillagePopulation;
-use super::tile_set::tile_coord_translation;
+use super::resources::VillageStock;
+use super::resources::VillagerJob;
+use super::tile_set::footprint_translation;
 use super::tile_set::TileSet;
 use super::tile_set::TILE_ANCHOR;
+use super::undo::TurnCommand;
+use super::undo::UndoStack;
 
 #[derive(Component, EnumIter, AsRefStr, Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum StructureType {
@@ -43,6 +66,13 @@ pub enum StructureType {
     Tavern,
     ArcherTower,
     Blacksmith,
+    Farm,
+    LumberCamp,
+    Quarry,
+    Wall,
+    Gate,
+    BearTrap,
+    Landmine,
 }
 
 impl StructureType {
@@ -52,6 +82,13 @@ impl StructureType {
             StructureType::Tavern => "Tavern",
             StructureType::ArcherTower => "Tower",
             StructureType::Blacksmith => "Blacksmith",
+            StructureType::Farm => "Farm",
+            StructureType::LumberCamp => "Lumber Camp",
+            StructureType::Quarry => "Quarry",
+            StructureType::Wall => "Wall",
+            StructureType::Gate => "Gate",
+            StructureType::BearTrap => "Bear Trap",
+            StructureType::Landmine => "Landmine",
         }
     }
 
@@ -61,6 +98,124 @@ impl StructureType {
             StructureType::Tavern => "tavern",
             StructureType::ArcherTower => "tower",
             StructureType::Blacksmith => "blacksmith",
+            StructureType::Farm => "block_green",
+            StructureType::LumberCamp => "house",
+            StructureType::Quarry => "block_grey",
+            StructureType::Wall => "block_grey",
+            StructureType::Gate => "block_orange",
+            StructureType::BearTrap => "border_thick",
+            StructureType::Landmine => "border",
+        }
+    }
+
+    /// Structure a level object with this tile texture stands for, the first one using it.
+    pub fn from_tile_texture(texture: &str) -> Option<Self> {
+        Self::iter().find(|structure_type| structure_type.tile_texture() == texture)
+    }
+
+    /// Tiles covered by the structure, anchored at its minimum corner.
+    pub fn footprint(&self) -> TileDim {
+        match self {
+            StructureType::Tavern | StructureType::Blacksmith => TileDim(2, 1),
+            _ => TileDim::ONE,
+        }
+    }
+
+    /// Image shown on the build button.
+    pub fn icon_path(&self) -> String {
+        match self {
+            StructureType::House
+            | StructureType::Tavern
+            | StructureType::ArcherTower
+            | StructureType::Blacksmith => format!("icons/{}.png", self.tile_texture()),
+            _ => format!("tiles/{}.png", self.tile_texture()),
+        }
+    }
+
+    /// Sprite tint, used to tell apart structures that share a tile texture.
+    pub fn tint(&self) -> Color {
+        match self {
+            StructureType::LumberCamp => css::PERU.into(),
+            StructureType::Quarry => css::DIM_GRAY.into(),
+            StructureType::BearTrap => css::ORANGE.into(),
+            StructureType::Landmine => css::RED.into(),
+            _ => Color::WHITE,
+        }
+    }
+
+    /// Resources added to the stock every dawn, scaled by how well the structure is staffed
+    /// if it has [`job_slots`](Self::job_slots).
+    pub fn production(&self) -> ResourceBundle {
+        match self {
+            StructureType::Farm => ResourceBundle::EMPTY.with(ResourceKind::Food, 10),
+            StructureType::LumberCamp => ResourceBundle::EMPTY.with(ResourceKind::Wood, 6),
+            StructureType::Quarry => ResourceBundle::EMPTY.with(ResourceKind::Stone, 4),
+            _ => ResourceBundle::EMPTY,
+        }
+    }
+
+    /// Resources taken from the stock every dawn.
+    pub fn consumption(&self) -> ResourceBundle {
+        match self {
+            StructureType::House => ResourceBundle::EMPTY.with(ResourceKind::Food, 2),
+            StructureType::Tavern => ResourceBundle::EMPTY.with(ResourceKind::Food, 4),
+            StructureType::ArcherTower => ResourceBundle::EMPTY.with(ResourceKind::Wood, 1),
+            StructureType::Blacksmith => ResourceBundle::EMPTY.with(ResourceKind::Wood, 2),
+            _ => ResourceBundle::EMPTY,
+        }
+    }
+
+    /// Job and the number of villagers needed to fully staff the structure.
+    pub fn job_slots(&self) -> Option<(VillagerJob, u32)> {
+        match self {
+            StructureType::Farm => Some((VillagerJob::Farming, FARM_JOB_SLOTS)),
+            StructureType::LumberCamp | StructureType::Quarry => {
+                Some((VillagerJob::Gathering, GATHERING_JOB_SLOTS))
+            }
+            StructureType::ArcherTower => Some((VillagerJob::TowerDuty, TOWER_GUARDS)),
+            _ => None,
+        }
+    }
+
+    /// Name of the structure after `tier` upgrades.
+    pub fn tier_name(&self, tier: u32) -> &str {
+        match tier.checked_sub(1) {
+            Some(index) => self.upgrades()[index as usize].name,
+            None => self.name(),
+        }
+    }
+
+    /// Upgrade path in order, the first entry upgrades the base structure.
+    pub fn upgrades(&self) -> &'static [StructureUpgrade] {
+        match self {
+            StructureType::House => &HOUSE_UPGRADES,
+            StructureType::ArcherTower => &TOWER_UPGRADES,
+            StructureType::Wall => &WALL_UPGRADES,
+            _ => &[],
+        }
+    }
+
+    /// Next upgrade for a structure that already has `tier` upgrades.
+    pub fn next_upgrade(&self, tier: u32) -> Option<&'static StructureUpgrade> {
+        self.upgrades().get(tier as usize)
+    }
+
+    /// Walls, gates and traps, which don't count as village buildings.
+    pub fn is_defence(&self) -> bool {
+        matches!(self, StructureType::Wall | StructureType::Gate) || self.trap().is_some()
+    }
+
+    pub fn trap(&self) -> Option<Trap> {
+        match self {
+            StructureType::BearTrap => Some(Trap {
+                damage: 1,
+                immobilize: true,
+            }),
+            StructureType::Landmine => Some(Trap {
+                damage: 2,
+                immobilize: false,
+            }),
+            _ => None,
         }
     }
 }
@@ -74,11 +229,89 @@ pub struct CancelBuildButton;
 pub struct StructureCost {
     pub turns: u32,
     pub workers: u32,
-    pub gold: u32,
+    pub resources: ResourceBundle,
     /// only one of these structures can be built
     pub is_exclusive: bool,
 }
 
+/// Stats a structure gains when upgraded.
+pub struct StructureUpgrade {
+    pub name: &'static str,
+    pub cost: StructureCost,
+    pub health: u32,
+    pub population_capacity: u32,
+    pub production: ResourceBundle,
+}
+
+const HOUSE_UPGRADES: [StructureUpgrade; 2] = [
+    StructureUpgrade {
+        name: "Manor",
+        cost: StructureCost {
+            turns: 1,
+            workers: 10,
+            resources: ResourceBundle::gold(75).with(ResourceKind::Wood, 20),
+            is_exclusive: false,
+        },
+        health: 3,
+        population_capacity: HOUSE_POPULATION_CAPACITY * 2,
+        production: ResourceBundle::gold(5),
+    },
+    StructureUpgrade {
+        name: "Estate",
+        cost: StructureCost {
+            turns: 2,
+            workers: 15,
+            resources: ResourceBundle::gold(150)
+                .with(ResourceKind::Wood, 30)
+                .with(ResourceKind::Stone, 20),
+            is_exclusive: false,
+        },
+        health: 4,
+        population_capacity: HOUSE_POPULATION_CAPACITY * 3,
+        production: ResourceBundle::gold(10),
+    },
+];
+
+const TOWER_UPGRADES: [StructureUpgrade; 2] = [
+    StructureUpgrade {
+        name: "Stone Tower",
+        cost: StructureCost {
+            turns: 1,
+            workers: 5,
+            resources: ResourceBundle::gold(50).with(ResourceKind::Stone, 15),
+            is_exclusive: false,
+        },
+        health: 4,
+        population_capacity: HOUSE_POPULATION_CAPACITY,
+        production: ResourceBundle::EMPTY,
+    },
+    StructureUpgrade {
+        name: "Fortified Tower",
+        cost: StructureCost {
+            turns: 2,
+            workers: 10,
+            resources: ResourceBundle::gold(100).with(ResourceKind::Stone, 30),
+            is_exclusive: false,
+        },
+        health: 6,
+        population_capacity: HOUSE_POPULATION_CAPACITY,
+        production: ResourceBundle::EMPTY,
+    },
+];
+
+const WALL_UPGRADES: [StructureUpgrade; 1] = [StructureUpgrade {
+    name: "Stone Wall",
+    cost: StructureCost {
+        turns: 1,
+        workers: 2,
+        resources: ResourceBundle::gold(10).with(ResourceKind::Stone, 10),
+        is_exclusive: false,
+    },
+    health: WALL_HEALTH * 2,
+    population_capacity: 0,
+    production: ResourceBundle::EMPTY,
+}];
+
 #[derive(Resource, Deref)]
 pub struct StructureCosts(pub HashMap<StructureType, StructureCost>);
 
@@ -90,7 +323,7 @@ impl Default for StructureCosts {
                 StructureCost {
                     turns: 1,
                     workers: 10,
-                    gold: 50,
+                    resources: ResourceBundle::gold(50).with(ResourceKind::Wood, 10),
                     is_exclusive: false,
                 },
             ),
@@ -99,7 +332,7 @@ impl Default for StructureCosts {
                 StructureCost {
                     turns: 1,
                     workers: 10,
-                    gold: 75,
+                    resources: ResourceBundle::gold(75).with(ResourceKind::Wood, 15),
                     is_exclusive: true,
                 },
             ),
@@ -108,7 +341,9 @@ impl Default for StructureCosts {
                 StructureCost {
                     turns: 1,
                     workers: 5,
-                    gold: 25,
+                    resources: ResourceBundle::gold(25)
+                        .with(ResourceKind::Wood, 10)
+                        .with(ResourceKind::Stone, 5),
                     is_exclusive: false,
                 },
             ),
@@ -117,10 +352,73 @@ impl Default for StructureCosts {
                 StructureCost {
                     turns: 1,
                     workers: 10,
-                    gold: 125,
+                    resources: ResourceBundle::gold(125).with(ResourceKind::Stone, 15),
                     is_exclusive: true,
                 },
             ),
+            (
+                StructureType::Farm,
+                StructureCost {
+                    turns: 1,
+                    workers: 5,
+                    resources: ResourceBundle::gold(30).with(ResourceKind::Wood, 10),
+                    is_exclusive: false,
+                },
+            ),
+            (
+                StructureType::LumberCamp,
+                StructureCost {
+                    turns: 1,
+                    workers: 5,
+                    resources: ResourceBundle::gold(30),
+                    is_exclusive: false,
+                },
+            ),
+            (
+                StructureType::Quarry,
+                StructureCost {
+                    turns: 1,
+                    workers: 5,
+                    resources: ResourceBundle::gold(40).with(ResourceKind::Wood, 10),
+                    is_exclusive: false,
+                },
+            ),
+            (
+                StructureType::Wall,
+                StructureCost {
+                    turns: 1,
+                    workers: 2,
+                    resources: ResourceBundle::gold(5).with(ResourceKind::Stone, 5),
+                    is_exclusive: false,
+                },
+            ),
+            (
+                StructureType::Gate,
+                StructureCost {
+                    turns: 1,
+                    workers: 3,
+                    resources: ResourceBundle::gold(10).with(ResourceKind::Wood, 5),
+                    is_exclusive: false,
+                },
+            ),
+            (
+                StructureType::BearTrap,
+                StructureCost {
+                    turns: 1,
+                    workers: 1,
+                    resources: ResourceBundle::gold(10),
+                    is_exclusive: false,
+                },
+            ),
+            (
+                StructureType::Landmine,
+                StructureCost {
+                    turns: 1,
+                    workers: 1,
+                    resources: ResourceBundle::gold(15),
+                    is_exclusive: false,
+                },
+            ),
         ]
         .into_iter()
         .collect();
@@ -135,6 +433,14 @@ pub struct BuildingPanel;
 #[derive(Component)]
 pub struct StructureDetail;
 
+/// Container for the actions of the structure selected on the map.
+#[derive(Component)]
+pub struct StructureActions;
+
+/// Container for the villager job panel.
+#[derive(Component)]
+pub struct JobAssignments;
+
 pub fn building_panel_layout(mut commands: Commands) {
     commands.ui_builder(UiRoot).row(|ui| {
         ui.insert((BuildingPanel, StateScoped(Screen::Playing)));
@@ -151,7 +457,27 @@ pub fn building_panel_layout(mut commands: Commands) {
             ui.row(|_| {}).style().height(Val::Px(60.));
 
             ui.row(|ui| {
-                ui.style().justify_content(JustifyContent::End);
+                ui.style()
+                    .justify_content(JustifyContent::End)
+                    .align_items(AlignItems::Center)
+                    .column_gap(Val::Px(10.));
+                ui.container(ButtonBundle::default(), |ui| {
+                    ui.label(LabelConfig::from("Ledger"))
+                        .style()
+                        .font_size(TEXT_SIZE);
+                })
+                .insert((
+                    LedgerButton,
+                    InteractionPalette {
+                        none: css::BLACK.into(),
+                        hovered: css::TEAL.into(),
+                        pressed: css::INDIAN_RED.into(),
+                    },
+                ))
+                .style()
+                .padding(UiRect::all(Val::Px(4.)))
+                .border(UiRect::all(Val::Px(2.)))
+                .border_color(Color::WHITE);
                 ui.label(LabelConfig::from("Build"))
                     .style()
                     .font_size(BIG_TEXT_SIZE);
@@ -180,7 +506,7 @@ pub fn building_panel_layout(mut commands: Commands) {
 
                         ui.column(|ui| {
                             ui.style().justify_content(JustifyContent::SpaceBetween);
-                            ui.icon("icons/".to_owned() + building_type.tile_texture() + ".png")
+                            ui.icon(building_type.icon_path())
                                 .style()
                                 .height(Val::Px(30.))
                                 .width(Val::Px(30.));
@@ -201,6 +527,16 @@ pub fn building_panel_layout(mut commands: Commands) {
                     ui.style().justify_content(JustifyContent::End);
                     ui.insert(StructureDetail);
                 });
+
+                ui.row(|ui| {
+                    ui.style().justify_content(JustifyContent::End);
+                    ui.insert(StructureActions);
+                });
+
+                ui.row(|ui| {
+                    ui.style().justify_content(JustifyContent::End);
+                    ui.insert(JobAssignments);
+                });
             });
         });
     });
@@ -253,11 +589,14 @@ pub fn update_structure_detail(
         });
 
         ui.style().row_gap(Val::Px(10.));
-        for (icon, value) in [
-            ("icons/gold_coins.png", cost.gold.to_string()),
+        let resources = cost
+            .resources
+            .iter()
+            .map(|(kind, amount)| (kind.icon_path(), amount.to_string()));
+        for (icon, value) in resources.chain([
             ("icons/population.png", cost.workers.to_string()),
             ("icons/hourglass.png", cost.turns.to_string()),
-        ] {
+        ]) {
             ui.row(|ui| {
                 ui.style().justify_content(JustifyContent::End);
                 ui.icon(icon).style().width(ICON_SIZE).height(ICON_SIZE);
@@ -330,112 +669,163 @@ pub fn update_build_panel(
 #[derive(Component)]
 pub struct BuildingSite;
 
-pub fn spawn_in_progress_building(
-    mut commands: Commands,
-    mut events: EventReader<TilePressedEvent>,
-    //mut village_map: ResMut<VillageMap>,
-    tile_set: Res<TileSet>,
-    selected_structure_type: Res<SelectedStructueType>,
-    structure_cost: Res<StructureCosts>,
-    population: Res<VillagePopulation>,
-    mut working_population: ResMut<VillageEmployment>,
-    mut gold: ResMut<VillageGold>,
-    structure_query: Query<&StructureType>,
-    mut game: Game,
-) {
-    let Some(TilePressedEvent(tile)) = events.read().last() else {
-        return;
-    };
+/// Everything needed to place building sites and take them back down.
+#[derive(SystemParam)]
+pub struct BuildingPlacement<'w, 's> {
+    commands: Commands<'w, 's>,
+    tile_set: Res<'w, TileSet>,
+    structure_cost: Res<'w, StructureCosts>,
+    population: Res<'w, VillagePopulation>,
+    jobs: ResMut<'w, VillageJobs>,
+    stock: ResMut<'w, VillageStock>,
+    ledger: ResMut<'w, Ledger>,
+    structure_query: Query<'w, 's, &'static StructureType>,
+    q_sites: Query<'w, 's, (), With<BuildingSite>>,
+    pub game: Game<'w, 's>,
+}
 
-    let Some(structure_type) = selected_structure_type.0 else {
-        return;
-    };
+impl BuildingPlacement<'_, '_> {
+    /// Pay for and spawn a building site on `tile`, returns the site if it could be placed.
+    pub fn place(&mut self, structure_type: StructureType, tile: Tile) -> Option<Entity> {
+        let cost = self.structure_cost.get(&structure_type)?;
+
+        let footprint = structure_type.footprint();
+        if TileRect::from_origin(tile, footprint)
+            .into_iter()
+            .any(|tile| self.game.is_occupied(tile) || self.game.map.traps.contains_key(&tile))
+        {
+            return None;
+        }
 
-    let Some(cost) = structure_cost.get(&structure_type) else {
-        return;
-    };
+        let sites = self.game.find_tiles_that_can_be_built_on(footprint);
 
-    if game.is_occupied(*tile) {
-        return;
-    }
+        if !sites.contains(&tile) {
+            return None;
+        }
 
-    let sites = game.find_tiles_that_can_be_built_on();
+        if cost.is_exclusive && self.structure_query.iter().any(|s| *s == structure_type) {
+            return None;
+        }
 
-    if !sites.contains(tile) {
-        return;
-    }
+        if !self.stock.can_afford(&cost.resources) {
+            return None;
+        }
 
-    if cost.is_exclusive {
-        for s in structure_query.iter() {
-            if *s == structure_type {
-                return;
-            }
+        if self.jobs.resting(self.population.0) < cost.workers {
+            return None;
         }
+
+        self.stock.spend(&cost.resources);
+        self.ledger.gold(
+            LedgerSource::Construction,
+            -(cost.resources.get(ResourceKind::Gold) as i32),
+        );
+        self.jobs
+            .assign(VillagerJob::Construction, cost.workers, self.population.0);
+
+        let object_translation = footprint_translation(tile, footprint, 2.0);
+        let id = self
+            .commands
+            .spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        anchor: TILE_ANCHOR,
+                        color: structure_type.tint().with_alpha(0.85),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(object_translation),
+                    texture: self.tile_set.get(structure_type.tile_texture()),
+                    ..default()
+                },
+                StateScoped(Screen::Playing),
+                structure_type,
+                RemainingConstructionTurns(cost.turns),
+                ConstructionWorkers(cost.workers),
+                BuildingSite,
+                Structure,
+            ))
+            .with_children(|builder| {
+                builder.spawn((
+                    Text2dBundle {
+                        text: Text::from_section(
+                            cost.turns.to_string(),
+                            TextStyle {
+                                font_size: 100.,
+                                ..Default::default()
+                            },
+                        ),
+                        transform: Transform::from_translation(0.01 * Vec3::Z),
+                        ..Default::default()
+                    },
+                    BuildingProgressLabel,
+                ));
+            })
+            .with_children(|builder| {
+                builder.spawn((SpriteBundle {
+                    sprite: Sprite {
+                        anchor: super::tile_set::TILE_ANCHOR,
+                        color: Color::WHITE.with_alpha(0.75),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(-0.01 * Vec3::Z),
+                    texture: self.tile_set.get("border_thick"),
+                    ..Default::default()
+                },));
+            })
+            .id();
+        // Unfinished defences don't keep the village standing either
+        if structure_type.is_defence() {
+            self.commands.entity(id).insert(Defence);
+        }
+
+        self.game.insert_with_footprint(tile, id, footprint);
+        self.commands
+            .trigger(PlaySfx::Key(SfxKey::BuildingPlacement));
+        Some(id)
     }
 
-    if gold.0 < cost.gold {
-        return;
+    /// Take down a site placed this turn, refunding its cost and workers.
+    pub fn remove(&mut self, site: Entity, structure_type: StructureType) -> bool {
+        let Some(cost) = self.structure_cost.get(&structure_type) else {
+            return false;
+        };
+        if !self.q_sites.contains(site) {
+            return false;
+        }
+
+        self.stock.deposit(&cost.resources);
+        self.ledger.gold(
+            LedgerSource::Construction,
+            cost.resources.get(ResourceKind::Gold) as i32,
+        );
+        self.jobs.release(VillagerJob::Construction, cost.workers);
+        self.game.map.actors.remove_entity(site);
+        self.commands.entity(site).despawn_recursive();
+        true
     }
+}
 
-    if population.0 < working_population.0 + cost.workers {
+pub fn spawn_in_progress_building(
+    mut events: EventReader<TilePressedEvent>,
+    selected_structure_type: Res<SelectedStructueType>,
+    mut placement: BuildingPlacement,
+    mut undo_stack: ResMut<UndoStack>,
+) {
+    let Some(TilePressedEvent(tile)) = events.read().last() else {
         return;
-    }
+    };
 
-    gold.0 -= cost.gold;
-    working_population.0 += cost.workers;
+    let Some(structure_type) = selected_structure_type.0 else {
+        return;
+    };
 
-    let object_translation = tile_coord_translation(tile.x() as f32, tile.y() as f32, 2.0);
-    let id = commands
-        .spawn((
-            SpriteBundle {
-                sprite: Sprite {
-                    anchor: TILE_ANCHOR,
-                    color: Color::WHITE.with_alpha(0.85),
-                    ..Default::default()
-                },
-                transform: Transform::from_translation(object_translation),
-                texture: tile_set.get(structure_type.tile_texture()),
-                ..default()
-            },
-            StateScoped(Screen::Playing),
+    if let Some(site) = placement.place(structure_type, *tile) {
+        undo_stack.push(TurnCommand::PlaceBuilding {
+            site,
             structure_type,
-            RemainingConstructionTurns(cost.turns),
-            ConstructionWorkers(cost.workers),
-            BuildingSite,
-            Structure,
-        ))
-        .with_children(|builder| {
-            builder.spawn((
-                Text2dBundle {
-                    text: Text::from_section(
-                        cost.turns.to_string(),
-                        TextStyle {
-                            font_size: 100.,
-                            ..Default::default()
-                        },
-                    ),
-                    transform: Transform::from_translation(0.01 * Vec3::Z),
-                    ..Default::default()
-                },
-                BuildingProgressLabel,
-            ));
-        })
-        .with_children(|builder| {
-            builder.spawn((SpriteBundle {
-                sprite: Sprite {
-                    anchor: super::tile_set::TILE_ANCHOR,
-                    color: Color::WHITE.with_alpha(0.75),
-                    ..Default::default()
-                },
-                transform: Transform::from_translation(-0.01 * Vec3::Z),
-                texture: tile_set.get("border_thick"),
-                ..Default::default()
-            },));
-        })
-        .id();
-
-    game.insert(*tile, id);
-    commands.trigger(PlaySfx::Key(SfxKey::BuildingPlacement));
+            tile: *tile,
+        });
+    }
 }
 
 pub fn update_building_progress(
@@ -448,21 +838,22 @@ pub fn update_building_progress(
     )>,
     mut village_map: ResMut<VillageMap>,
     tile_set: Res<TileSet>,
-    mut working_population: ResMut<VillageEmployment>,
+    mut jobs: ResMut<VillageJobs>,
 ) {
     for (e, mut b, s, w) in building_query.iter_mut() {
         b.0 = b.0.saturating_sub(1);
         if b.0 == 0 {
-            working_population.0 = working_population.0.saturating_sub(w.0);
+            jobs.release(VillagerJob::Construction, w.0);
             commands.entity(e).despawn_recursive();
             let Some(tile) = village_map.actors.locate(e) else {
                 continue;
             };
-            let object_translation = tile_coord_translation(tile.x() as f32, tile.y() as f32, 2.);
+            let object_translation = footprint_translation(tile, s.footprint(), 2.);
             let mut object_entity = commands.spawn((
                 SpriteBundle {
                     sprite: Sprite {
                         anchor: TILE_ANCHOR,
+                        color: s.tint(),
                         ..Default::default()
                     },
                     texture: tile_set.get(s.tile_texture()),
@@ -470,9 +861,7 @@ pub fn update_building_progress(
                     ..default()
                 },
                 tile,
-                PickableTile,
                 StateScoped(Screen::Playing),
-                StructureBundle::default(),
                 SpawnAnimation::new(object_translation),
                 *s,
             ));
@@ -480,20 +869,62 @@ pub fn update_building_progress(
             println!("entity = {:?}", object_entity.id());
             match s {
                 StructureType::Tavern => {
-                    object_entity.insert(Tavern);
+                    object_entity.insert((StructureBundle::default(), PickableTile, Tavern));
                 }
                 StructureType::House => {
-                    object_entity.insert(House);
+                    object_entity.insert((StructureBundle::default(), PickableTile, House));
                 }
                 StructureType::ArcherTower => {
-                    object_entity.insert(ArcherTower);
+                    object_entity.insert((StructureBundle::default(), PickableTile, ArcherTower));
                 }
                 StructureType::Blacksmith => {
-                    object_entity.insert(Blacksmith);
+                    object_entity.insert((StructureBundle::default(), PickableTile, Blacksmith));
+                }
+                StructureType::Farm | StructureType::LumberCamp | StructureType::Quarry => {
+                    object_entity.insert((
+                        StructureBundle::default().with_population_capacity(0),
+                        PickableTile,
+                    ));
+                }
+                StructureType::Wall => {
+                    object_entity.insert((
+                        StructureBundle::default()
+                            .with_health(WALL_HEALTH)
+                            .with_population_capacity(0),
+                        PickableTile,
+                        Defence,
+                        Wall,
+                    ));
+                }
+                StructureType::Gate => {
+                    object_entity.insert((
+                        StructureBundle::default().with_population_capacity(0),
+                        PickableTile,
+                        Defence,
+                        Gate,
+                    ));
+                }
+                StructureType::BearTrap | StructureType::Landmine => {
+                    if let Some(trap) = s.trap() {
+                        object_entity.insert(trap);
+                    }
                 }
             };
 
-            village_map.actors.set(tile, object_entity.id());
+            match s.trap() {
+                Some(_) => {
+                    // Traps are hidden from the actor layer
+                    village_map.actors.remove(tile);
+                    village_map.traps.insert(tile, object_entity.id());
+                }
+                None => {
+                    object_entity
+                        .insert((Production(s.production()), Consumption(s.consumption())));
+                    village_map
+                        .actors
+                        .set_with_footprint(tile, object_entity.id(), s.footprint());
+                }
+            }
         }
     }
 }
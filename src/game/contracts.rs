@@ -0,0 +1,245 @@
+//! Mercenaries hired at the tavern, paid every night and gone once their loyalty runs out.
+
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::screen::Screen;
+
+use super::actors::player::{spawn_player_unit, PlayerSprite};
+use super::actors::stats::{ActorName, Health, Movement};
+use super::actors::AvailableActorNames;
+use super::actors_list::PlayerActorList;
+use super::constants::{MAX_LOYALTY, TAVERN_ROSTER_SIZE};
+use super::cycle::{TimeOfDay, Turn};
+use super::ledger::{Ledger, LedgerSource};
+use super::map::VillageMap;
+use super::resources::{ResourceKind, VillageStock};
+use super::rng::{GameRng, RngStream};
+use super::selection::SelectedActor;
+
+pub struct ContractsPlugin;
+
+impl Plugin for ContractsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TavernRoster>()
+            .init_resource::<Desertions>()
+            .add_systems(OnEnter(Screen::Playing), reset_contracts)
+            .add_systems(
+                OnEnter(TimeOfDay::Day),
+                refresh_roster.run_if(|turn: Res<Turn>| turn.0 != 0),
+            )
+            .add_systems(
+                OnEnter(TimeOfDay::Night),
+                pay_wages.run_if(in_state(Screen::Playing)),
+            );
+    }
+}
+
+#[derive(Debug)]
+pub struct MercenaryTemplate {
+    pub title: &'static str,
+    /// Class the mercenary fights as.
+    pub sprite: PlayerSprite,
+    pub health: u32,
+    pub movement: u32,
+    /// Paid once when hiring.
+    pub hire_cost: u32,
+    /// Paid every night, hunters fight for free.
+    pub wage: u32,
+}
+
+pub const MERCENARIES: &[MercenaryTemplate] = &[
+    MercenaryTemplate {
+        title: "Hunter",
+        sprite: PlayerSprite::Viking,
+        health: 2,
+        movement: 4,
+        hire_cost: 40,
+        wage: 0,
+    },
+    MercenaryTemplate {
+        title: "Sellsword",
+        sprite: PlayerSprite::Fighter,
+        health: 3,
+        movement: 3,
+        hire_cost: 20,
+        wage: 10,
+    },
+    MercenaryTemplate {
+        title: "Scout",
+        sprite: PlayerSprite::Spartan,
+        health: 2,
+        movement: 5,
+        hire_cost: 15,
+        wage: 8,
+    },
+    MercenaryTemplate {
+        title: "Knight",
+        sprite: PlayerSprite::Warrior,
+        health: 5,
+        movement: 2,
+        hire_cost: 30,
+        wage: 20,
+    },
+];
+
+impl MercenaryTemplate {
+    pub fn find(title: &str) -> Option<&'static MercenaryTemplate> {
+        MERCENARIES
+            .iter()
+            .find(|mercenary| mercenary.title == title)
+    }
+
+    /// Spawn the mercenary as a new hero under contract, returns the hero and its name.
+    pub fn hire(
+        &self,
+        commands: &mut Commands,
+        names: &mut AvailableActorNames,
+        player_unit_list: &mut PlayerActorList,
+    ) -> (Entity, String) {
+        let name = names.next_name();
+        let id = spawn_player_unit(commands, name.clone(), self.sprite);
+        commands.entity(id).insert((
+            Health::new(self.health),
+            Movement(self.movement),
+            Contract {
+                wage: self.wage,
+                loyalty: MAX_LOYALTY,
+            },
+        ));
+        player_unit_list.0.push(id);
+        (id, name)
+    }
+}
+
+/// Mercenaries waiting at the tavern today, hired ones leave an empty spot.
+#[derive(Resource, Default, Debug)]
+pub struct TavernRoster(pub Vec<Option<&'static MercenaryTemplate>>);
+
+/// Heroes that left during the last night because they weren't paid.
+#[derive(Resource, Default, Debug)]
+pub struct Desertions(pub Vec<String>);
+
+/// A hero fighting for gold, heroes without one stay for good.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Contract {
+    pub wage: u32,
+    /// Drops every unpaid night, the hero leaves at zero.
+    pub loyalty: u32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Payday {
+    Paid,
+    Unpaid,
+    Leaves,
+}
+
+impl Contract {
+    /// Pay tonight's wage from `gold` if there is enough of it.
+    pub fn pay(&mut self, gold: &mut u32) -> Payday {
+        if self.wage <= *gold {
+            *gold -= self.wage;
+            self.loyalty = (self.loyalty + 1).min(MAX_LOYALTY);
+            return Payday::Paid;
+        }
+
+        self.loyalty = self.loyalty.saturating_sub(1);
+        match self.loyalty {
+            0 => Payday::Leaves,
+            _ => Payday::Unpaid,
+        }
+    }
+}
+
+pub fn roll_roster(rng: &mut impl Rng) -> Vec<Option<&'static MercenaryTemplate>> {
+    (0..TAVERN_ROSTER_SIZE)
+        .map(|_| MERCENARIES.choose(rng))
+        .collect()
+}
+
+fn reset_contracts(
+    mut roster: ResMut<TavernRoster>,
+    mut desertions: ResMut<Desertions>,
+    mut rng: ResMut<GameRng>,
+) {
+    roster.0 = roll_roster(rng.stream(RngStream::Tavern));
+    desertions.0.clear();
+}
+
+fn refresh_roster(mut roster: ResMut<TavernRoster>, mut rng: ResMut<GameRng>) {
+    roster.0 = roll_roster(rng.stream(RngStream::Tavern));
+}
+
+fn pay_wages(
+    mut commands: Commands,
+    mut q_contracts: Query<(Entity, &ActorName, &mut Contract)>,
+    mut stock: ResMut<VillageStock>,
+    mut ledger: ResMut<Ledger>,
+    mut player_unit_list: ResMut<PlayerActorList>,
+    mut village_map: ResMut<VillageMap>,
+    mut selected_actor: ResMut<SelectedActor>,
+    mut desertions: ResMut<Desertions>,
+) {
+    desertions.0.clear();
+
+    for (entity, name, mut contract) in q_contracts.iter_mut() {
+        let gold = stock.get_mut(ResourceKind::Gold);
+        let before = *gold;
+        let payday = contract.pay(gold);
+        ledger.gold(LedgerSource::Wages, -((before - *gold) as i32));
+
+        if payday != Payday::Leaves {
+            continue;
+        }
+
+        desertions.0.push(name.0.clone());
+        player_unit_list.0.retain(|hero| *hero != entity);
+        village_map.actors.remove_entity(entity);
+        if selected_actor.entity == Some(entity) {
+            selected_actor.entity = None;
+        }
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn test_unpaid_mercenary_leaves() {
+        let mut contract = Contract {
+            wage: 10,
+            loyalty: MAX_LOYALTY,
+        };
+        let mut gold = 15;
+
+        assert_eq!(contract.pay(&mut gold), Payday::Paid);
+        assert_eq!(gold, 5);
+        for _ in 1..MAX_LOYALTY {
+            assert_eq!(contract.pay(&mut gold), Payday::Unpaid);
+        }
+        assert_eq!(contract.pay(&mut gold), Payday::Leaves);
+        assert_eq!(gold, 5);
+    }
+
+    #[test]
+    fn test_hunters_are_free() {
+        let mut contract = Contract {
+            wage: 0,
+            loyalty: 1,
+        };
+        let mut gold = 0;
+        assert_eq!(contract.pay(&mut gold), Payday::Paid);
+        assert_eq!(contract.loyalty, 2.min(MAX_LOYALTY));
+
+        let roster = roll_roster(&mut StdRng::seed_from_u64(3));
+        assert_eq!(roster.len(), TAVERN_ROSTER_SIZE);
+        assert!(roster.iter().all(Option::is_some));
+    }
+}
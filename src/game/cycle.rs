@@ -1,37 +1,39 @@
 use bevy::prelude::*;
+use serde::Deserialize;
 
 use crate::screen::{playing::GameState, Screen};
 
+use self::calendar::{Calendar, CalendarPlugin};
+
 use super::{
-    actors_list::PlayerActorList, events::EndDayTurn, map::VillageMap,
-    resources::VillageEmployment, update_resource_label, update_resource_label_system, WatchRes,
+    actors_list::PlayerActorList, events::EndDayTurn, map::VillageMap, resources::VillageJobs,
+    update_resource_label, update_resource_label_system, WatchRes,
 };
 
-/// Number of days in a season.
-pub const DAY_PER_SEASON: u32 = 2; // TODO: Determine a balanced number, set to low for testing.
-/// Number of days in a cycle which contains all 3 seasons.
-pub const DAY_PER_CYCLE: u32 = DAY_PER_SEASON * 3;
+pub mod calendar;
 
 const M: f32 = 0.4;
 pub const CLEAR_BACKGROUND: Color = Color::srgb(0.7 * M, 0.75 * M, 0.8 * M);
-pub const SUMMER_BACKGROUND: Color = Color::srgb(0.16 * M, 0.67 * M, 0.29 * M);
-pub const AUTUMN_BACKGROUND: Color = Color::srgb(0.98 * M, 0.69 * M, 0.23 * M);
-pub const WINTER_BACKGROUND: Color = Color::srgb(0.65 * M, 0.82 * M, 0.95 * M);
 
 pub struct CyclePlugin;
 
 impl Plugin for CyclePlugin {
     fn build(&self, app: &mut App) {
-        app.init_state::<TimeOfDay>()
+        app.add_plugins(CalendarPlugin)
+            .init_state::<TimeOfDay>()
             .init_resource::<Season>()
             .init_resource::<DayCycle>()
             .init_resource::<Turn>()
-            .init_resource::<VillageEmployment>()
+            .init_resource::<DayClock>()
+            .init_resource::<VillageJobs>()
             .add_event::<NextSeason>()
             .add_event::<EndTurn>()
             .add_event::<EndDeployment>()
             .add_event::<EndDayTurn>()
-            .add_systems(OnEnter(Screen::Playing), (reset_cycle, update_background))
+            .add_systems(
+                OnEnter(Screen::Playing),
+                (reset_cycle, update_background).chain(),
+            )
             .add_systems(OnExit(Screen::Playing), reset_background)
             .add_systems(
                 Update,
@@ -46,7 +48,8 @@ impl Plugin for CyclePlugin {
                         update_resource_label_system::<Turn>(turn_until_label.into_configs()),
                     )
                         .after(update_cycle),
-                    update_background.run_if(state_changed::<TimeOfDay>),
+                    update_background
+                        .run_if(state_changed::<TimeOfDay>.or_else(resource_changed::<Calendar>)),
                 )
                     .run_if(in_state(Screen::Playing)),
             );
@@ -55,14 +58,11 @@ impl Plugin for CyclePlugin {
 
 fn update_background(
     time_of_day: Res<State<TimeOfDay>>,
-    season: Res<Season>,
+    calendar: Res<Calendar>,
+    clock: Res<DayClock>,
     mut clear_color: ResMut<ClearColor>,
 ) {
-    let color = match *season {
-        Season::Summer => SUMMER_BACKGROUND,
-        Season::Autumn => AUTUMN_BACKGROUND,
-        Season::Winter => WINTER_BACKGROUND,
-    };
+    let color = calendar.background(clock.day).unwrap_or(CLEAR_BACKGROUND);
     clear_color.0 = match time_of_day.get() {
         TimeOfDay::Day => color,
         TimeOfDay::Night => color.mix(&Color::BLACK, 0.5),
@@ -73,9 +73,17 @@ fn reset_background(mut clear_color: ResMut<ClearColor>) {
     clear_color.0 = CLEAR_BACKGROUND;
 }
 
-fn reset_cycle(mut season: ResMut<Season>, mut turn: ResMut<Turn>) {
-    *season = Season::default();
+fn reset_cycle(
+    mut season: ResMut<Season>,
+    mut turn: ResMut<Turn>,
+    mut clock: ResMut<DayClock>,
+    mut day_cycle: ResMut<DayCycle>,
+    calendar: Res<Calendar>,
+) {
     turn.0 = 0;
+    *clock = DayClock::default();
+    *season = calendar.season(clock.day);
+    *day_cycle = calendar.day_cycle(clock.day);
 }
 
 fn end_turn(mut end_turn_evt: EventReader<EndTurn>, mut turn: ResMut<Turn>) {
@@ -105,32 +113,33 @@ fn end_deployment(
 
 fn update_cycle(
     turn: Res<Turn>,
+    mut clock: ResMut<DayClock>,
     mut day_cycle: ResMut<DayCycle>,
     mut next_tod: ResMut<NextState<TimeOfDay>>,
     mut season: ResMut<Season>,
     mut game_state: ResMut<NextState<GameState>>,
+    calendar: Res<Calendar>,
 ) {
-    // Season
-    let day = turn.0 / day_cycle.turns_per_day();
-    season.set_if_neq(match (day % DAY_PER_CYCLE) / DAY_PER_SEASON {
-        0 => Season::Summer,
-        1 => Season::Autumn,
-        2 => Season::Winter,
-        num => unreachable!("Season range is [0, 3) but given {num} instead!"),
-    });
-
-    // Day cycle
-    *day_cycle = DayCycle::from(*season);
+    // The day cycle is only decided at dawn so that it can be changed during the day
+    if clock.turn_in_day(*turn) >= day_cycle.turns_per_day() {
+        clock.day += 1;
+        clock.start_turn = turn.0;
+
+        season.set_if_neq(calendar.season(clock.day));
+        *day_cycle = calendar.day_cycle(clock.day);
+    }
+
+    let turn_in_day = clock.turn_in_day(*turn);
 
     // Time of day
-    match turn.0 % day_cycle.turns_per_day() >= day_cycle.day {
+    match turn_in_day >= day_cycle.day {
         true => {
             next_tod.set(TimeOfDay::Night);
         }
         false => next_tod.set(TimeOfDay::Day),
     }
 
-    if turn.0 % day_cycle.turns_per_day() == day_cycle.day {
+    if turn_in_day == day_cycle.day {
         game_state.set(GameState::Deployment);
     }
 }
@@ -138,16 +147,24 @@ fn update_cycle(
 fn turn_until_label(
     mut q_texts: Query<&mut Text, With<WatchRes<Turn>>>,
     turn: Res<Turn>,
+    clock: Res<DayClock>,
     day_cycle: Res<DayCycle>,
+    calendar: Res<Calendar>,
 ) {
     let Ok(mut text) = q_texts.get_single_mut() else {
         return;
     };
 
-    let turn_in_day = turn.0 % day_cycle.turns_per_day();
+    let turn_in_day = clock.turn_in_day(*turn);
     let (turn_left, target_day) = match turn_in_day >= day_cycle.day {
-        true => (day_cycle.turns_per_day() - turn_in_day, "day"),
-        false => (day_cycle.day - turn_in_day, "night"),
+        true => (day_cycle.turns_per_day() - turn_in_day, "day".to_string()),
+        false => (
+            day_cycle.day - turn_in_day,
+            match calendar.special_day(clock.day) {
+                Some(special) => format!("the {}", special.kind),
+                None => "night".to_string(),
+            },
+        ),
     };
 
     let section = &mut text.sections[0];
@@ -163,13 +180,27 @@ fn turn_until_label(
 #[derive(Resource, Debug, Copy, Clone, PartialEq, Default)]
 pub struct Turn(pub u32);
 
+/// Day count and the turn it started on.
+#[derive(Resource, Debug, Copy, Clone, PartialEq, Default)]
+pub struct DayClock {
+    pub day: u32,
+    pub start_turn: u32,
+}
+
+impl DayClock {
+    pub fn turn_in_day(&self, turn: Turn) -> u32 {
+        turn.0.saturating_sub(self.start_turn)
+    }
+}
+
 #[derive(Event, Copy, Clone, PartialEq, Default)]
 pub struct EndTurn;
 
 #[derive(Event, Copy, Clone, PartialEq, Default)]
 pub struct EndDeployment;
 
-#[derive(Resource, Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[derive(Resource, Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Season {
     #[default]
     Summer,
@@ -211,27 +242,9 @@ impl DayCycle {
     }
 }
 
+/// Only used until the [`Calendar`] is loaded.
 impl Default for DayCycle {
     fn default() -> Self {
-        Self::from(Season::default())
-    }
-}
-
-impl From<Season> for DayCycle {
-    fn from(season: Season) -> Self {
-        match season {
-            Season::Summer => DayCycle { day: 1, night: 4 },
-            Season::Autumn => DayCycle { day: 1, night: 5 },
-            Season::Winter => DayCycle { day: 1, night: 6 },
-        }
-    }
-}
-
-#[derive(Resource, Debug, Copy, Clone, PartialEq)]
-pub struct DaysUntilFullMoon(pub u32);
-
-impl Default for DaysUntilFullMoon {
-    fn default() -> Self {
-        Self(3)
+        Self { day: 1, night: 1 }
     }
 }
@@ -0,0 +1,302 @@
+//! Seasons, day/night turns and special days, loaded from `seasons.calendar.json`.
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::game::vfx::EnvironmentVfx;
+
+use super::{DayCycle, Season};
+
+/// Background colours are dimmed so the map stands out.
+const BACKGROUND_BRIGHTNESS: f32 = 0.4;
+
+pub struct CalendarPlugin;
+
+impl Plugin for CalendarPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Calendar>()
+            .init_asset_loader::<CalendarLoader>()
+            .init_resource::<Calendar>()
+            .add_systems(PreStartup, load_calendar)
+            .add_systems(Update, update_calendar);
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct CalendarHandle(pub Handle<Calendar>);
+
+fn load_calendar(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(CalendarHandle(asset_server.load("seasons.calendar.json")));
+}
+
+/// Keep the [`Calendar`] resource in sync with the asset, so it can be tuned while playing.
+fn update_calendar(
+    mut asset_events: EventReader<AssetEvent<Calendar>>,
+    handle: Res<CalendarHandle>,
+    calendars: Res<Assets<Calendar>>,
+    mut calendar: ResMut<Calendar>,
+) {
+    for event in asset_events.read() {
+        if !event.is_loaded_with_dependencies(&handle.0) && !event.is_modified(&handle.0) {
+            continue;
+        }
+        if let Some(loaded) = calendars.get(&handle.0) {
+            *calendar = loaded.clone();
+        }
+    }
+}
+
+/// The whole year, seasons follow each other and start over after the last one.
+#[derive(Asset, Resource, TypePath, Deserialize, Default, Clone, Debug)]
+pub struct Calendar {
+    pub seasons: Vec<SeasonSchedule>,
+    /// The first matching special day applies.
+    #[serde(default)]
+    pub special_days: Vec<SpecialDay>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct SeasonSchedule {
+    pub season: Season,
+    /// Number of days in the season.
+    pub days: u32,
+    pub day_turns: u32,
+    pub night_turns: u32,
+    /// Srgb colour of the daytime background.
+    pub background: [f32; 3],
+    pub vfx: EnvironmentVfx,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct SpecialDay {
+    pub kind: SpecialDayKind,
+    /// Only falls in this season, any season otherwise.
+    #[serde(default)]
+    pub season: Option<Season>,
+    /// Day of the season, starting from 1.
+    #[serde(default)]
+    pub day: Option<u32>,
+    /// Falls on every n-th day of the season.
+    #[serde(default)]
+    pub every: Option<u32>,
+    /// Overrides the season's night length.
+    #[serde(default)]
+    pub night_turns: Option<u32>,
+    /// Overrides the season's background.
+    #[serde(default)]
+    pub background: Option<[f32; 3]>,
+}
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SpecialDayKind {
+    FullMoon,
+    Eclipse,
+}
+
+impl std::fmt::Display for SpecialDayKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpecialDayKind::FullMoon => "full moon".fmt(f),
+            SpecialDayKind::Eclipse => "eclipse".fmt(f),
+        }
+    }
+}
+
+impl SpecialDay {
+    /// Whether it falls on the given day of the season, starting from 1.
+    fn falls_on(&self, season: Season, day_of_season: u32) -> bool {
+        self.season.is_none_or(|s| s == season)
+            && (self.day == Some(day_of_season)
+                || self
+                    .every
+                    .is_some_and(|every| every > 0 && day_of_season.is_multiple_of(every)))
+    }
+}
+
+impl Calendar {
+    /// Number of days before the seasons start over.
+    pub fn year_length(&self) -> u32 {
+        self.seasons.iter().map(|season| season.days).sum()
+    }
+
+    /// Season of the day and the day of the season, starting from 1.
+    pub fn date(&self, day: u32) -> Option<(&SeasonSchedule, u32)> {
+        let year_length = self.year_length();
+        if year_length == 0 {
+            return None;
+        }
+
+        let mut day_of_year = day % year_length;
+        for season in self.seasons.iter() {
+            if day_of_year < season.days {
+                return Some((season, day_of_year + 1));
+            }
+            day_of_year -= season.days;
+        }
+        None
+    }
+
+    pub fn season(&self, day: u32) -> Season {
+        self.date(day)
+            .map(|(schedule, _)| schedule.season)
+            .unwrap_or_default()
+    }
+
+    /// Whether the day is the first one of its season.
+    pub fn is_season_start(&self, day: u32) -> bool {
+        self.date(day)
+            .is_some_and(|(_, day_of_season)| day_of_season == 1)
+    }
+
+    pub fn special_day(&self, day: u32) -> Option<&SpecialDay> {
+        let (schedule, day_of_season) = self.date(day)?;
+        self.special_days
+            .iter()
+            .find(|special| special.falls_on(schedule.season, day_of_season))
+    }
+
+    pub fn day_cycle(&self, day: u32) -> DayCycle {
+        let Some((schedule, _)) = self.date(day) else {
+            return DayCycle::default();
+        };
+
+        DayCycle {
+            day: schedule.day_turns,
+            night: self
+                .special_day(day)
+                .and_then(|special| special.night_turns)
+                .unwrap_or(schedule.night_turns),
+        }
+    }
+
+    /// Daytime background of the day.
+    pub fn background(&self, day: u32) -> Option<Color> {
+        let (schedule, _) = self.date(day)?;
+        let [r, g, b] = self
+            .special_day(day)
+            .and_then(|special| special.background)
+            .unwrap_or(schedule.background);
+
+        Some(Color::srgb(
+            r * BACKGROUND_BRIGHTNESS,
+            g * BACKGROUND_BRIGHTNESS,
+            b * BACKGROUND_BRIGHTNESS,
+        ))
+    }
+
+    pub fn vfx(&self, day: u32) -> Option<EnvironmentVfx> {
+        self.date(day).map(|(schedule, _)| schedule.vfx)
+    }
+}
+
+#[derive(Default)]
+pub struct CalendarLoader;
+
+impl AssetLoader for CalendarLoader {
+    type Asset = Calendar;
+    type Settings = ();
+    type Error = CalendarLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        Ok(serde_json::from_slice::<Calendar>(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["calendar.json"]
+    }
+}
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum CalendarLoaderError {
+    #[error("Could not load json file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not deserialize using serde: {0}")]
+    Serde(#[from] serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Kept apart from the shipped asset, so that it can be tuned freely.
+    const CALENDAR: &str = r#"{
+        "seasons": [
+            {
+                "season": "summer",
+                "days": 10,
+                "day_turns": 6,
+                "night_turns": 4,
+                "background": [0.0, 1.0, 0.0],
+                "vfx": "summer"
+            },
+            {
+                "season": "autumn",
+                "days": 10,
+                "day_turns": 5,
+                "night_turns": 5,
+                "background": [1.0, 0.5, 0.0],
+                "vfx": "autumn"
+            },
+            {
+                "season": "winter",
+                "days": 10,
+                "day_turns": 4,
+                "night_turns": 6,
+                "background": [1.0, 1.0, 1.0],
+                "vfx": "winter"
+            }
+        ],
+        "special_days": [
+            { "kind": "eclipse", "season": "winter", "day": 10, "night_turns": 10 },
+            { "kind": "full_moon", "every": 5 }
+        ]
+    }"#;
+
+    fn calendar() -> Calendar {
+        serde_json::from_str(CALENDAR).unwrap()
+    }
+
+    #[test]
+    fn test_calendar_dates() {
+        let calendar = calendar();
+        assert_eq!(calendar.year_length(), 30);
+
+        assert_eq!(calendar.season(0), Season::Summer);
+        assert_eq!(calendar.season(10), Season::Autumn);
+        assert_eq!(calendar.season(29), Season::Winter);
+        // The year starts over
+        assert_eq!(calendar.season(30), Season::Summer);
+        assert!(calendar.is_season_start(20) && !calendar.is_season_start(21));
+
+        assert_eq!(calendar.day_cycle(0), DayCycle { day: 6, night: 4 });
+        assert_eq!(calendar.day_cycle(25), DayCycle { day: 4, night: 6 });
+    }
+
+    #[test]
+    fn test_calendar_special_days() {
+        let calendar = calendar();
+        let kind = |day| calendar.special_day(day).map(|special| special.kind);
+
+        assert_eq!(kind(0), None);
+        assert_eq!(kind(4), Some(SpecialDayKind::FullMoon));
+        assert_eq!(kind(19), Some(SpecialDayKind::FullMoon));
+        assert_eq!(kind(24), Some(SpecialDayKind::FullMoon));
+        // The eclipse takes the place of the last full moon of winter
+        assert_eq!(kind(29), Some(SpecialDayKind::Eclipse));
+        assert_eq!(calendar.day_cycle(29).night, 10);
+    }
+}
@@ -0,0 +1,213 @@
+//! Daily challenge runs, everyone playing on the same day gets the same seed and rules.
+
+use bevy::prelude::*;
+use bevy::utils::SystemTime;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+use crate::path_finding::tiles::Tile;
+use crate::screen::Screen;
+
+use super::actors::PlayerActor;
+use super::difficulty::Difficulty;
+use super::game_mode::GameMode;
+use super::inventory::{Inventory, ITEM_TEMPLATES};
+use super::level::Terrain;
+use super::map::VillageMap;
+
+/// Rules picked for every daily challenge.
+const DAILY_RULES: usize = 2;
+/// Chance for open grass outside the deployment zone to be flooded.
+const FLOOD_PERCENT: u32 = 35;
+
+pub struct DailyPlugin;
+
+impl Plugin for DailyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DailyChallenge>()
+            .add_systems(OnExit(Screen::Title), start_daily_challenge)
+            .add_systems(Update, arm_with_bows.run_if(in_state(Screen::Playing)));
+    }
+}
+
+#[derive(EnumIter, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ChallengeRule {
+    /// The merchant never comes by.
+    NoMerchant,
+    /// Waves get twice the budget.
+    DoubleEnemies,
+    /// Heroes only fight with bows, and bows are all the merchant sells.
+    OnlyBows,
+    /// A lot of the open land around the village is flooded.
+    WaterHeavyMap,
+}
+
+impl std::fmt::Display for ChallengeRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChallengeRule::NoMerchant => "No merchant".fmt(f),
+            ChallengeRule::DoubleEnemies => "Double enemies".fmt(f),
+            ChallengeRule::OnlyBows => "Only bows".fmt(f),
+            ChallengeRule::WaterHeavyMap => "Flooded village".fmt(f),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl std::fmt::Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl Date {
+    /// Date of the given number of days since 1970-01-01.
+    pub fn from_days(days: i64) -> Self {
+        // Civil from days, http://howardhinnant.github.io/date_algorithms.html
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (yoe + era * 400 + i64::from(month <= 2)) as i32;
+
+        Self { year, month, day }
+    }
+}
+
+/// Days since 1970-01-01 in UTC, so that the day changes at the same time for everyone.
+pub fn today() -> i64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| (elapsed.as_secs() / 86_400) as i64)
+        .unwrap_or_default()
+}
+
+/// Challenge of the current run, without a date outside daily challenges.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct DailyChallenge {
+    pub date: Option<Date>,
+    seed: u64,
+    pub rules: Vec<ChallengeRule>,
+}
+
+impl DailyChallenge {
+    pub fn for_day(days: i64) -> Self {
+        let seed = (days as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        let mut rules: Vec<_> = ChallengeRule::iter().collect();
+        let mut rng = StdRng::seed_from_u64(seed);
+        rules.shuffle(&mut rng);
+        rules.truncate(DAILY_RULES);
+
+        Self {
+            date: Some(Date::from_days(days)),
+            seed,
+            rules,
+        }
+    }
+
+    /// Seed shared by everyone playing the challenge.
+    pub fn seed(&self) -> Option<u64> {
+        self.date.map(|_| self.seed)
+    }
+
+    pub fn has(&self, rule: ChallengeRule) -> bool {
+        self.rules.contains(&rule)
+    }
+
+    /// Points to spend on tonight's wave under the challenge rules.
+    pub fn wave_budget(&self, budget: u32) -> u32 {
+        match self.has(ChallengeRule::DoubleEnemies) {
+            true => budget * 2,
+            false => budget,
+        }
+    }
+}
+
+/// Daily challenges are all played on normal difficulty, so that the scores compare.
+pub fn start_daily_challenge(
+    game_mode: Res<GameMode>,
+    mut challenge: ResMut<DailyChallenge>,
+    mut difficulty: ResMut<Difficulty>,
+) {
+    *challenge = match *game_mode {
+        GameMode::Daily => {
+            *difficulty = Difficulty::Normal;
+            DailyChallenge::for_day(today())
+        }
+        _ => DailyChallenge::default(),
+    };
+}
+
+/// Turn open grass around the village into water, heroes still have room to deploy.
+pub fn flood_map(village_map: &mut VillageMap, rng: &mut impl Rng) {
+    let size = village_map.size.to_ivec2();
+    let deployment = IRect::from_corners(IVec2::ZERO, size).inflate(-3);
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let tile = Tile(x, y);
+            if deployment.contains(IVec2::new(x, y))
+                || village_map.actors.get(tile).is_some()
+                || village_map.get_terrain(tile) != Some(Terrain::Grass)
+            {
+                continue;
+            }
+            if rng.gen_ratio(FLOOD_PERCENT, 100) {
+                village_map.set_terrain(tile, Terrain::Water);
+            }
+        }
+    }
+}
+
+/// Heroes trade whatever they carry for a bow.
+fn arm_with_bows(
+    challenge: Res<DailyChallenge>,
+    mut q_inventories: Query<&mut Inventory, Added<PlayerActor>>,
+) {
+    if !challenge.has(ChallengeRule::OnlyBows) {
+        return;
+    }
+
+    let Some(bow) = ITEM_TEMPLATES.iter().find(|item| item.name == "bow") else {
+        return;
+    };
+    for mut inventory in q_inventories.iter_mut() {
+        *inventory = Inventory::with_item(*bow);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_from_days() {
+        assert_eq!(Date::from_days(0).to_string(), "1970-01-01");
+        assert_eq!(Date::from_days(59).to_string(), "1970-03-01");
+        // Leap day
+        assert_eq!(Date::from_days(19_782).to_string(), "2024-02-29");
+        assert_eq!(Date::from_days(20_000).to_string(), "2024-10-04");
+    }
+
+    #[test]
+    fn test_daily_challenge_is_stable() {
+        let challenge = DailyChallenge::for_day(20_000);
+        assert_eq!(challenge.rules, DailyChallenge::for_day(20_000).rules);
+        assert_eq!(challenge.seed(), DailyChallenge::for_day(20_000).seed());
+        assert_ne!(challenge.seed(), DailyChallenge::for_day(20_001).seed());
+        assert_eq!(challenge.rules.len(), DAILY_RULES);
+        assert!(DailyChallenge::default().seed().is_none());
+    }
+}
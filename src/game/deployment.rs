@@ -37,6 +37,7 @@ pub fn deployment_zone_visualization(
     village_map: Res<VillageMap>,
     mut selected_tiles: ResMut<SelectedTiles>,
 ) {
+    selected_tiles.clear();
     selected_tiles
         .tiles
         .clone_from(&village_map.deployment_zone);
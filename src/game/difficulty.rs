@@ -0,0 +1,154 @@
+//! Difficulty presets picked on the title screen.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use strum_macros::{EnumIter, IntoStaticStr};
+
+use crate::screen::Screen;
+
+use super::resources::{ResourceBundle, ResourceKind, VillagePopulation, VillageStock};
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Difficulty>()
+            .add_systems(OnEnter(Screen::Playing), apply_starting_resources);
+    }
+}
+
+#[derive(
+    Resource,
+    EnumIter,
+    IntoStaticStr,
+    Serialize,
+    Deserialize,
+    Default,
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl std::fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        <&str>::from(self).fmt(f)
+    }
+}
+
+impl Difficulty {
+    pub fn preset(self) -> &'static DifficultyPreset {
+        match self {
+            Difficulty::Easy => &DIFFICULTY_PRESETS[0],
+            Difficulty::Normal => &DIFFICULTY_PRESETS[1],
+            Difficulty::Hard => &DIFFICULTY_PRESETS[2],
+        }
+    }
+
+    /// The next harder difficulty, wrapping around to the easiest.
+    pub fn next(self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DifficultyPreset {
+    /// Gold and resources the village starts with.
+    pub resources: ResourceBundle,
+    pub population: u32,
+    /// Enemy hit points in percent of their base value.
+    pub enemy_health_percent: u32,
+    /// Tiles added to the movement of every enemy.
+    pub enemy_movement_bonus: u32,
+    /// Points spent on enemies on the first night.
+    pub wave_budget: u32,
+    /// Percent of the first night's budget added every night.
+    pub wave_growth_percent: u32,
+}
+
+pub const DIFFICULTY_PRESETS: &[DifficultyPreset] = &[
+    DifficultyPreset {
+        resources: ResourceBundle::gold(80)
+            .with(ResourceKind::Wood, 80)
+            .with(ResourceKind::Stone, 60)
+            .with(ResourceKind::Food, 60),
+        population: 30,
+        enemy_health_percent: 75,
+        enemy_movement_bonus: 0,
+        wave_budget: 3,
+        wave_growth_percent: 8,
+    },
+    DifficultyPreset {
+        resources: ResourceBundle::gold(50)
+            .with(ResourceKind::Wood, 60)
+            .with(ResourceKind::Stone, 40)
+            .with(ResourceKind::Food, 40),
+        population: 25,
+        enemy_health_percent: 100,
+        enemy_movement_bonus: 0,
+        wave_budget: 4,
+        wave_growth_percent: 10,
+    },
+    DifficultyPreset {
+        resources: ResourceBundle::gold(35)
+            .with(ResourceKind::Wood, 40)
+            .with(ResourceKind::Stone, 30)
+            .with(ResourceKind::Food, 30),
+        population: 20,
+        enemy_health_percent: 150,
+        enemy_movement_bonus: 1,
+        wave_budget: 5,
+        wave_growth_percent: 15,
+    },
+];
+
+impl DifficultyPreset {
+    /// Points to spend on enemies on the given night, starting from 0.
+    pub fn wave_budget(&self, night: u32) -> u32 {
+        self.wave_budget * (100 + self.wave_growth_percent * night) / 100
+    }
+
+    pub fn enemy_health(&self, hit_points: u32) -> u32 {
+        (hit_points * self.enemy_health_percent / 100).max(1)
+    }
+}
+
+fn apply_starting_resources(
+    difficulty: Res<Difficulty>,
+    mut stock: ResMut<VillageStock>,
+    mut population: ResMut<VillagePopulation>,
+) {
+    let preset = difficulty.preset();
+    stock.set(&preset.resources);
+    population.0 = preset.population;
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    #[test]
+    fn test_wave_budget_grows() {
+        for difficulty in Difficulty::iter() {
+            let preset = difficulty.preset();
+            assert_eq!(preset.wave_budget(0), preset.wave_budget);
+            assert!(preset.wave_budget(10) > preset.wave_budget(0));
+        }
+        assert!(
+            Difficulty::Hard.preset().wave_budget(5) > Difficulty::Easy.preset().wave_budget(5)
+        );
+    }
+}
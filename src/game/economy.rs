@@ -1,30 +1,43 @@
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use rand::Rng;
 
 use crate::screen::Screen;
 
 use super::actors::Structure;
-use super::components::Income;
+use super::components::Consumption;
 use super::components::PopulationCapacity;
+use super::components::Production;
+use super::constants::POPULATION_GROWTH;
+use super::constants::RESTING_PER_NEWCOMER;
+use super::construction::StructureType;
 use super::cycle::TimeOfDay;
 use super::cycle::Turn;
-use super::resources::VillageEmployment;
-use super::resources::VillageGold;
+use super::jobs::count_job_slots;
+use super::jobs::staffing_percent;
+use super::ledger::Ledger;
+use super::ledger::LedgerSource;
+use super::mutators::ActiveMutators;
+use super::resources::ResourceBundle;
+use super::resources::ResourceKind;
+use super::resources::VillageJobs;
 use super::resources::VillagePopulation;
-use super::update_resource_label;
+use super::resources::VillageStock;
+use super::rng::{GameRng, RngStream};
+use super::{update_keyed_resource_label, update_resource_label};
 
 pub struct EconomyPlugin;
 
 impl Plugin for EconomyPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<VillageGold>()
+        app.init_resource::<VillageStock>()
             .init_resource::<VillagePopulation>()
             .add_systems(
                 Update,
                 (
-                    update_resource_label::<VillageGold>(),
+                    update_keyed_resource_label::<VillageStock>(),
                     update_resource_label::<VillagePopulation>(),
-                    update_resource_label::<VillageEmployment>(),
+                    update_resource_label::<VillageJobs>(),
                 )
                     .run_if(in_state(Screen::Playing)),
             )
@@ -35,23 +48,113 @@ impl Plugin for EconomyPlugin {
     }
 }
 
+/// What the village gains and loses at the next dawn, before any random growth.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DawnForecast {
+    /// Gold paid by the employed population.
+    pub gold: u32,
+    pub production: ResourceBundle,
+    pub consumption: ResourceBundle,
+    pub population_capacity: u32,
+    /// Villagers that leave because there isn't enough food.
+    pub hungry: u32,
+    /// Extra villagers arriving thanks to resting villagers.
+    pub newcomers: u32,
+}
+
+/// Structures that decide the village's income at dawn.
+#[derive(SystemParam)]
+pub struct IncomeSources<'w, 's> {
+    production_query:
+        Query<'w, 's, (&'static Production, Option<&'static StructureType>), With<Structure>>,
+    consumption_query: Query<'w, 's, &'static Consumption, With<Structure>>,
+    cap_query: Query<'w, 's, &'static PopulationCapacity, With<Structure>>,
+    mutators: Res<'w, ActiveMutators>,
+}
+
+impl IncomeSources<'_, '_> {
+    pub fn forecast(&self, population: u32, jobs: &VillageJobs, food: u32) -> DawnForecast {
+        let population_capacity = self.cap_query.iter().map(|capacity| capacity.0).sum();
+
+        let slots = count_job_slots(self.production_query.iter().filter_map(|(_, s)| s));
+        let production = self.production_query.iter().fold(
+            ResourceBundle::EMPTY,
+            |total, (production, structure_type)| match structure_type
+                .and_then(StructureType::job_slots)
+            {
+                Some((job, _)) => {
+                    let staffing = staffing_percent(
+                        jobs.get(job),
+                        slots.get(&job).copied().unwrap_or_default(),
+                    );
+                    total + production.0.percent(staffing)
+                }
+                None => total + production.0,
+            },
+        );
+
+        let consumption = self
+            .consumption_query
+            .iter()
+            .fold(ResourceBundle::EMPTY, |total, consumption| {
+                total + consumption.0
+            });
+
+        let production = self.mutators.production(production);
+        let hungry = consumption
+            .get(ResourceKind::Food)
+            .saturating_sub(food + production.get(ResourceKind::Food));
+
+        DawnForecast {
+            gold: self.mutators.income(population.min(population_capacity)),
+            production,
+            consumption,
+            population_capacity,
+            hungry,
+            newcomers: jobs.resting(population) / RESTING_PER_NEWCOMER,
+        }
+    }
+}
+
 pub fn update_income(
     mut population: ResMut<VillagePopulation>,
-    mut gold: ResMut<VillageGold>,
-    income_query: Query<&Income, With<Structure>>,
-    cap_query: Query<&PopulationCapacity, With<Structure>>,
+    mut stock: ResMut<VillageStock>,
+    mut jobs: ResMut<VillageJobs>,
+    mut ledger: ResMut<Ledger>,
+    mut rng: ResMut<GameRng>,
+    mutators: Res<ActiveMutators>,
+    sources: IncomeSources,
 ) {
-    let mut total_population_capacity = 0;
-    for population_capacity in cap_query.iter() {
-        total_population_capacity += population_capacity.0;
-    }
+    let forecast = sources.forecast(population.0, &jobs, stock.get(ResourceKind::Food));
 
-    gold.0 += population.0.min(total_population_capacity);
+    let gold = stock.get(ResourceKind::Gold);
+    stock.deposit(&(ResourceBundle::gold(forecast.gold) + forecast.production));
+    stock.withdraw(&forecast.consumption);
+    ledger.gold(
+        LedgerSource::Income,
+        stock.get(ResourceKind::Gold) as i32 - gold as i32,
+    );
 
-    for income in income_query.iter() {
-        gold.0 += income.0;
+    // Villagers leave when there isn't enough food to go around
+    if forecast.hungry > 0 {
+        let lost = forecast.hungry.min(population.0);
+        population.0 -= lost;
+        ledger.population(LedgerSource::Starvation, -(lost as i32));
+    } else {
+        let newcomers = rng
+            .stream(RngStream::Population)
+            .gen_range(POPULATION_GROWTH);
+        let growth = mutators
+            .growth(newcomers + forecast.newcomers)
+            .min(forecast.population_capacity.saturating_sub(population.0));
+        population.0 += growth;
+        ledger.population(LedgerSource::Growth, growth as i32);
     }
-    let mut rng = rand::thread_rng();
-    population.0 += 5 + rng.gen_range(0..10);
-    population.0 = population.0.min(total_population_capacity);
+
+    // Destroyed homes leave villagers without a place to live
+    let homeless = population.0.saturating_sub(forecast.population_capacity);
+    population.0 -= homeless;
+    ledger.population(LedgerSource::Homeless, -(homeless as i32));
+
+    jobs.fit_to(population.0);
 }
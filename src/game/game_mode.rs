@@ -0,0 +1,77 @@
+//! Campaign, endless and daily challenge runs, picked on the title screen.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use strum_macros::{EnumIter, IntoStaticStr};
+
+use super::constants::{ENDLESS_WAVE_GROWTH, MAX_WAVE_SIZE};
+use super::cycle::calendar::Calendar;
+
+pub struct GameModePlugin;
+
+impl Plugin for GameModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameMode>();
+    }
+}
+
+#[derive(
+    Resource,
+    EnumIter,
+    IntoStaticStr,
+    Serialize,
+    Deserialize,
+    Default,
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+)]
+pub enum GameMode {
+    /// Survive as long as possible, the seasons come back as they are.
+    #[default]
+    Campaign,
+    /// The seasons start over with ever stronger waves until the village falls.
+    Endless,
+    /// A campaign seeded from the date with a few extra rules.
+    Daily,
+}
+
+impl std::fmt::Display for GameMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        <&str>::from(self).fmt(f)
+    }
+}
+
+impl GameMode {
+    pub fn next(self) -> Self {
+        match self {
+            GameMode::Campaign => GameMode::Endless,
+            GameMode::Endless => GameMode::Daily,
+            GameMode::Daily => GameMode::Campaign,
+        }
+    }
+
+    /// Hit points added to every enemy, endless runs add one for every year survived.
+    pub fn enemy_health_bonus(self, day: u32, calendar: &Calendar) -> u32 {
+        match self {
+            GameMode::Campaign | GameMode::Daily => 0,
+            GameMode::Endless => years_survived(day, calendar),
+        }
+    }
+
+    /// Enemies in a wave at most, endless runs make room for more every year survived.
+    pub fn max_wave_size(self, day: u32, calendar: &Calendar) -> usize {
+        match self {
+            GameMode::Campaign | GameMode::Daily => MAX_WAVE_SIZE,
+            GameMode::Endless => {
+                MAX_WAVE_SIZE + years_survived(day, calendar) as usize * ENDLESS_WAVE_GROWTH
+            }
+        }
+    }
+}
+
+fn years_survived(day: u32, calendar: &Calendar) -> u32 {
+    day.checked_div(calendar.year_length()).unwrap_or_default()
+}
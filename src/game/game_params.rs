@@ -1,7 +1,9 @@
 use crate::path_finding::distance_map;
 use crate::path_finding::find_all;
 use crate::path_finding::tiles::Tile;
+use crate::path_finding::tiles::TileDim;
 use crate::path_finding::tiles::TileDir;
+use crate::path_finding::tiles::TileRect;
 
 use super::actors::stats::Health;
 use super::actors::stats::Movement;
@@ -129,13 +131,33 @@ impl Game<'_, '_> {
         self.structures.iter().filter_map(|entity| self.get(entity))
     }
 
+    /// Every tile covered by a structure's footprint.
+    pub fn structure_tiles(&self) -> impl Iterator<Item = (Tile, Entity)> + '_ {
+        self.structures.iter().flat_map(|entity| {
+            self.map
+                .actors
+                .footprint_tiles(entity)
+                .map(move |tile| (tile, entity))
+        })
+    }
+
     pub fn insert(&mut self, tile: Tile, entity: Entity) -> InsertedActor {
+        let footprint = self.map.actors.footprint(entity);
+        self.insert_with_footprint(tile, entity, footprint)
+    }
+
+    pub fn insert_with_footprint(
+        &mut self,
+        tile: Tile,
+        entity: Entity,
+        footprint: TileDim,
+    ) -> InsertedActor {
         assert!(
             self.contains(tile),
             "{tile:?} outside of map bounds {:?}",
             self.map.bounds()
         );
-        match self.map.actors.set(tile, entity) {
+        match self.map.actors.set_with_footprint(tile, entity, footprint) {
             Overwritten::Left(_, entity) => InsertedActor::ReplacedActor(entity),
             Overwritten::Right(tile, _) => InsertedActor::MovedFrom(tile),
             _ => InsertedActor::Placed,
@@ -151,7 +173,7 @@ impl Game<'_, '_> {
             return false;
         };
 
-        let structures: HashSet<Tile> = self.structures().map(|(tile, _)| tile).collect();
+        let structures: HashSet<Tile> = self.structure_tiles().map(|(tile, _)| tile).collect();
         let navigator = |tile| {
             let structures = &structures;
             self.edge_adjacent(tile)
@@ -166,8 +188,10 @@ impl Game<'_, '_> {
     /// - from the tile there exists a walkable path to every perimeter tile on the map
     /// - it is within two tiles distance (by edge steps) of another structure
     ///     (losing all buildings is defeat)
-    pub fn find_tiles_that_can_be_built_on(&self) -> HashSet<Tile> {
-        let structures: HashSet<Tile> = self.structures().map(|(tile, _)| tile).collect();
+    ///
+    /// Returned tiles are anchors such that every tile of `footprint` can be built on.
+    pub fn find_tiles_that_can_be_built_on(&self, footprint: TileDim) -> HashSet<Tile> {
+        let structures: HashSet<Tile> = self.structure_tiles().map(|(tile, _)| tile).collect();
 
         let distance_map = distance_map(structures.iter().copied(), |t| {
             t.edge_adjacent()
@@ -208,7 +232,17 @@ impl Game<'_, '_> {
 
         let mut reachable = find_all(start, navigator);
         reachable.remove(&start);
+
         reachable
+            .iter()
+            .copied()
+            .filter(|anchor| {
+                // hidden traps count as built on
+                TileRect::from_origin(*anchor, footprint)
+                    .into_iter()
+                    .all(|tile| reachable.contains(&tile) && !self.map.traps.contains_key(&tile))
+            })
+            .collect()
     }
 
     pub fn iter_terrain(&self) -> impl Iterator<Item = (Tile, Terrain)> + '_ {
@@ -286,6 +320,16 @@ impl ReadGame<'_, '_> {
         self.structures.iter().filter_map(|entity| self.get(entity))
     }
 
+    /// Every tile covered by a structure's footprint.
+    pub fn structure_tiles(&self) -> impl Iterator<Item = (Tile, Entity)> + '_ {
+        self.structures.iter().flat_map(|entity| {
+            self.map()
+                .actors
+                .footprint_tiles(entity)
+                .map(move |tile| (tile, entity))
+        })
+    }
+
     pub fn all_structures_reachable(&self) -> bool {
         let Some(start) = self.perimeter().find(|tile| {
             self.terrain(*tile)
@@ -295,7 +339,7 @@ impl ReadGame<'_, '_> {
             return false;
         };
 
-        let structures: HashSet<Tile> = self.structures().map(|(tile, _)| tile).collect();
+        let structures: HashSet<Tile> = self.structure_tiles().map(|(tile, _)| tile).collect();
         let navigator = |tile| {
             let structures = &structures;
             self.edge_adjacent(tile)
@@ -310,8 +354,10 @@ impl ReadGame<'_, '_> {
     /// - from the tile there exists a walkable path to every perimeter tile on the map
     /// - it is within two tiles distance (by edge steps) of another structure
     ///     (losing all buildings is defeat)
-    pub fn find_tiles_that_can_be_built_on(&self) -> HashSet<Tile> {
-        let structures: HashSet<Tile> = self.structures().map(|(tile, _)| tile).collect();
+    ///
+    /// Returned tiles are anchors such that every tile of `footprint` can be built on.
+    pub fn find_tiles_that_can_be_built_on(&self, footprint: TileDim) -> HashSet<Tile> {
+        let structures: HashSet<Tile> = self.structure_tiles().map(|(tile, _)| tile).collect();
 
         let distance_map = distance_map(structures.iter().copied(), |t| {
             t.edge_adjacent()
@@ -352,7 +398,17 @@ impl ReadGame<'_, '_> {
 
         let mut reachable = find_all(start, navigator);
         reachable.remove(&start);
+
         reachable
+            .iter()
+            .copied()
+            .filter(|anchor| {
+                // hidden traps count as built on
+                TileRect::from_origin(*anchor, footprint)
+                    .into_iter()
+                    .all(|tile| reachable.contains(&tile) && !self.map().traps.contains_key(&tile))
+            })
+            .collect()
     }
 
     pub fn iter_terrain(&self) -> impl Iterator<Item = (Tile, Terrain)> + '_ {
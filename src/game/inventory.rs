@@ -1,12 +1,16 @@
 use bevy::prelude::*;
 
-use crate::path_finding::tiles::TileDir;
+use bevy::utils::HashSet;
+
+use crate::path_finding::tiles::{Tile, TileDim, TileDir};
 
 use super::assets::SfxKey;
+use super::constants::VILLAGE_STASH_SIZE;
 
 pub const ITEM_TEMPLATES: &[Item] = &[
     Item {
         name: "axe",
+        icon: "axe",
         description: "Axe, mid range weapon. (Land only)",
         health_effect: -2,
         item_count: 2,
@@ -14,10 +18,15 @@ pub const ITEM_TEMPLATES: &[Item] = &[
         range: 2,
         cost: 10,
         air: false,
+        shape: EffectShape::Single,
+        thrown: false,
+        tier: 1,
+        upgrades: 0,
         sfx: SfxKey::AxeSlash,
     },
     Item {
         name: "dagger",
+        icon: "dagger",
         description: "Dagger, close range high damage weapon. (Land only)",
         health_effect: -4,
         item_count: 1,
@@ -25,10 +34,15 @@ pub const ITEM_TEMPLATES: &[Item] = &[
         range: 1,
         cost: 40,
         air: false,
+        shape: EffectShape::Single,
+        thrown: false,
+        tier: 1,
+        upgrades: 0,
         sfx: SfxKey::SwordSlash,
     },
     Item {
         name: "sword",
+        icon: "sword",
         description: "Sword, mid range weapon. (Land only)",
         health_effect: -1,
         item_count: 1,
@@ -36,10 +50,15 @@ pub const ITEM_TEMPLATES: &[Item] = &[
         range: 2,
         cost: 30,
         air: false,
+        shape: EffectShape::Single,
+        thrown: false,
+        tier: 1,
+        upgrades: 0,
         sfx: SfxKey::SwordSlash,
     },
     Item {
         name: "whip",
+        icon: "whip",
         description: "Whip, long range low damage weapon. (Land & Air)",
         health_effect: -1,
         item_count: 1,
@@ -47,10 +66,15 @@ pub const ITEM_TEMPLATES: &[Item] = &[
         range: 3,
         cost: 20,
         air: true,
+        shape: EffectShape::Single,
+        thrown: false,
+        tier: 1,
+        upgrades: 0,
         sfx: SfxKey::Whip,
     },
     Item {
         name: "bow",
+        icon: "bow",
         description: "Bow, long range weapon. (Land & Air)",
         health_effect: -2,
         item_count: 2,
@@ -58,10 +82,15 @@ pub const ITEM_TEMPLATES: &[Item] = &[
         range: 3,
         cost: 30,
         air: true,
+        shape: EffectShape::Single,
+        thrown: false,
+        tier: 1,
+        upgrades: 0,
         sfx: SfxKey::ArrowFire,
     },
     Item {
         name: "health_potion",
+        icon: "health_potion",
         description: "Healing potion, heals 1 health.",
         health_effect: 1,
         item_count: 1,
@@ -69,10 +98,121 @@ pub const ITEM_TEMPLATES: &[Item] = &[
         range: 2,
         cost: 20,
         air: false,
+        shape: EffectShape::Single,
+        thrown: false,
+        tier: 1,
+        upgrades: 0,
+        sfx: SfxKey::Health,
+    },
+    Item {
+        name: "mace",
+        icon: "mace",
+        description: "Mace, close range crushing weapon. (Land only)",
+        health_effect: -3,
+        item_count: 2,
+        directions: &TileDir::ALL,
+        range: 1,
+        cost: 50,
+        air: false,
+        shape: EffectShape::Single,
+        thrown: false,
+        tier: 2,
+        upgrades: 0,
+        sfx: SfxKey::Hit,
+    },
+    Item {
+        name: "fire_bomb",
+        icon: "fire_potion",
+        description: "Fire bomb, thrown flask bursting over the tiles around it. (Land & Air)",
+        health_effect: -3,
+        item_count: 1,
+        directions: &TileDir::ALL,
+        range: 3,
+        cost: 45,
+        air: true,
+        shape: EffectShape::Cross,
+        thrown: true,
+        tier: 2,
+        upgrades: 0,
+        sfx: SfxKey::ArrowFire,
+    },
+    Item {
+        name: "healing_mist",
+        icon: "healing_potion",
+        description: "Healing mist, thrown flask healing everyone close to where it lands.",
+        health_effect: 1,
+        item_count: 1,
+        directions: &TileDir::ALL,
+        range: 3,
+        cost: 35,
+        air: true,
+        shape: EffectShape::Radius(1),
+        thrown: true,
+        tier: 2,
+        upgrades: 0,
         sfx: SfxKey::Health,
     },
 ];
 
+/// Area an item's effect covers, everything standing in it is affected, friend or foe.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EffectShape {
+    /// Only the target tile.
+    Single,
+    /// Target tile and the tiles along its edges.
+    Cross,
+    /// Every tile up to this many steps from the target, diagonals included.
+    Radius(u32),
+    /// This many tiles from the target onwards, away from the user.
+    Line(u32),
+    /// Widening fan of this many rows from the target onwards, away from the user.
+    Cone(u32),
+}
+
+impl EffectShape {
+    /// Tiles affected when used from `from` on `target`.
+    pub fn area(self, from: Tile, target: Tile) -> HashSet<Tile> {
+        let offset = from.difference(target);
+        let heading = TileDim(offset.x().signum(), offset.y().signum());
+        let square = |radius: i32| {
+            (-radius..=radius)
+                .flat_map(move |x| (-radius..=radius).map(move |y| target + TileDim(x, y)))
+        };
+
+        match self {
+            EffectShape::Single => HashSet::from_iter([target]),
+            EffectShape::Cross => HashSet::from_iter(
+                std::iter::once(target).chain(TileDir::EDGES.map(|edge| target.step(edge))),
+            ),
+            EffectShape::Radius(radius) => square(radius as i32).collect(),
+            EffectShape::Line(length) => (0..length.max(1) as i32)
+                .map(|step| target + TileDim(heading.x() * step, heading.y() * step))
+                .collect(),
+            EffectShape::Cone(rows) => square(rows.max(1) as i32 - 1)
+                .filter(|tile| {
+                    let d = target.difference(*tile);
+                    let along = d.x() * heading.x() + d.y() * heading.y();
+                    let across = d.y() * heading.x() - d.x() * heading.y();
+                    along >= across.abs()
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Highest item tier available without a blacksmith.
+pub const BASE_ITEM_TIER: u32 = 1;
+
+/// Maximum number of blacksmith upgrades a single item can receive.
+pub const MAX_ITEM_UPGRADES: u32 = 2;
+
+/// Item templates that can be stocked when items up to `max_tier` are unlocked.
+pub fn available_items(max_tier: u32) -> impl Iterator<Item = &'static Item> {
+    ITEM_TEMPLATES
+        .iter()
+        .filter(move |item| item.tier <= max_tier)
+}
+
 /// Maximum number of the items a character can
 #[derive(Component, Debug)]
 pub struct MaxInventorySize(pub u32);
@@ -84,7 +224,7 @@ impl Default for MaxInventorySize {
 }
 
 /// List of an entity's equipped items
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone)]
 pub struct Inventory {
     pub selected_item: Option<usize>,
     item_slots: Vec<Option<Item>>,
@@ -100,6 +240,14 @@ impl Default for Inventory {
 }
 
 impl Inventory {
+    /// Default slots holding a single starting item.
+    pub fn with_item(item: Item) -> Self {
+        let mut inventory = Self::default();
+        inventory.clear();
+        inventory.set(0, item);
+        inventory
+    }
+
     pub fn slot_count(&self) -> usize {
         self.item_slots.len()
     }
@@ -122,6 +270,11 @@ impl Inventory {
         previous
     }
 
+    /// Put the item in the slot, or empty it, returns what was there.
+    pub fn replace(&mut self, slot: usize, item: Option<Item>) -> Option<Item> {
+        std::mem::replace(&mut self.item_slots[slot], item)
+    }
+
     pub fn get_empty_slot(&self) -> Option<usize> {
         self.item_slots.iter().position(|i| i.is_none())
     }
@@ -133,6 +286,29 @@ impl Inventory {
     }
 }
 
+/// Items kept in the village for any hero to pick up.
+#[derive(Resource, Default, Debug)]
+pub struct VillageStash(pub Vec<Item>);
+
+impl VillageStash {
+    pub fn is_full(&self) -> bool {
+        self.0.len() >= VILLAGE_STASH_SIZE
+    }
+
+    /// Returns the item back if there is no room for it.
+    pub fn store(&mut self, item: Item) -> Result<(), Item> {
+        if self.is_full() {
+            return Err(item);
+        }
+        self.0.push(item);
+        Ok(())
+    }
+
+    pub fn take(&mut self, index: usize) -> Option<Item> {
+        (index < self.0.len()).then(|| self.0.remove(index))
+    }
+}
+
 #[derive(Component, Default, Debug, Clone)]
 pub struct ItemIcon {
     pub icon: Handle<Image>,
@@ -157,10 +333,12 @@ pub struct ItemName(pub String);
 // }
 
 /// Marking that this entity is just a reference item.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Item {
     /// Name of the item
     pub name: &'static str,
+    /// Key of the item's icon in the icon set
+    pub icon: &'static str,
     /// Long description of the item
     pub description: &'static str,
     /// Positive for healing effect, negative for attack effect.
@@ -172,5 +350,73 @@ pub struct Item {
     /// Cost of the item in gold coins
     pub cost: u32,
     pub air: bool,
+    /// Tiles around the target the effect spreads to.
+    pub shape: EffectShape,
+    /// Thrown items land on any tile in range, over anything in between.
+    pub thrown: bool,
+    /// Items above tier 1 are only stocked once a blacksmith is built.
+    pub tier: u32,
+    /// Number of blacksmith upgrades applied to this item.
+    pub upgrades: u32,
     pub sfx: SfxKey,
 }
+
+impl Item {
+    /// The template this item was bought as.
+    pub fn template(&self) -> Option<&'static Item> {
+        ITEM_TEMPLATES
+            .iter()
+            .find(|template| template.name == self.name)
+    }
+
+    /// Number of uses the item has when freshly bought.
+    pub fn max_count(&self) -> u32 {
+        self.template()
+            .map(|template| template.item_count)
+            .unwrap_or(self.item_count)
+    }
+
+    pub fn can_upgrade(&self) -> bool {
+        self.upgrades < MAX_ITEM_UPGRADES
+    }
+
+    /// Strengthen the item's effect by one point, keeping its sign.
+    pub fn upgrade_effect(&mut self) {
+        self.health_effect += self.health_effect.signum();
+        self.upgrades += 1;
+    }
+
+    pub fn upgrade_range(&mut self) {
+        self.range += 1;
+        self.upgrades += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effect_shape_area() {
+        let from = Tile(0, 2);
+        let target = Tile(2, 2);
+
+        assert_eq!(EffectShape::Single.area(from, target).len(), 1);
+        assert_eq!(EffectShape::Cross.area(from, target).len(), 5);
+        assert_eq!(EffectShape::Radius(1).area(from, target).len(), 9);
+
+        let line = EffectShape::Line(3).area(from, target);
+        assert_eq!(
+            line,
+            HashSet::from_iter([Tile(2, 2), Tile(3, 2), Tile(4, 2)])
+        );
+
+        // Opens up away from the user, one tile wider on each side every row
+        let cone = EffectShape::Cone(2).area(from, target);
+        assert_eq!(
+            cone,
+            HashSet::from_iter([Tile(2, 2), Tile(3, 1), Tile(3, 2), Tile(3, 3)])
+        );
+        assert!(!cone.contains(&Tile(1, 2)));
+    }
+}
@@ -1,3 +1,5 @@
+use bevy::ecs::system::SystemParam;
+use bevy::utils::HashSet;
 use bevy::{color::palettes::css, prelude::*};
 use bevy_trauma_shake::TraumaCommands;
 
@@ -8,26 +10,44 @@ use crate::path_finding::tiles::Tile;
 use crate::screen::playing::GameState;
 use crate::screen::Screen;
 
-use super::actors::{stats::Health, ActorTurnState, ClearUndoEvent, EnemyActor};
-use super::inventory::{Inventory, Item};
+use super::actors::class::Experience;
+use super::actors::player::{move_unit, PlayerSprite};
+use super::actors::{stats::Health, ActorTurnState, ClearUndoEvent, EnemyActor, IsAirborne};
+use super::inventory::{Inventory, Item, VillageStash};
 use super::map::VillageMap;
+use super::mutators::Damage;
+use super::picking::{PickedTile, TilePressedEvent};
 use super::selection::{self, SelectedActor, SelectedTiles, SelectionEvent};
+use super::turn_order::Side;
 use super::vfx::{FireOneShotVfx, OneShotVfx};
 
 pub struct ItemPlugin;
 
 impl Plugin for ItemPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<InventorySelection>().add_systems(
-            Update,
-            (
-                show_attack_range,
-                apply_item_effect.after(selection::set_selected_unit),
-                deselect_inventory_on_click,
+        app.init_resource::<InventorySelection>()
+            .init_resource::<VillageStash>()
+            .add_systems(
+                OnEnter(Screen::Playing),
+                |mut stash: ResMut<VillageStash>| {
+                    stash.0.clear();
+                },
             )
-                .chain()
-                .run_if(in_state(GameState::BattleTurn).and_then(in_state(Screen::Playing))),
-        );
+            .add_systems(
+                Update,
+                (
+                    show_attack_range,
+                    apply_item_effect.after(selection::set_selected_unit),
+                    throw_item.after(move_unit),
+                    deselect_inventory_on_click,
+                    preview_item_area.run_if(
+                        resource_changed::<PickedTile>
+                            .or_else(resource_changed::<InventorySelection>),
+                    ),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::BattleTurn).and_then(in_state(Screen::Playing))),
+            );
     }
 }
 
@@ -37,8 +57,38 @@ pub struct InventorySelection {
     pub tile: Tile,
 }
 
+impl InventorySelection {
+    /// A thrown item is being aimed, pressed tiles are its target.
+    pub fn is_throwing(&self) -> bool {
+        self.selection.is_some_and(|(item, ..)| item.thrown)
+    }
+}
+
+/// Tiles within the item's range from `from`, whether reachable or not.
+pub fn item_range(item: &Item, from: Tile) -> HashSet<Tile> {
+    find_all_within_distance_unweighted(from, item.range, |t| {
+        item.directions.iter().copied().map(move |d| t.step(d))
+    })
+}
+
+/// Land only items can't reach flying targets, air items reach them over anything in between.
+/// Otherwise nothing may stand between user and target.
+pub fn can_reach(
+    village_map: &VillageMap,
+    item: &Item,
+    from: Tile,
+    to: Tile,
+    target_airborne: bool,
+) -> bool {
+    match target_airborne {
+        true => item.air,
+        false => village_map.actors.has_line_of_sight(from, to),
+    }
+}
+
 fn show_attack_range(
     q_inventories: Query<(Entity, &Inventory, &ActorTurnState), Changed<Inventory>>,
+    q_airborne: Query<(), With<IsAirborne>>,
     mut selection_tiles: ResMut<SelectedTiles>,
     village_map: Res<VillageMap>,
     mut inventory_selection: ResMut<InventorySelection>,
@@ -53,12 +103,16 @@ fn show_attack_range(
                 continue;
             };
 
-            let possible_action_tiles =
-                find_all_within_distance_unweighted(tile, item.range, |t| {
-                    item.directions.iter().copied().map(move |d| t.step(d))
-                });
+            let (tiles, blocked) = item_range(&item, tile).into_iter().partition(|&target| {
+                let target_airborne = village_map
+                    .actors
+                    .get(target)
+                    .is_some_and(|entity| q_airborne.contains(entity));
+                item.thrown || can_reach(&village_map, &item, tile, target, target_airborne)
+            });
 
-            selection_tiles.tiles = possible_action_tiles;
+            selection_tiles.tiles = tiles;
+            selection_tiles.blocked = blocked;
             selection_tiles.color = css::ORANGE.into();
 
             *inventory_selection = InventorySelection {
@@ -79,17 +133,110 @@ fn deselect_inventory_on_click(
     }
 }
 
+/// Everything needed to apply an item's effect on the map.
+#[derive(SystemParam)]
+pub struct ItemStrike<'w, 's> {
+    commands: Commands<'w, 's>,
+    q_healths: Query<'w, 's, &'static mut Health>,
+    q_enemy_units: Query<'w, 's, (), With<EnemyActor>>,
+    village_map: ResMut<'w, VillageMap>,
+    evw_oneshot_vfx: EventWriter<'w, FireOneShotVfx>,
+    clear_undo_event: EventWriter<'w, ClearUndoEvent>,
+    damage: Damage<'w>,
+}
+
+impl ItemStrike<'_, '_> {
+    /// Extra effect the item has in the hands of `user`.
+    fn bonus(&self, item: &Item, user: Entity, sprite: Option<&PlayerSprite>) -> u32 {
+        match (sprite, self.q_healths.get(user)) {
+            (Some(sprite), Ok(user_health)) => sprite.class().item_bonus(item, *user_health),
+            _ => 0,
+        }
+    }
+
+    /// Apply the item to everything standing in `area`, returns the number of enemies killed.
+    fn apply(&mut self, item: &Item, bonus: u32, area: &HashSet<Tile>) -> u32 {
+        let mut targets: Vec<(Entity, Tile)> = area
+            .iter()
+            .filter_map(|&tile| self.village_map.actors.get(tile).map(|e| (e, tile)))
+            .collect();
+        // Large buildings covering several tiles are only hit once
+        targets.sort_by_key(|(entity, _)| *entity);
+        targets.dedup_by_key(|(entity, _)| *entity);
+
+        let mut kills = 0;
+        for (entity, tile) in targets {
+            let Ok(mut health) = self.q_healths.get_mut(entity) else {
+                continue;
+            };
+
+            if item.health_effect > 0 {
+                health.value = health.heal(item.health_effect as u32 + bonus);
+                continue;
+            }
+
+            let damage = self.damage.dealt(
+                Side::Heroes,
+                Side::of(self.q_enemy_units.contains(entity)),
+                item.health_effect.unsigned_abs() + bonus,
+            );
+            health.value = health.hurt(damage);
+
+            let mut tile_trans = tile_coord_translation(tile.x() as f32, tile.y() as f32, 3.0);
+            tile_trans.y += 100.0;
+
+            self.evw_oneshot_vfx.send(FireOneShotVfx(
+                OneShotVfx::AttackFlash,
+                Transform::from_translation(tile_trans),
+            ));
+            self.evw_oneshot_vfx.send(FireOneShotVfx(
+                OneShotVfx::BloodSplash,
+                Transform::from_translation(tile_trans),
+            ));
+            self.commands.add_trauma(0.5);
+
+            if health.value == 0 && self.q_enemy_units.contains(entity) {
+                self.village_map.actors.remove_entity(entity);
+                kills += 1;
+            }
+        }
+        kills
+    }
+
+    /// Use up one of the item in `index` and end the user's action.
+    fn use_up(
+        &mut self,
+        mut item: Item,
+        inventory: &mut Inventory,
+        index: usize,
+        turn_state: &mut ActorTurnState,
+    ) {
+        self.commands.trigger(PlaySfx::Key(item.sfx));
+
+        item.item_count = item.item_count.saturating_sub(1);
+        if item.item_count > 0 {
+            // Set back item if it is not used up yet.
+            inventory.set(index, item);
+        }
+
+        turn_state.used_action = true;
+
+        self.clear_undo_event.send(ClearUndoEvent);
+    }
+}
+
 fn apply_item_effect(
-    mut commands: Commands,
-    mut q_inventories: Query<(&mut Inventory, &mut ActorTurnState)>,
-    mut q_healths: Query<&mut Health>,
-    q_enemy_units: Query<(), With<EnemyActor>>,
-    mut village_map: ResMut<VillageMap>,
+    mut q_inventories: Query<(
+        &mut Inventory,
+        &mut ActorTurnState,
+        Option<&PlayerSprite>,
+        Option<&mut Experience>,
+    )>,
+    q_airborne: Query<(), With<IsAirborne>>,
     selected_unit: Res<SelectedActor>,
     inventory_selection: Res<InventorySelection>,
     mut selection_events: EventReader<SelectionEvent>,
-    mut clear_undo_event: EventWriter<ClearUndoEvent>,
-    mut evw_oneshot_vfx: EventWriter<FireOneShotVfx>,
+    mut strike: ItemStrike,
 ) {
     if selection_events.is_empty() {
         return;
@@ -97,82 +244,181 @@ fn apply_item_effect(
         selection_events.clear();
     }
 
-    let Some((mut item, origin_entity, index)) = inventory_selection.selection else {
+    // Thrown items land on tiles rather than units
+    let Some((item, origin_entity, index)) = inventory_selection
+        .selection
+        .filter(|(item, ..)| item.thrown == false)
+    else {
         return;
     };
     let Some(target_entity) = selected_unit.entity else {
         return;
     };
-    let Some(target_tile) = village_map.actors.locate(target_entity) else {
+    let Some(target_tile) = strike.village_map.actors.locate(target_entity) else {
         return;
     };
-    let Ok((mut inventory, mut turn_state)) = q_inventories.get_mut(origin_entity) else {
+    let Ok((mut inventory, mut turn_state, sprite, experience)) =
+        q_inventories.get_mut(origin_entity)
+    else {
         return;
     };
 
-    if turn_state.used_action {
+    if turn_state.used_action || strike.q_healths.contains(target_entity) == false {
         return;
     }
 
     // Cannot apply negative effect on player units
-    if q_enemy_units.contains(target_entity) == false && item.health_effect < 0 {
+    if strike.q_enemy_units.contains(target_entity) == false && item.health_effect < 0 {
         return;
     }
 
     // Cannot apply positive effect on enemy units
-    if q_enemy_units.contains(target_entity) && item.health_effect > 0 {
+    if strike.q_enemy_units.contains(target_entity) && item.health_effect > 0 {
         return;
     }
 
     info!("Using item: {}", item.name);
 
-    let possible_action_tiles =
-        find_all_within_distance_unweighted(inventory_selection.tile, item.range, |t| {
-            item.directions.iter().copied().map(move |d| t.step(d))
-        });
-
-    if possible_action_tiles.contains(&target_tile) {
-        if let Ok(mut health) = q_healths.get_mut(target_entity) {
-            if item.health_effect > 0 {
-                health.value += item.health_effect as u32;
-            } else {
-                health.value = health
-                    .value
-                    .saturating_sub(item.health_effect.unsigned_abs());
-
-                let mut tile_trans =
-                    tile_coord_translation(target_tile.x() as f32, target_tile.y() as f32, 3.0);
-                tile_trans.y += 100.0;
-
-                evw_oneshot_vfx.send(FireOneShotVfx(
-                    OneShotVfx::AttackFlash,
-                    Transform::from_translation(tile_trans),
-                ));
-                evw_oneshot_vfx.send(FireOneShotVfx(
-                    OneShotVfx::BloodSplash,
-                    Transform::from_translation(tile_trans),
-                ));
-                commands.add_trauma(0.5);
-
-                if health.value == 0 {
-                    village_map.actors.remove_entity(target_entity);
-                }
+    let target_airborne = q_airborne.contains(target_entity);
+    if item_range(&item, inventory_selection.tile).contains(&target_tile)
+        && can_reach(
+            &strike.village_map,
+            &item,
+            inventory_selection.tile,
+            target_tile,
+            target_airborne,
+        )
+    {
+        let bonus = strike.bonus(&item, origin_entity, sprite);
+        let area = item.shape.area(inventory_selection.tile, target_tile);
+        let kills = strike.apply(&item, bonus, &area);
+        if let Some(mut experience) = experience {
+            for _ in 0..kills {
+                experience.gain_kill();
             }
+        }
+
+        strike.use_up(item, &mut inventory, index, &mut turn_state);
+    }
+}
+
+/// Thrown items land on the pressed tile and hit everything around it.
+fn throw_item(
+    mut tile_pressed_events: EventReader<TilePressedEvent>,
+    mut q_inventories: Query<(
+        &mut Inventory,
+        &mut ActorTurnState,
+        Option<&PlayerSprite>,
+        Option<&mut Experience>,
+    )>,
+    mut inventory_selection: ResMut<InventorySelection>,
+    mut selected_tiles: ResMut<SelectedTiles>,
+    mut strike: ItemStrike,
+) {
+    let Some(&TilePressedEvent(target_tile)) = tile_pressed_events.read().last() else {
+        return;
+    };
+    let Some((item, origin_entity, index)) = inventory_selection
+        .selection
+        .filter(|(item, ..)| item.thrown)
+    else {
+        return;
+    };
+    let Ok((mut inventory, mut turn_state, sprite, experience)) =
+        q_inventories.get_mut(origin_entity)
+    else {
+        return;
+    };
 
-            commands.trigger(PlaySfx::Key(item.sfx));
+    if turn_state.used_action || !item_range(&item, inventory_selection.tile).contains(&target_tile)
+    {
+        return;
+    }
 
-            println!("Successfully used item: {}", item.name);
+    info!("Throwing item: {}", item.name);
 
-            // Use up one item
-            item.item_count = item.item_count.saturating_sub(1);
-            if item.item_count > 0 {
-                // Set back item if it is not used up yet.
-                inventory.set(index, item);
-            }
+    let bonus = strike.bonus(&item, origin_entity, sprite);
+    let area = item.shape.area(inventory_selection.tile, target_tile);
+    let kills = strike.apply(&item, bonus, &area);
+    if let Some(mut experience) = experience {
+        for _ in 0..kills {
+            experience.gain_kill();
+        }
+    }
+
+    strike.use_up(item, &mut inventory, index, &mut turn_state);
+    inventory_selection.selection = None;
+    selected_tiles.clear();
+}
+
+/// Show what the selected item would hit when used on the tile under the cursor.
+fn preview_item_area(
+    picked_tile: Res<PickedTile>,
+    inventory_selection: Res<InventorySelection>,
+    mut selected_tiles: ResMut<SelectedTiles>,
+) {
+    let area = match (inventory_selection.selection, picked_tile.0) {
+        (Some((item, ..)), Some(tile)) if selected_tiles.tiles.contains(&tile) => {
+            item.shape.area(inventory_selection.tile, tile)
+        }
+        _ => HashSet::new(),
+    };
 
-            turn_state.used_action = true;
+    if selected_tiles.area != area {
+        selected_tiles.area = area;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::Events;
+    use bevy::ecs::system::RunSystemOnce;
+
+    use crate::game::actors::health_ui;
+    use crate::game::actors::spawn::DespawnAnimation;
+    use crate::game::inventory::{EffectShape, ITEM_TEMPLATES};
+    use crate::game::mutators::ActiveMutators;
+    use crate::path_finding::tiles::TileDim;
+
+    use super::*;
+
+    #[test]
+    fn test_area_strike_takes_down_every_unit() {
+        let mut world = World::new();
+        world.insert_resource(VillageMap::new(TileDim::splat(8)));
+        world.init_resource::<ActiveMutators>();
+        world.init_resource::<Events<FireOneShotVfx>>();
+        world.init_resource::<Events<ClearUndoEvent>>();
+
+        let enemies: Vec<_> = [Tile(2, 2), Tile(3, 3)]
+            .into_iter()
+            .map(|tile| {
+                let enemy = world
+                    .spawn((EnemyActor, Health::new(1), Transform::default()))
+                    .id();
+                world.resource_mut::<VillageMap>().actors.set(tile, enemy);
+                enemy
+            })
+            .collect();
+
+        let bomb = Item {
+            health_effect: -1,
+            shape: EffectShape::Radius(1),
+            ..ITEM_TEMPLATES[0]
+        };
+        let kills = world.run_system_once(move |mut strike: ItemStrike| {
+            strike.apply(&bomb, 0, &bomb.shape.area(Tile(0, 2), Tile(2, 2)))
+        });
+        assert_eq!(kills, 2);
 
-            clear_undo_event.send(ClearUndoEvent);
+        world.run_system_once(health_ui);
+        for enemy in enemies {
+            assert!(world.get::<DespawnAnimation>(enemy).is_some());
+            assert!(world
+                .resource::<VillageMap>()
+                .actors
+                .locate(enemy)
+                .is_none());
         }
     }
 }
@@ -0,0 +1,249 @@
+//! Assigning villagers to jobs during the building turn and the work they do.
+
+use bevy::color::palettes::css;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use sickle_ui::prelude::*;
+use strum::IntoEnumIterator;
+
+use crate::path_finding::tiles::Tile;
+use crate::screen::playing::GameState;
+use crate::screen::Screen;
+use crate::ui::prelude::InteractionPalette;
+
+use super::actors::stats::Health;
+use super::actors::{EnemyActor, Structure};
+use super::assets::SfxKey;
+use super::audio::sfx::PlaySfx;
+use super::components::ArcherTower;
+use super::constants::{TEXT_SIZE, TOWER_DAMAGE, TOWER_RANGE};
+use super::construction::{BuildingSite, JobAssignments, StructureType};
+use super::map::VillageMap;
+use super::mutators::Damage;
+use super::resources::{VillageJobs, VillagePopulation, VillagerJob};
+use super::turn_order::{Side, TurnOrder};
+use super::vfx::{FireOneShotVfx, OneShotVfx};
+
+pub struct JobsPlugin;
+
+impl Plugin for JobsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                job_buttons,
+                update_job_assignments.run_if(
+                    resource_changed::<VillageJobs>.or_else(resource_changed::<VillagePopulation>),
+                ),
+            )
+                .chain()
+                .run_if(in_state(Screen::Playing).and_then(in_state(GameState::BuildingTurn))),
+        )
+        .add_systems(OnEnter(GameState::BuildingTurn), update_job_assignments)
+        .add_systems(
+            OnEnter(GameState::EnemyTurn),
+            manned_towers_fire.run_if(in_state(Screen::Playing)),
+        );
+    }
+}
+
+#[derive(Component, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum JobButton {
+    Assign(VillagerJob),
+    Release(VillagerJob),
+}
+
+/// Villagers needed to fully staff every finished structure, by job.
+pub fn count_job_slots<'a>(
+    structures: impl Iterator<Item = &'a StructureType>,
+) -> HashMap<VillagerJob, u32> {
+    let mut slots = HashMap::new();
+    for (job, count) in structures.filter_map(StructureType::job_slots) {
+        *slots.entry(job).or_default() += count;
+    }
+    slots
+}
+
+/// Percentage of the job slots that are filled.
+pub fn staffing_percent(assigned: u32, slots: u32) -> u32 {
+    match slots {
+        0 => 0,
+        slots => assigned.min(slots) * 100 / slots,
+    }
+}
+
+fn job_buttons(
+    q_interactions: Query<(&Interaction, &JobButton), Changed<Interaction>>,
+    q_structures: Query<&StructureType, (With<Structure>, Without<BuildingSite>)>,
+    mut jobs: ResMut<VillageJobs>,
+    population: Res<VillagePopulation>,
+) {
+    for (interaction, button) in q_interactions.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match *button {
+            JobButton::Assign(job) => {
+                let slots = count_job_slots(q_structures.iter());
+                if jobs.get(job) < slots.get(&job).copied().unwrap_or_default() {
+                    jobs.assign(job, 1, population.0);
+                }
+            }
+            JobButton::Release(job) => jobs.release(job, 1),
+        }
+    }
+}
+
+fn update_job_assignments(
+    mut commands: Commands,
+    q_job_assignments: Query<Entity, With<JobAssignments>>,
+    q_structures: Query<&StructureType, (With<Structure>, Without<BuildingSite>)>,
+    jobs: Res<VillageJobs>,
+    population: Res<VillagePopulation>,
+) {
+    let Ok(container) = q_job_assignments.get_single() else {
+        return;
+    };
+    commands.entity(container).despawn_descendants();
+
+    let slots = count_job_slots(q_structures.iter());
+    let resting = jobs.resting(population.0);
+
+    commands.ui_builder(container).column(|ui| {
+        ui.style()
+            .padding(UiRect::all(Val::Px(10.)))
+            .border(UiRect::all(Val::Px(2.)))
+            .border_color(Color::WHITE.with_alpha(0.3))
+            .background_color(Color::BLACK.with_alpha(0.3))
+            .row_gap(Val::Px(6.));
+
+        ui.row(|ui| {
+            ui.label(LabelConfig::from("Jobs"))
+                .style()
+                .font_size(TEXT_SIZE);
+        });
+
+        for job in VillagerJob::iter() {
+            let count = match job {
+                VillagerJob::Resting => resting.to_string(),
+                job if job.is_assignable() => format!(
+                    "{}/{}",
+                    jobs.get(job),
+                    slots.get(&job).copied().unwrap_or_default()
+                ),
+                job => jobs.get(job).to_string(),
+            };
+
+            ui.row(|ui| {
+                ui.style()
+                    .column_gap(Val::Px(6.))
+                    .justify_content(JustifyContent::End)
+                    .align_items(AlignItems::Center);
+
+                ui.label(LabelConfig::from(format!("{}: {count}", job.name())))
+                    .style()
+                    .font_size(TEXT_SIZE * 0.8);
+
+                if !job.is_assignable() {
+                    return;
+                }
+
+                for (button, text) in [
+                    (JobButton::Release(job), "-"),
+                    (JobButton::Assign(job), "+"),
+                ] {
+                    ui.container(ButtonBundle::default(), |ui| {
+                        ui.label(LabelConfig::from(text))
+                            .style()
+                            .font_size(TEXT_SIZE);
+                    })
+                    .insert((
+                        button,
+                        InteractionPalette {
+                            none: css::TEAL.into(),
+                            hovered: css::DARK_CYAN.into(),
+                            pressed: css::DARK_SLATE_GRAY.into(),
+                        },
+                    ))
+                    .style()
+                    .width(Val::Px(20.))
+                    .justify_content(JustifyContent::Center)
+                    .border_radius(BorderRadius::all(Val::Px(4.)));
+                }
+            });
+        }
+    });
+}
+
+/// Every fully manned tower shoots the closest enemy in range as the enemies start moving.
+/// Manned towers fire once a round, as enemies first act.
+fn manned_towers_fire(
+    mut commands: Commands,
+    mut turn_order: ResMut<TurnOrder>,
+    q_towers: Query<&Tile, (With<ArcherTower>, Without<BuildingSite>)>,
+    mut q_enemies: Query<(Entity, &Transform, &mut Health), With<EnemyActor>>,
+    village_map: Res<VillageMap>,
+    jobs: Res<VillageJobs>,
+    damage: Damage,
+    mut evw_oneshot_vfx: EventWriter<FireOneShotVfx>,
+) {
+    if !turn_order.take_tower_volley() {
+        return;
+    }
+    let Some((_, guards_per_tower)) = StructureType::ArcherTower.job_slots() else {
+        return;
+    };
+    let manned_towers = (jobs.get(VillagerJob::TowerDuty) / guards_per_tower) as usize;
+
+    for tower_tile in q_towers.iter().take(manned_towers) {
+        let target = q_enemies
+            .iter()
+            .filter(|(_, _, health)| health.value > 0)
+            .filter_map(|(entity, ..)| {
+                let tile = village_map.actors.locate(entity)?;
+                let distance = tower_tile.distance_rook(tile);
+                (distance <= TOWER_RANGE).then_some((entity, distance))
+            })
+            .min_by_key(|(_, distance)| *distance);
+
+        let Some((_, transform, mut health)) =
+            target.and_then(|(entity, _)| q_enemies.get_mut(entity).ok())
+        else {
+            continue;
+        };
+
+        health.value = health.hurt(damage.dealt(Side::Heroes, Side::Enemies, TOWER_DAMAGE));
+        evw_oneshot_vfx.send(FireOneShotVfx(
+            OneShotVfx::BloodSplash,
+            Transform::from_translation(transform.translation),
+        ));
+        commands.trigger(PlaySfx::Key(SfxKey::ArrowFire));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_job_slots() {
+        let structures = [
+            StructureType::Farm,
+            StructureType::Farm,
+            StructureType::Quarry,
+            StructureType::House,
+        ];
+        let slots = count_job_slots(structures.iter());
+        assert_eq!(slots.get(&VillagerJob::Farming), Some(&8));
+        assert_eq!(slots.get(&VillagerJob::Gathering), Some(&3));
+        assert_eq!(slots.get(&VillagerJob::TowerDuty), None);
+    }
+
+    #[test]
+    fn test_staffing_percent() {
+        assert_eq!(staffing_percent(3, 4), 75);
+        assert_eq!(staffing_percent(6, 4), 100);
+        assert_eq!(staffing_percent(2, 0), 0);
+    }
+}
@@ -0,0 +1,395 @@
+//! Tracks where the village's gold and villagers come from and go to.
+
+use bevy::color::palettes::css;
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use sickle_ui::prelude::*;
+
+use crate::screen::Screen;
+use crate::ui::palette::LABEL_SIZE;
+use crate::ui::prelude::InteractionPalette;
+
+use super::constants::{BIG_TEXT_SIZE, LEDGER_HISTORY_DAYS, POPULATION_GROWTH, TEXT_SIZE};
+use super::cycle::{TimeOfDay, Turn};
+use super::economy::{update_income, IncomeSources};
+use super::resources::{ResourceKind, VillageJobs, VillagePopulation, VillageStock};
+use super::MODAL_Z_LAYER;
+
+pub struct LedgerPlugin;
+
+impl Plugin for LedgerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Ledger>()
+            .add_systems(OnEnter(Screen::Playing), |mut ledger: ResMut<Ledger>| {
+                *ledger = Ledger::default();
+            })
+            .add_systems(
+                OnEnter(TimeOfDay::Day),
+                close_day
+                    .before(update_income)
+                    .run_if(|turn: Res<Turn>| turn.0 != 0),
+            )
+            .add_systems(
+                Update,
+                (ledger_btn_interaction, close_ledger_btn_interaction)
+                    .run_if(in_state(Screen::Playing)),
+            );
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum LedgerSource {
+    /// Dawn income from the population and production.
+    Income,
+    /// Building sites paid for, cancelled sites are refunded here so that they cancel out.
+    Construction,
+    /// Refunds from demolished structures.
+    Demolition,
+    Repair,
+    Upgrade,
+    MerchantPurchase,
+    Sell,
+    Recruit,
+    /// Nightly pay of mercenaries.
+    Wages,
+    Blacksmith,
+    /// Deals made with dawn visitors.
+    Visitor,
+    Growth,
+    Starvation,
+    /// Villagers without a home after the population capacity dropped.
+    Homeless,
+    /// Villagers lost with their shelter during the night.
+    Casualties,
+}
+
+impl LedgerSource {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LedgerSource::Income => "Income",
+            LedgerSource::Construction => "Construction",
+            LedgerSource::Demolition => "Demolition",
+            LedgerSource::Repair => "Repair",
+            LedgerSource::Upgrade => "Upgrade",
+            LedgerSource::MerchantPurchase => "Merchant purchase",
+            LedgerSource::Sell => "Sell",
+            LedgerSource::Recruit => "Recruit",
+            LedgerSource::Wages => "Wages",
+            LedgerSource::Blacksmith => "Blacksmith",
+            LedgerSource::Visitor => "Visitor",
+            LedgerSource::Growth => "Growth",
+            LedgerSource::Starvation => "Starvation",
+            LedgerSource::Homeless => "Homeless",
+            LedgerSource::Casualties => "Casualties",
+        }
+    }
+}
+
+/// Gold and population change of a single source.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct LedgerChange {
+    pub gold: i32,
+    pub population: i32,
+}
+
+impl std::ops::AddAssign for LedgerChange {
+    fn add_assign(&mut self, other: Self) {
+        self.gold += other.gold;
+        self.population += other.population;
+    }
+}
+
+impl std::fmt::Display for LedgerChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        if self.gold != 0 {
+            parts.push(format!("{:+} gold", self.gold));
+        }
+        if self.population != 0 {
+            parts.push(format!("{:+} villagers", self.population));
+        }
+        match parts.is_empty() {
+            true => write!(f, "no change"),
+            false => write!(f, "{}", parts.join(", ")),
+        }
+    }
+}
+
+/// Changes of a single day, grouped by source in the order they first happened.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DayLedger {
+    pub day: u32,
+    pub changes: Vec<(LedgerSource, LedgerChange)>,
+}
+
+impl DayLedger {
+    fn record(&mut self, source: LedgerSource, change: LedgerChange) {
+        match self.changes.iter_mut().find(|(s, _)| *s == source) {
+            Some((_, total)) => *total += change,
+            None => self.changes.push((source, change)),
+        }
+    }
+
+    pub fn total(&self) -> LedgerChange {
+        let mut total = LedgerChange::default();
+        for (_, change) in self.changes.iter() {
+            total += *change;
+        }
+        total
+    }
+}
+
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct Ledger {
+    pub today: DayLedger,
+    /// Previous days, oldest first.
+    pub history: Vec<DayLedger>,
+}
+
+impl Default for Ledger {
+    fn default() -> Self {
+        Self {
+            today: DayLedger {
+                day: 1,
+                ..default()
+            },
+            history: Vec::new(),
+        }
+    }
+}
+
+impl Ledger {
+    pub fn gold(&mut self, source: LedgerSource, amount: i32) {
+        if amount != 0 {
+            self.today.record(
+                source,
+                LedgerChange {
+                    gold: amount,
+                    ..default()
+                },
+            );
+        }
+    }
+
+    pub fn population(&mut self, source: LedgerSource, amount: i32) {
+        if amount != 0 {
+            self.today.record(
+                source,
+                LedgerChange {
+                    population: amount,
+                    ..default()
+                },
+            );
+        }
+    }
+
+    /// Move today's changes to the history and start the next day.
+    pub fn close_day(&mut self) {
+        let day = self.today.day + 1;
+        let today = std::mem::replace(&mut self.today, DayLedger { day, ..default() });
+        self.history.push(today);
+
+        let overflow = self.history.len().saturating_sub(LEDGER_HISTORY_DAYS);
+        self.history.drain(..overflow);
+    }
+}
+
+#[derive(Component)]
+pub struct LedgerButton;
+
+#[derive(Component)]
+pub struct LedgerModal;
+
+#[derive(Component)]
+pub struct CloseLedgerButton;
+
+fn close_day(mut ledger: ResMut<Ledger>) {
+    ledger.close_day();
+}
+
+fn ledger_btn_interaction(
+    mut commands: Commands,
+    q_interactions: Query<&Interaction, (Changed<Interaction>, With<LedgerButton>)>,
+    q_modals: Query<(), With<LedgerModal>>,
+    ledger: Res<Ledger>,
+    sources: IncomeSources,
+    population: Res<VillagePopulation>,
+    jobs: Res<VillageJobs>,
+    stock: Res<VillageStock>,
+) {
+    if !q_interactions
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+        || !q_modals.is_empty()
+    {
+        return;
+    }
+
+    let forecast = sources.forecast(population.0, &jobs, stock.get(ResourceKind::Food));
+    let mut forecast_lines = vec![format!("{:+} gold", forecast.gold)];
+    if !forecast.production.is_empty() {
+        forecast_lines.push(format!("Produced: {}", forecast.production));
+    }
+    if !forecast.consumption.is_empty() {
+        forecast_lines.push(format!("Consumed: {}", forecast.consumption));
+    }
+    forecast_lines.push(match forecast.hungry {
+        0 => format!(
+            "{:+} to {:+} villagers (capacity {})",
+            POPULATION_GROWTH.start + forecast.newcomers,
+            POPULATION_GROWTH.end - 1 + forecast.newcomers,
+            forecast.population_capacity
+        ),
+        hungry => format!("-{hungry} villagers, not enough food"),
+    });
+
+    let today_lines = match ledger.today.changes.is_empty() {
+        true => vec!["Nothing yet.".to_string()],
+        false => ledger
+            .today
+            .changes
+            .iter()
+            .map(|(source, change)| format!("{}: {change}", source.name()))
+            .collect(),
+    };
+
+    let history_lines: Vec<_> = ledger
+        .history
+        .iter()
+        .rev()
+        .map(|day| format!("Day {}: {}", day.day, day.total()))
+        .collect();
+
+    commands
+        .ui_builder(UiRoot)
+        .column(|ui| {
+            ui.column(|ui| {
+                ui.style()
+                    .padding(UiRect::all(Val::Px(18.)))
+                    .border(UiRect::all(Val::Px(2.)))
+                    .border_color(Color::WHITE)
+                    .border_radius(BorderRadius::all(Val::Px(16.)))
+                    .background_color(Color::BLACK)
+                    .width(Val::Px(480.))
+                    .row_gap(Val::Px(6.));
+
+                ui.label(LabelConfig::from("Ledger"))
+                    .style()
+                    .align_self(AlignSelf::Center)
+                    .font_size(BIG_TEXT_SIZE);
+
+                for (title, lines) in [
+                    ("Next dawn".to_string(), forecast_lines),
+                    (format!("Day {}", ledger.today.day), today_lines),
+                    ("History".to_string(), history_lines),
+                ] {
+                    if lines.is_empty() {
+                        continue;
+                    }
+
+                    ui.label(LabelConfig::from(title))
+                        .style()
+                        .margin(UiRect::top(Val::Px(10.)))
+                        .font_size(LABEL_SIZE);
+                    for line in lines {
+                        ui.label(LabelConfig::from(line))
+                            .style()
+                            .font_size(TEXT_SIZE);
+                    }
+                }
+
+                ui.container(ButtonBundle::default(), |ui| {
+                    ui.label(LabelConfig::from("Close"))
+                        .style()
+                        .font_size(TEXT_SIZE);
+                })
+                .insert((
+                    CloseLedgerButton,
+                    InteractionPalette {
+                        none: css::BLACK.into(),
+                        hovered: css::DARK_RED.into(),
+                        pressed: css::INDIAN_RED.into(),
+                    },
+                ))
+                .style()
+                .align_self(AlignSelf::Center)
+                .margin(UiRect::top(Val::Px(10.)))
+                .padding(UiRect::all(Val::Px(4.)))
+                .border(UiRect::all(Val::Px(2.)))
+                .border_color(Color::WHITE);
+            });
+        })
+        .insert((LedgerModal, StateScoped(Screen::Playing)))
+        .style()
+        .focus_policy(FocusPolicy::Block)
+        .z_index(ZIndex::Global(MODAL_Z_LAYER))
+        .width(Val::Percent(100.))
+        .height(Val::Percent(100.))
+        .background_color(Color::srgba(0.25, 0.25, 0.25, 0.75))
+        .justify_content(JustifyContent::Center)
+        .align_items(AlignItems::Center);
+}
+
+fn close_ledger_btn_interaction(
+    mut commands: Commands,
+    q_interactions: Query<&Interaction, (Changed<Interaction>, With<CloseLedgerButton>)>,
+    q_modals: Query<Entity, With<LedgerModal>>,
+) {
+    for interaction in q_interactions.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        for entity in q_modals.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ledger_groups_by_source() {
+        let mut ledger = Ledger::default();
+        ledger.gold(LedgerSource::Construction, -25);
+        ledger.gold(LedgerSource::Income, 100);
+        ledger.gold(LedgerSource::Construction, -30);
+        ledger.population(LedgerSource::Growth, 0);
+
+        assert_eq!(
+            ledger.today.changes,
+            vec![
+                (
+                    LedgerSource::Construction,
+                    LedgerChange {
+                        gold: -55,
+                        population: 0
+                    }
+                ),
+                (
+                    LedgerSource::Income,
+                    LedgerChange {
+                        gold: 100,
+                        population: 0
+                    }
+                ),
+            ]
+        );
+        assert_eq!(ledger.today.total().to_string(), "+45 gold");
+    }
+
+    #[test]
+    fn test_ledger_history_is_capped() {
+        let mut ledger = Ledger::default();
+        for _ in 0..LEDGER_HISTORY_DAYS + 2 {
+            ledger.population(LedgerSource::Growth, 5);
+            ledger.close_day();
+        }
+
+        assert_eq!(ledger.history.len(), LEDGER_HISTORY_DAYS);
+        assert_eq!(ledger.history[0].day, 3);
+        assert_eq!(ledger.today.day, LEDGER_HISTORY_DAYS as u32 + 3);
+    }
+}
@@ -1,6 +1,7 @@
 //! Spawn the main level by triggering other observers.
 
 use bevy::prelude::*;
+use rand::Rng;
 
 use crate::game::actors::spawn::SpawnAnimation;
 use crate::game::actors::StructureBundle;
@@ -8,7 +9,12 @@ use crate::path_finding::tiles::{Tile, TileDim};
 use crate::{screen::Screen, VillageCamera};
 
 use super::actors::EnemyActor;
+use super::components::{Consumption, Production};
+use super::construction::StructureType;
+use super::daily::{flood_map, ChallengeRule, DailyChallenge};
 use super::picking::PickableTile;
+use super::rng::{GameRng, RngStream};
+use super::turn_order::{TurnOrder, TurnOrderSetting};
 
 use self::level_asset::{LevelAsset, LevelAssetPlugin, Levels};
 
@@ -43,9 +49,13 @@ pub fn load_level(
     mut levels: ResMut<Levels>,
     level_assets: Res<Assets<LevelAsset>>,
     tile_set: Res<TileSet>,
+    mut turn_order: ResMut<TurnOrder>,
+    turn_order_setting: Res<TurnOrderSetting>,
+    mut rng: ResMut<GameRng>,
+    challenge: Res<DailyChallenge>,
 ) {
     // Choose a random level
-    let level_index = rand::random::<usize>() % levels.0.len();
+    let level_index = rng.stream(RngStream::Level).gen_range(0..levels.0.len());
     let level = &mut levels.0[level_index];
 
     let Some(level_asset) = level_assets.get(&level.handle) else {
@@ -54,6 +64,11 @@ pub fn load_level(
     };
 
     let mut village_map = VillageMap::new(TileDim::splat(level_asset.size as i32));
+    // Daily challenges play by the level's turn order like everyone else
+    turn_order.mode = match challenge.date {
+        Some(_) => level_asset.turn_order,
+        None => turn_order_setting.mode(level_asset.turn_order),
+    };
 
     let camera_translation = Vec3::new(
         0.0,
@@ -91,7 +106,7 @@ pub fn load_level(
             village_map.set_terrain(Tile(xi, yi), terrain);
 
             if object_tile_name != "empty" {
-                let object_entity = commands.spawn((
+                let mut object_entity = commands.spawn((
                     SpriteBundle {
                         sprite: Sprite {
                             anchor: TILE_ANCHOR,
@@ -106,12 +121,23 @@ pub fn load_level(
                     StructureBundle::default(),
                     SpawnAnimation::new(object_translation),
                 ));
+                if let Some(s) = StructureType::from_tile_texture(object_tile_name) {
+                    object_entity.insert((
+                        s,
+                        Production(s.production()),
+                        Consumption(s.consumption()),
+                    ));
+                }
 
                 village_map.actors.set(Tile(xi, yi), object_entity.id());
             }
         }
     }
 
+    if challenge.has(ChallengeRule::WaterHeavyMap) {
+        flood_map(&mut village_map, rng.stream(RngStream::Level));
+    }
+
     village_map.generate_heat_map(|e| enemies_query.contains(e));
     commands.insert_resource(village_map);
 }
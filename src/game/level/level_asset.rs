@@ -5,6 +5,8 @@ use bevy::{
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::game::turn_order::TurnOrderMode;
+
 pub struct LevelAssetPlugin;
 
 impl Plugin for LevelAssetPlugin {
@@ -35,6 +37,9 @@ pub struct LevelAsset {
     pub name: String,
     pub size: usize,
     pub tiles: [Vec<String>; 2],
+    /// Levels without one use the default phases.
+    #[serde(default)]
+    pub turn_order: TurnOrderMode,
 }
 
 #[derive(Default)]
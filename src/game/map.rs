@@ -37,6 +37,8 @@ pub struct VillageMap {
     pub heat_map: Vec<u32>,
     pub terrain: HashMap<Tile, Terrain>,
     pub actors: TileMap,
+    /// Traps are kept out of [`Self::actors`] so that enemies can't see them.
+    pub traps: HashMap<Tile, Entity>,
     pub deployment_zone: HashSet<Tile>,
 }
 
@@ -47,6 +49,7 @@ impl VillageMap {
             heat_map: Vec::new(),
             terrain: Default::default(),
             actors: TileMap::new(size),
+            traps: HashMap::default(),
             deployment_zone: HashSet::default(),
         }
     }
@@ -79,6 +82,22 @@ impl VillageMap {
         directions: &[TileDir],
         is_airborne: bool,
     ) -> Option<(Vec<Tile>, i32)> {
+        self.pathfind_with_footprint(start, target, directions, is_airborne, TileDim::ONE)
+    }
+
+    /// Create a path for an actor covering `footprint` tiles.
+    /// Path tiles are the anchor (minimum corner) of the footprint,
+    /// and the actor at `start` never blocks itself.
+    pub fn pathfind_with_footprint(
+        &self,
+        start: &Tile,
+        target: &Tile,
+        directions: &[TileDir],
+        is_airborne: bool,
+        footprint: TileDim,
+    ) -> Option<(Vec<Tile>, i32)> {
+        let this = self.actors.get(*start);
+
         astar(
             start,
             // successors
@@ -87,20 +106,25 @@ impl VillageMap {
                 directions.iter().filter_map(move |dir| {
                     let final_coord = tile_coord.step(*dir);
 
-                    if self.is_out_of_bounds(final_coord) {
-                        return None;
-                    }
-
-                    // There is an obstacle blocking it
-                    if self.actors.get(final_coord).is_some() {
-                        return None;
-                    }
-
-                    // Check eligibility of moving on top of water tile
-                    match self.get_terrain(final_coord) {
-                        Some(Terrain::Water) if is_airborne == false => None,
-                        _ => Some((final_coord, 1)),
-                    }
+                    let can_enter = TileRect::from_origin(final_coord, footprint)
+                        .into_iter()
+                        .all(|tile| {
+                            if self.is_out_of_bounds(tile) {
+                                return false;
+                            }
+
+                            // There is an obstacle blocking it
+                            if let Some(actor) = self.actors.get(tile) {
+                                if Some(actor) != this {
+                                    return false;
+                                }
+                            }
+
+                            // Check eligibility of moving on top of water tile
+                            !matches!(self.get_terrain(tile), Some(Terrain::Water) if !is_airborne)
+                        });
+
+                    can_enter.then_some((final_coord, 1))
                 })
             },
             // heuristic
@@ -213,15 +237,15 @@ impl VillageMap {
         self.heat_map = vec![u32::MAX; (self.size.x() * self.size.y()) as usize];
         let mut stack = VecDeque::new();
 
-        for (tile_coord, entity) in self.actors.map.iter() {
-            if is_enemy(*entity) {
+        for (tile_coord, entity) in self.actors.iter_tiles() {
+            if is_enemy(entity) {
                 continue;
             }
 
             let index = (tile_coord.x() + tile_coord.y() * self.size.x()) as usize;
             self.heat_map[index] = 0;
 
-            stack.push_back(*tile_coord);
+            stack.push_back(tile_coord);
         }
 
         if stack.is_empty() {
@@ -275,7 +299,12 @@ impl Tiled for VillageMap {
 #[derive(Debug, Default)]
 pub struct TileMap {
     size: TileDim,
+    /// Anchor (minimum corner) tile of every actor.
     map: BiHashMap<Tile, Entity>,
+    /// Footprints of actors covering more than one tile.
+    footprints: HashMap<Entity, TileDim>,
+    /// Non-anchor tiles covered by a footprint.
+    covered: HashMap<Tile, Entity>,
 }
 
 impl TileMap {
@@ -284,6 +313,8 @@ impl TileMap {
         TileMap {
             size,
             map: BiHashMap::default(),
+            footprints: HashMap::default(),
+            covered: HashMap::default(),
         }
     }
 
@@ -296,37 +327,123 @@ impl TileMap {
     }
 
     pub fn is_occupied(&self, position: Tile) -> bool {
-        self.map.get_by_left(&position).is_some()
+        self.get(position).is_some()
     }
 
-    /// get entity at position
+    /// get entity covering position
     pub fn get(&self, position: Tile) -> Option<Entity> {
-        self.map.get_by_left(&position).copied()
+        self.map
+            .get_by_left(&position)
+            .or_else(|| self.covered.get(&position))
+            .copied()
     }
 
-    /// find entity's position in map
+    /// Nothing stands on the tiles between `from` and `to`.
+    pub fn has_line_of_sight(&self, from: Tile, to: Tile) -> bool {
+        from.get_line_between(to)
+            .is_none_or(|mut line| line.all(|tile| !self.is_occupied(tile)))
+    }
+
+    /// find entity's anchor position in map
     pub fn locate(&self, entity: Entity) -> Option<Tile> {
         self.map.get_by_right(&entity).copied()
     }
 
+    /// number of tiles the entity covers, single tile unless set otherwise
+    pub fn footprint(&self, entity: Entity) -> TileDim {
+        self.footprints
+            .get(&entity)
+            .copied()
+            .unwrap_or(TileDim::ONE)
+    }
+
+    /// all tiles covered by the entity
+    pub fn footprint_tiles(&self, entity: Entity) -> impl Iterator<Item = Tile> {
+        let footprint = self.footprint(entity);
+        self.locate(entity)
+            .into_iter()
+            .flat_map(move |anchor| TileRect::from_origin(anchor, footprint))
+    }
+
     /// place entity at map position, will move entity if already in map.
     /// will overwrite any existing entity at the position
     pub fn set(&mut self, position: Tile, entity: Entity) -> Overwritten<Tile, Entity> {
-        self.map.insert(position, entity)
+        let footprint = self.footprint(entity);
+        self.set_with_footprint(position, entity, footprint)
+    }
+
+    /// place entity with its anchor at map position, covering `footprint` tiles.
+    /// will overwrite any existing entity at the covered positions
+    pub fn set_with_footprint(
+        &mut self,
+        position: Tile,
+        entity: Entity,
+        footprint: TileDim,
+    ) -> Overwritten<Tile, Entity> {
+        self.uncover(entity);
+
+        let area = TileRect::from_origin(position, footprint);
+        for tile in area {
+            // Anchors are overwritten by the insert below
+            if let Some(other) = self.covered.get(&tile).copied() {
+                if other != entity {
+                    self.remove_entity(other);
+                }
+            }
+            if tile == position {
+                continue;
+            }
+            if let Some(other) = self.map.get_by_left(&tile).copied() {
+                if other != entity {
+                    self.remove_entity(other);
+                }
+            }
+        }
+
+        let overwritten = self.map.insert(position, entity);
+        if let Overwritten::Left(_, other) | Overwritten::Both((_, other), _) = overwritten {
+            self.uncover(other);
+            self.footprints.remove(&other);
+        }
+
+        match footprint == TileDim::ONE {
+            true => {
+                self.footprints.remove(&entity);
+            }
+            false => {
+                self.footprints.insert(entity, footprint);
+                for tile in area.into_iter().filter(|tile| *tile != position) {
+                    self.covered.insert(tile, entity);
+                }
+            }
+        }
+
+        overwritten
     }
 
-    /// remove entity from map at position
+    /// remove entity covering position from map
     pub fn remove(&mut self, position: Tile) -> Option<Entity> {
-        self.map.remove_by_left(&position).map(|(_, entity)| entity)
+        let entity = self.get(position)?;
+        self.remove_entity(entity);
+        Some(entity)
     }
 
-    /// remove entity from map
+    /// remove entity from map, returns its anchor position
     pub fn remove_entity(&mut self, entity: Entity) -> Option<Tile> {
+        self.uncover(entity);
+        self.footprints.remove(&entity);
         self.map
             .remove_by_right(&entity)
             .map(|(position, _)| position)
     }
 
+    /// clear the non-anchor tiles covered by the entity
+    fn uncover(&mut self, entity: Entity) {
+        if self.footprints.contains_key(&entity) {
+            self.covered.retain(|_, e| *e != entity);
+        }
+    }
+
     pub fn get_neighbouring_positions_rook(
         &self,
         position: Tile,
@@ -349,9 +466,16 @@ impl TileMap {
             .filter(|target| self.bounds().contains(*target))
     }
 
+    /// iterate over the anchor tile of every entity
     pub fn iter(&self) -> impl Iterator<Item = (Tile, Entity)> + '_ {
         self.map.iter().map(|(t, e)| (*t, *e))
     }
+
+    /// iterate over every occupied tile
+    pub fn iter_tiles(&self) -> impl Iterator<Item = (Tile, Entity)> + '_ {
+        self.iter()
+            .chain(self.covered.iter().map(|(t, e)| (*t, *e)))
+    }
 }
 
 #[cfg(test)]
@@ -421,6 +545,19 @@ mod tests {
         assert!(!tile_map.is_occupied(Tile(2, 2)));
     }
 
+    #[test]
+    fn test_tile_map_line_of_sight() {
+        let mut tile_map = TileMap::new(TileDim(10, 10));
+        tile_map.set(Tile(2, 2), Entity::from_raw(1));
+        tile_map.set(Tile(4, 4), Entity::from_raw(2));
+
+        assert!(!tile_map.has_line_of_sight(Tile(1, 1), Tile(4, 4)));
+        // The units at either end don't block
+        assert!(tile_map.has_line_of_sight(Tile(2, 2), Tile(4, 4)));
+        assert!(tile_map.has_line_of_sight(Tile(1, 2), Tile(1, 5)));
+        assert!(tile_map.has_line_of_sight(Tile(3, 3), Tile(4, 4)));
+    }
+
     #[test]
     fn test_tile_map_get_set_remove() {
         let size = TileDim(10, 10);
@@ -577,6 +714,117 @@ mod tests {
         assert!(flooded_tiles.contains(&Tile(3, 3)));
     }
 
+    #[test]
+    fn test_village_map_pathfind_blocked_by_wall() {
+        let mut village_map = VillageMap::new(TileDim(3, 3));
+        for (i, y) in (0..3).enumerate() {
+            village_map
+                .actors
+                .set(Tile(1, y), Entity::from_raw(i as u32 + 1));
+        }
+
+        let path = village_map.pathfind(&Tile(0, 0), &Tile(2, 0), &TileDir::EDGES, false);
+        assert!(path.is_none());
+
+        let flooded = village_map.flood(Tile(0, 0), 4, &TileDir::ALL, false, &[]);
+        assert!(flooded.iter().all(|tile| tile.x() == 0));
+    }
+
+    #[test]
+    fn test_village_map_gate_lets_heroes_through() {
+        let mut village_map = VillageMap::new(TileDim(3, 1));
+        let gate = Entity::from_raw(1);
+        village_map.actors.set(Tile(1, 0), gate);
+
+        // Heroes move with the gates ignored
+        let passable = village_map.flood(Tile(0, 0), 2, &TileDir::EDGES, false, &[gate]);
+        assert!(passable.contains(&Tile(2, 0)));
+    }
+
+    #[test]
+    fn test_village_map_gate_blocks_enemies() {
+        let mut village_map = VillageMap::new(TileDim(3, 1));
+        let gate = Entity::from_raw(1);
+        village_map.actors.set(Tile(1, 0), gate);
+
+        let blocked = village_map.flood(Tile(0, 0), 2, &TileDir::EDGES, false, &[]);
+        assert!(!blocked.contains(&Tile(2, 0)));
+
+        let path = village_map.pathfind(&Tile(0, 0), &Tile(2, 0), &TileDir::EDGES, false);
+        assert!(path.is_none());
+    }
+
+    #[test]
+    fn test_village_map_trap_hidden_from_actors() {
+        let mut village_map = VillageMap::new(TileDim(3, 1));
+        let trap = Entity::from_raw(1);
+        village_map.traps.insert(Tile(1, 0), trap);
+
+        assert!(!village_map.actors.is_occupied(Tile(1, 0)));
+        assert_eq!(village_map.actors.locate(trap), None);
+
+        // Enemies walk right onto it
+        let (path, _) = village_map
+            .pathfind(&Tile(0, 0), &Tile(2, 0), &TileDir::EDGES, false)
+            .unwrap();
+        assert!(path.contains(&Tile(1, 0)));
+
+        let flooded = village_map.flood(Tile(0, 0), 2, &TileDir::EDGES, false, &[]);
+        assert!(flooded.contains(&Tile(1, 0)));
+    }
+
+    #[test]
+    fn test_village_map_pathfind_with_footprint_moves_over_itself() {
+        let mut village_map = VillageMap::new(TileDim(3, 1));
+        let entity = Entity::from_raw(1);
+        village_map
+            .actors
+            .set_with_footprint(Tile(0, 0), entity, TileDim(2, 1));
+
+        let (path, _) = village_map
+            .pathfind_with_footprint(
+                &Tile(0, 0),
+                &Tile(1, 0),
+                &TileDir::EDGES,
+                false,
+                TileDim(2, 1),
+            )
+            .unwrap();
+        assert_eq!(path, vec![Tile(0, 0), Tile(1, 0)]);
+    }
+
+    #[test]
+    fn test_village_map_pathfind_with_footprint_avoids_obstacles() {
+        let mut village_map = VillageMap::new(TileDim(4, 3));
+        let footprint = TileDim(2, 1);
+        village_map
+            .actors
+            .set_with_footprint(Tile(0, 0), Entity::from_raw(1), footprint);
+        village_map.actors.set(Tile(2, 1), Entity::from_raw(2));
+
+        let (path, _) = village_map
+            .pathfind_with_footprint(&Tile(0, 0), &Tile(2, 2), &TileDir::EDGES, false, footprint)
+            .unwrap();
+        assert!(path
+            .iter()
+            .all(|anchor| !TileRect::from_origin(*anchor, footprint).contains(Tile(2, 1))));
+
+        // Footprint can't fit past the right edge
+        assert!(village_map
+            .pathfind_with_footprint(&Tile(0, 0), &Tile(3, 0), &TileDir::EDGES, false, footprint)
+            .is_none());
+    }
+
+    #[test]
+    fn test_village_map_heat_map_with_footprint() {
+        let mut village_map = VillageMap::new(TileDim(3, 1));
+        village_map
+            .actors
+            .set_with_footprint(Tile(0, 0), Entity::from_raw(1), TileDim(2, 1));
+        village_map.generate_heat_map(|_| false);
+        assert_eq!(village_map.heat_map, vec![0, 0, 1]);
+    }
+
     #[test]
     fn test_village_map_sort_tiles_by_distance() {
         let target = Tile(0, 0);
@@ -644,4 +892,39 @@ mod tests {
         assert!(!perimeter.contains(&Tile(2, 2)));
         assert!(!perimeter.contains(&Tile(-1, -1)));
     }
+
+    #[test]
+    fn test_tile_map_footprint() {
+        let mut tile_map = TileMap::new(TileDim(10, 10));
+        let entity = Entity::from_raw(1);
+        tile_map.set_with_footprint(Tile(1, 1), entity, TileDim(2, 1));
+        assert_eq!(tile_map.get(Tile(2, 1)), Some(entity));
+        assert_eq!(tile_map.locate(entity), Some(Tile(1, 1)));
+        assert_eq!(tile_map.footprint_tiles(entity).count(), 2);
+
+        // Moving keeps the footprint
+        tile_map.set(Tile(4, 4), entity);
+        assert!(!tile_map.is_occupied(Tile(2, 1)));
+        assert_eq!(tile_map.get(Tile(5, 4)), Some(entity));
+
+        assert_eq!(tile_map.remove(Tile(5, 4)), Some(entity));
+        assert!(!tile_map.is_occupied(Tile(4, 4)));
+        assert_eq!(tile_map.footprint(entity), TileDim::ONE);
+    }
+
+    #[test]
+    fn test_tile_map_footprint_overwrites() {
+        let mut tile_map = TileMap::new(TileDim(10, 10));
+        let small = Entity::from_raw(1);
+        let large = Entity::from_raw(2);
+        tile_map.set(Tile(2, 1), small);
+        tile_map.set_with_footprint(Tile(1, 1), large, TileDim(2, 2));
+        assert_eq!(tile_map.locate(small), None);
+        assert_eq!(tile_map.iter_tiles().count(), 4);
+
+        // Placing onto a covered tile removes the whole footprint
+        tile_map.set(Tile(2, 2), small);
+        assert_eq!(tile_map.locate(large), None);
+        assert_eq!(tile_map.iter_tiles().count(), 1);
+    }
 }
@@ -0,0 +1,273 @@
+//! Mutators changing the rules of a run, picked before it starts.
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use crate::screen::Screen;
+
+use super::construction::{StructureCost, StructureCosts};
+use super::game_mode::GameMode;
+use super::resources::ResourceBundle;
+use super::turn_order::Side;
+
+pub struct MutatorsPlugin;
+
+impl Plugin for MutatorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(MutatorRegistry(MUTATOR_TEMPLATES.to_vec()))
+            .init_resource::<ActiveMutators>()
+            .add_systems(OnExit(Screen::Title), disable_for_daily)
+            .add_systems(OnEnter(Screen::Playing), apply_construction_costs);
+    }
+}
+
+/// Changes to the rules, every field leaves the game untouched at zero.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Mutator {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Change in percent of the gold and resources the village makes at dawn.
+    pub income_percent: i32,
+    /// Villagers added to the growth at dawn.
+    pub growth: i32,
+    /// Change in percent of the points spent on enemies every night.
+    pub wave_budget_percent: i32,
+    /// Hit points added to every enemy.
+    pub enemy_health: i32,
+    /// Damage added to every hit the village lands on enemies, from heroes, towers or traps.
+    pub hero_damage: i32,
+    /// Damage added to every hit enemies land on the village.
+    pub enemy_damage: i32,
+    /// Change in percent of the resources needed to build.
+    pub construction_cost_percent: i32,
+}
+
+impl Mutator {
+    pub const NONE: Self = Self {
+        name: "",
+        description: "",
+        income_percent: 0,
+        growth: 0,
+        wave_budget_percent: 0,
+        enemy_health: 0,
+        hero_damage: 0,
+        enemy_damage: 0,
+        construction_cost_percent: 0,
+    };
+}
+
+pub const MUTATOR_TEMPLATES: &[Mutator] = &[
+    Mutator {
+        name: "Boomtown",
+        description: "Half again as much income, buildings cost a quarter more.",
+        income_percent: 50,
+        construction_cost_percent: 25,
+        ..Mutator::NONE
+    },
+    Mutator {
+        name: "Famine",
+        description: "A quarter less income, no villager is born.",
+        income_percent: -25,
+        growth: -100,
+        ..Mutator::NONE
+    },
+    Mutator {
+        name: "Horde",
+        description: "Half again as many enemies, each with one less hit point.",
+        wave_budget_percent: 50,
+        enemy_health: -1,
+        ..Mutator::NONE
+    },
+    Mutator {
+        name: "Thick Hides",
+        description: "Enemies have two more hit points.",
+        enemy_health: 2,
+        ..Mutator::NONE
+    },
+    Mutator {
+        name: "Glass Cannons",
+        description: "Heroes and enemies deal one more damage.",
+        hero_damage: 1,
+        enemy_damage: 1,
+        ..Mutator::NONE
+    },
+    Mutator {
+        name: "Masons",
+        description: "Buildings cost half as much, enemies come a quarter more.",
+        wave_budget_percent: 25,
+        construction_cost_percent: -50,
+        ..Mutator::NONE
+    },
+];
+
+/// Every mutator that can be picked, mods can register their own.
+#[derive(Resource, Deref, DerefMut, Debug)]
+pub struct MutatorRegistry(pub Vec<Mutator>);
+
+impl MutatorRegistry {
+    pub fn register(&mut self, mutator: Mutator) {
+        self.0.push(mutator);
+    }
+}
+
+/// Mutators of the current run, their changes add up.
+#[derive(Resource, Default, Debug)]
+pub struct ActiveMutators(pub Vec<Mutator>);
+
+impl ActiveMutators {
+    pub fn is_active(&self, mutator: &Mutator) -> bool {
+        self.0.contains(mutator)
+    }
+
+    /// Turn the mutator on or off.
+    pub fn toggle(&mut self, mutator: Mutator) {
+        match self.0.iter().position(|active| *active == mutator) {
+            Some(index) => {
+                self.0.remove(index);
+            }
+            None => self.0.push(mutator),
+        }
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.0
+            .iter()
+            .map(|mutator| mutator.name.to_string())
+            .collect()
+    }
+
+    fn sum(&self, field: impl Fn(&Mutator) -> i32) -> i32 {
+        self.0.iter().map(field).sum()
+    }
+
+    fn percent(&self, field: impl Fn(&Mutator) -> i32) -> u32 {
+        (100 + self.sum(field)).max(0) as u32
+    }
+
+    /// Gold paid by the villagers at dawn.
+    pub fn income(&self, gold: u32) -> u32 {
+        gold * self.percent(|mutator| mutator.income_percent) / 100
+    }
+
+    /// Resources made by the village's buildings at dawn.
+    pub fn production(&self, production: ResourceBundle) -> ResourceBundle {
+        production.percent(self.percent(|mutator| mutator.income_percent))
+    }
+
+    pub fn growth(&self, growth: u32) -> u32 {
+        growth.saturating_add_signed(self.sum(|mutator| mutator.growth))
+    }
+
+    pub fn wave_budget(&self, budget: u32) -> u32 {
+        budget * self.percent(|mutator| mutator.wave_budget_percent) / 100
+    }
+
+    pub fn enemy_health(&self, hit_points: u32) -> u32 {
+        hit_points
+            .saturating_add_signed(self.sum(|mutator| mutator.enemy_health))
+            .max(1)
+    }
+
+    /// Damage of a hit on enemies, hits always hurt a little.
+    pub fn hero_damage(&self, damage: u32) -> u32 {
+        damage
+            .saturating_add_signed(self.sum(|mutator| mutator.hero_damage))
+            .max(1)
+    }
+
+    pub fn enemy_damage(&self, damage: u32) -> u32 {
+        damage
+            .saturating_add_signed(self.sum(|mutator| mutator.enemy_damage))
+            .max(1)
+    }
+
+    /// Damage `attacker` deals to the `target` side, friendly fire is left as it is.
+    pub fn dealt(&self, attacker: Side, target: Side, damage: u32) -> u32 {
+        match (attacker, target) {
+            (Side::Heroes, Side::Enemies) => self.hero_damage(damage),
+            (Side::Enemies, Side::Heroes) => self.enemy_damage(damage),
+            _ => damage,
+        }
+    }
+
+    pub fn construction_cost(&self, cost: &StructureCost) -> StructureCost {
+        StructureCost {
+            resources: cost
+                .resources
+                .percent(self.percent(|mutator| mutator.construction_cost_percent)),
+            ..*cost
+        }
+    }
+}
+
+/// Damage of every hit in a fight, changed by the mutators of the side landing it.
+#[derive(SystemParam)]
+pub struct Damage<'w> {
+    mutators: Res<'w, ActiveMutators>,
+}
+
+impl Damage<'_> {
+    pub fn dealt(&self, attacker: Side, target: Side, damage: u32) -> u32 {
+        self.mutators.dealt(attacker, target, damage)
+    }
+}
+
+/// Everyone plays the daily challenge by the same rules.
+fn disable_for_daily(game_mode: Res<GameMode>, mut mutators: ResMut<ActiveMutators>) {
+    if *game_mode == GameMode::Daily {
+        mutators.0.clear();
+    }
+}
+
+fn apply_construction_costs(mut costs: ResMut<StructureCosts>, mutators: Res<ActiveMutators>) {
+    *costs = StructureCosts(
+        StructureCosts::default()
+            .iter()
+            .map(|(structure_type, cost)| (*structure_type, mutators.construction_cost(cost)))
+            .collect(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mutators_add_up() {
+        let mut mutators = ActiveMutators::default();
+        assert_eq!(mutators.income(40), 40);
+        assert_eq!(mutators.enemy_health(3), 3);
+
+        mutators.toggle(MUTATOR_TEMPLATES[0]);
+        mutators.toggle(MUTATOR_TEMPLATES[1]);
+        assert_eq!(mutators.income(40), 50);
+        assert_eq!(mutators.growth(3), 0);
+
+        mutators.toggle(MUTATOR_TEMPLATES[0]);
+        assert!(!mutators.is_active(&MUTATOR_TEMPLATES[0]));
+        assert_eq!(mutators.income(40), 30);
+    }
+
+    #[test]
+    fn test_mutator_damage_never_drops_to_zero() {
+        let mutators = ActiveMutators(vec![Mutator {
+            hero_damage: -5,
+            enemy_health: -5,
+            ..Mutator::NONE
+        }]);
+        assert_eq!(mutators.hero_damage(2), 1);
+        assert_eq!(mutators.enemy_health(2), 1);
+    }
+
+    #[test]
+    fn test_friendly_fire_ignores_mutators() {
+        let mutators = ActiveMutators(vec![Mutator {
+            hero_damage: 1,
+            enemy_damage: 2,
+            ..Mutator::NONE
+        }]);
+        assert_eq!(mutators.dealt(Side::Heroes, Side::Enemies, 2), 3);
+        assert_eq!(mutators.dealt(Side::Enemies, Side::Heroes, 2), 4);
+        assert_eq!(mutators.dealt(Side::Heroes, Side::Heroes, 2), 2);
+    }
+}
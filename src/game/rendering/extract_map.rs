@@ -19,12 +19,14 @@ use crate::game::constants::CURSOR_COLOR;
 use crate::game::game_params::ReadGame;
 use crate::game::map::VillageMap;
 use crate::game::picking::PickedTile;
+use crate::game::resources::SelectedStructueType;
 use crate::game::selection::SelectedActor;
 use crate::game::selection::SelectedTiles;
 use crate::game::tile_set::TileSet;
 use crate::game::tile_set::TILE_ANCHOR;
 use crate::path_finding::tiles::Tile;
 use crate::path_finding::tiles::TileCorner;
+use crate::path_finding::tiles::TileDim;
 use crate::path_finding::tiles::TileEdge;
 use crate::path_finding::tiles::Tiled;
 use crate::screen::playing::GameState;
@@ -76,6 +78,7 @@ fn extract_terrain(
     game_state: Extract<Res<State<GameState>>>,
     tints: Extract<Res<TileTints>>,
     game: Extract<ReadGame>,
+    selected_structure: Extract<Res<SelectedStructueType>>,
     ent: Extract<Res<MapEnt>>,
 ) {
     if game.map.is_none() {
@@ -95,8 +98,8 @@ fn extract_terrain(
                 }
             }
             _ => {
-                if selected.tiles.contains(&tile) {
-                    selected.color
+                if let Some(color) = selected.tint(&tile) {
+                    color
                 } else if let Some(color) = tints.0.get(&tile) {
                     *color
                 } else {
@@ -106,7 +109,11 @@ fn extract_terrain(
         }
     };
 
-    let buildable = game.find_tiles_that_can_be_built_on();
+    let footprint = selected_structure
+        .0
+        .map(|structure| structure.footprint())
+        .unwrap_or(TileDim::ONE);
+    let buildable = game.find_tiles_that_can_be_built_on(footprint);
 
     for (tile, terrain) in game.map().iter_terrain() {
         extracted_sprites.sprites.insert(
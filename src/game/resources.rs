@@ -1,8 +1,9 @@
 use bevy::prelude::*;
+use strum::{EnumCount, IntoEnumIterator};
+use strum_macros::{EnumCount, EnumIter};
 
-use super::constants::INITIAL_GOLD;
-use super::constants::INITIAL_POPULATION;
 use super::construction::StructureType;
+use super::KeyedResource;
 
 #[derive(Resource, Debug, Copy, Clone, PartialEq, Default)]
 pub enum TurnPhase {
@@ -11,30 +12,159 @@ pub enum TurnPhase {
     Enemy,
 }
 
-#[derive(Resource, Debug, Copy, Clone, PartialEq)]
-pub struct VillageGold(pub u32);
+/// Every kind of resource the village stockpiles, labels watching
+/// [`VillageStock`] show the amount of their kind.
+#[derive(Component, EnumIter, EnumCount, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ResourceKind {
+    Gold,
+    Wood,
+    Stone,
+    Food,
+}
+
+impl ResourceKind {
+    pub fn name(&self) -> &str {
+        match self {
+            ResourceKind::Gold => "gold",
+            ResourceKind::Wood => "wood",
+            ResourceKind::Stone => "stone",
+            ResourceKind::Food => "food",
+        }
+    }
 
-impl Default for VillageGold {
-    fn default() -> Self {
-        Self(INITIAL_GOLD)
+    pub fn icon_path(&self) -> &'static str {
+        match self {
+            ResourceKind::Gold => "icons/gold_coins.png",
+            ResourceKind::Wood => "icons/axe.png",
+            ResourceKind::Stone => "tiles/block_grey.png",
+            ResourceKind::Food => "icons/leaf.png",
+        }
     }
 }
 
-impl std::fmt::Display for VillageGold {
+/// An amount of each [`ResourceKind`], used for costs, production and consumption.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct ResourceBundle([u32; ResourceKind::COUNT]);
+
+impl ResourceBundle {
+    pub const EMPTY: Self = Self([0; ResourceKind::COUNT]);
+
+    pub const fn with(mut self, kind: ResourceKind, amount: u32) -> Self {
+        self.0[kind as usize] = amount;
+        self
+    }
+
+    pub const fn gold(amount: u32) -> Self {
+        Self::EMPTY.with(ResourceKind::Gold, amount)
+    }
+
+    pub fn get(&self, kind: ResourceKind) -> u32 {
+        self.0[kind as usize]
+    }
+
+    pub fn get_mut(&mut self, kind: ResourceKind) -> &mut u32 {
+        &mut self.0[kind as usize]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|amount| *amount == 0)
+    }
+
+    /// Iterate over the kinds with a non zero amount.
+    pub fn iter(&self) -> impl Iterator<Item = (ResourceKind, u32)> + '_ {
+        ResourceKind::iter()
+            .map(|kind| (kind, self.get(kind)))
+            .filter(|(_, amount)| *amount > 0)
+    }
+
+    pub fn percent(&self, percent: u32) -> Self {
+        Self(self.0.map(|amount| amount * percent / 100))
+    }
+}
+
+impl std::ops::Add for ResourceBundle {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self {
+        for (amount, other) in self.0.iter_mut().zip(rhs.0) {
+            *amount += other;
+        }
+        self
+    }
+}
+
+impl std::fmt::Display for ResourceBundle {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.0.fmt(f)
+        let parts: Vec<String> = self
+            .iter()
+            .map(|(kind, amount)| format!("{amount} {}", kind.name()))
+            .collect();
+        write!(f, "{}", parts.join(", "))
     }
 }
 
-#[derive(Resource, Debug, Copy, Clone, PartialEq)]
-pub struct VillagePopulation(pub u32);
+/// How much of every [`ResourceKind`] the village has, starts with the resources set by the
+/// [`Difficulty`](super::difficulty::Difficulty).
+#[derive(Resource, Default, Debug, Clone, PartialEq)]
+pub struct VillageStock(ResourceBundle);
+
+impl KeyedResource for VillageStock {
+    type Key = ResourceKind;
+
+    fn label(&self, kind: &ResourceKind) -> String {
+        self.get(*kind).to_string()
+    }
+}
+
+impl VillageStock {
+    pub fn get(&self, kind: ResourceKind) -> u32 {
+        self.0.get(kind)
+    }
+
+    pub fn get_mut(&mut self, kind: ResourceKind) -> &mut u32 {
+        self.0.get_mut(kind)
+    }
+
+    /// Replace the whole stock, kinds missing from the bundle run out.
+    pub fn set(&mut self, bundle: &ResourceBundle) {
+        self.0 = *bundle;
+    }
+
+    pub fn can_afford(&self, cost: &ResourceBundle) -> bool {
+        cost.iter().all(|(kind, amount)| self.get(kind) >= amount)
+    }
+
+    /// Pay the cost only if all of it can be afforded.
+    pub fn spend(&mut self, cost: &ResourceBundle) -> bool {
+        if !self.can_afford(cost) {
+            return false;
+        }
+        self.withdraw(cost);
+        true
+    }
+
+    /// Take as much of the bundle as there is in stock, returns what was missing.
+    pub fn withdraw(&mut self, bundle: &ResourceBundle) -> ResourceBundle {
+        let mut shortfall = ResourceBundle::EMPTY;
+        for (kind, amount) in bundle.iter() {
+            let stock = self.get_mut(kind);
+            shortfall = shortfall.with(kind, amount.saturating_sub(*stock));
+            *stock = stock.saturating_sub(amount);
+        }
+        shortfall
+    }
 
-impl Default for VillagePopulation {
-    fn default() -> Self {
-        Self(INITIAL_POPULATION)
+    pub fn deposit(&mut self, bundle: &ResourceBundle) {
+        for (kind, amount) in bundle.iter() {
+            *self.get_mut(kind) += amount;
+        }
     }
 }
 
+/// Starts with the amount set by the [`Difficulty`](super::difficulty::Difficulty).
+#[derive(Resource, Default, Debug, Copy, Clone, PartialEq)]
+pub struct VillagePopulation(pub u32);
+
 impl std::fmt::Display for VillagePopulation {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.0.fmt(f)
@@ -44,11 +174,159 @@ impl std::fmt::Display for VillagePopulation {
 #[derive(Resource, Default)]
 pub struct SelectedStructueType(pub Option<StructureType>);
 
-#[derive(Resource, Default)]
-pub struct VillageEmployment(pub u32);
+/// Work a villager can be given.
+#[derive(EnumIter, EnumCount, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum VillagerJob {
+    /// Building, upgrading and repairing structures, assigned by the structures themselves.
+    Construction,
+    /// Staffs farms.
+    Farming,
+    /// Staffs lumber camps and quarries.
+    Gathering,
+    /// Mans archer towers during the night.
+    TowerDuty,
+    /// Villagers without a job, they help the village grow.
+    Resting,
+}
+
+impl VillagerJob {
+    pub fn name(&self) -> &'static str {
+        match self {
+            VillagerJob::Construction => "Construction",
+            VillagerJob::Farming => "Farming",
+            VillagerJob::Gathering => "Gathering",
+            VillagerJob::TowerDuty => "Tower Duty",
+            VillagerJob::Resting => "Resting",
+        }
+    }
+
+    /// Jobs that are assigned by hand from the job panel.
+    pub fn is_assignable(&self) -> bool {
+        matches!(
+            self,
+            VillagerJob::Farming | VillagerJob::Gathering | VillagerJob::TowerDuty
+        )
+    }
+}
+
+/// Number of villagers working each job, everyone else is resting.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct VillageJobs([u32; VillagerJob::COUNT]);
+
+impl VillageJobs {
+    pub fn get(&self, job: VillagerJob) -> u32 {
+        self.0[job as usize]
+    }
+
+    /// Villagers that have a job.
+    pub fn employed(&self) -> u32 {
+        VillagerJob::iter()
+            .filter(|job| *job != VillagerJob::Resting)
+            .map(|job| self.get(job))
+            .sum()
+    }
+
+    pub fn resting(&self, population: u32) -> u32 {
+        population.saturating_sub(self.employed())
+    }
+
+    /// Move resting villagers to `job`, returns false if there aren't enough of them.
+    pub fn assign(&mut self, job: VillagerJob, count: u32, population: u32) -> bool {
+        if job == VillagerJob::Resting || self.resting(population) < count {
+            return false;
+        }
+        self.0[job as usize] += count;
+        true
+    }
+
+    /// Send villagers working `job` back to rest.
+    pub fn release(&mut self, job: VillagerJob, count: u32) {
+        let assigned = &mut self.0[job as usize];
+        *assigned = assigned.saturating_sub(count);
+    }
 
-impl std::fmt::Display for VillageEmployment {
+    /// Release villagers from hand assigned jobs until everyone employed is still alive.
+    /// Construction is left alone since it is tied to structures in progress.
+    pub fn fit_to(&mut self, population: u32) {
+        for job in VillagerJob::iter().rev().filter(VillagerJob::is_assignable) {
+            let excess = self.employed().saturating_sub(population);
+            self.release(job, excess);
+        }
+    }
+}
+
+impl std::fmt::Display for VillageJobs {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.0.fmt(f)
+        self.employed().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_bundle_iter_skips_empty() {
+        let bundle = ResourceBundle::gold(10).with(ResourceKind::Stone, 5);
+        let kinds: Vec<_> = bundle.iter().collect();
+        assert_eq!(
+            kinds,
+            vec![(ResourceKind::Gold, 10), (ResourceKind::Stone, 5)]
+        );
+        assert!(ResourceBundle::EMPTY.is_empty());
+    }
+
+    #[test]
+    fn test_resource_bundle_arithmetic() {
+        let bundle = ResourceBundle::gold(50).with(ResourceKind::Wood, 15);
+        let refund = bundle.percent(50);
+        assert_eq!(refund.get(ResourceKind::Gold), 25);
+        assert_eq!(refund.get(ResourceKind::Wood), 7);
+
+        let total = bundle + refund;
+        assert_eq!(total.get(ResourceKind::Gold), 75);
+        assert_eq!(total.to_string(), "75 gold, 22 wood");
+    }
+
+    #[test]
+    fn test_village_stock_spending() {
+        let mut stock = VillageStock::default();
+        stock.set(&ResourceBundle::gold(20).with(ResourceKind::Wood, 5));
+        assert!(!stock.spend(&ResourceBundle::gold(10).with(ResourceKind::Wood, 6)));
+        assert!(stock.spend(&ResourceBundle::gold(10).with(ResourceKind::Wood, 5)));
+        assert_eq!(stock.label(&ResourceKind::Gold), "10");
+        assert_eq!(stock.label(&ResourceKind::Wood), "0");
+
+        let shortfall = stock.withdraw(&ResourceBundle::gold(15));
+        assert_eq!(shortfall, ResourceBundle::gold(5));
+        assert_eq!(stock.get(ResourceKind::Gold), 0);
+    }
+
+    #[test]
+    fn test_village_jobs_assignment() {
+        let mut jobs = VillageJobs::default();
+        assert!(jobs.assign(VillagerJob::Construction, 6, 10));
+        assert!(jobs.assign(VillagerJob::Farming, 4, 10));
+        assert!(!jobs.assign(VillagerJob::Gathering, 1, 10));
+        assert!(!jobs.assign(VillagerJob::Resting, 1, 20));
+        assert_eq!(jobs.resting(10), 0);
+
+        jobs.release(VillagerJob::Farming, 2);
+        assert_eq!(jobs.get(VillagerJob::Farming), 2);
+        assert_eq!(jobs.resting(10), 2);
+    }
+
+    #[test]
+    fn test_village_jobs_fit_to_population() {
+        let mut jobs = VillageJobs::default();
+        jobs.assign(VillagerJob::Construction, 5, 20);
+        jobs.assign(VillagerJob::Farming, 5, 20);
+        jobs.assign(VillagerJob::TowerDuty, 4, 20);
+
+        jobs.fit_to(7);
+        assert_eq!(jobs.get(VillagerJob::Construction), 5);
+        assert_eq!(jobs.get(VillagerJob::Farming), 2);
+        assert_eq!(jobs.get(VillagerJob::TowerDuty), 0);
+        assert_eq!(jobs.employed(), 7);
     }
 }
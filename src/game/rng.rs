@@ -0,0 +1,87 @@
+//! Randomness deciding how a run plays out, seeded so that a run can be played again.
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::screen::Screen;
+
+use super::daily::{start_daily_challenge, DailyChallenge};
+
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameRng::new(rand::random()))
+            .add_systems(OnExit(Screen::Title), seed_run.after(start_daily_challenge));
+    }
+}
+
+/// What the randomness is for, each gets its own rng so that systems drawing at the same
+/// time can run in any order.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RngStream {
+    Level,
+    Heroes,
+    Enemies,
+    Merchant,
+    Tavern,
+    Visitors,
+    Population,
+}
+
+/// Use instead of `rand::thread_rng` for anything that changes the game.
+#[derive(Resource)]
+pub struct GameRng {
+    seed: u64,
+    streams: HashMap<RngStream, StdRng>,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: HashMap::new(),
+        }
+    }
+
+    /// Rng of the stream, seeded from the run seed the first time it's used.
+    pub fn stream(&mut self, stream: RngStream) -> &mut StdRng {
+        let seed = self.seed ^ (stream as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        self.streams
+            .entry(stream)
+            .or_insert_with(|| StdRng::seed_from_u64(seed))
+    }
+}
+
+/// Daily challenges share the seed of the day, other runs get a fresh one.
+fn seed_run(mut rng: ResMut<GameRng>, challenge: Res<DailyChallenge>) {
+    let seed = challenge.seed().unwrap_or_else(rand::random);
+    *rng = GameRng::new(seed);
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+
+    #[test]
+    fn test_streams_are_independent() {
+        let mut rng = GameRng::new(42);
+        let level: u64 = rng.stream(RngStream::Level).gen();
+        let merchant: Vec<u64> = (0..3)
+            .map(|_| rng.stream(RngStream::Merchant).gen())
+            .collect();
+
+        // Drawing the other way round gives every stream the same numbers
+        let mut other = GameRng::new(42);
+        let other_merchant: Vec<u64> = (0..3)
+            .map(|_| other.stream(RngStream::Merchant).gen())
+            .collect();
+        assert_eq!(merchant, other_merchant);
+        assert_eq!(other.stream(RngStream::Level).gen::<u64>(), level);
+        assert_ne!(merchant[0], level);
+    }
+}
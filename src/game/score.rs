@@ -0,0 +1,247 @@
+//! Score of a run and the local high score table.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::screen::Screen;
+
+use super::actors::spawn::DespawnAnimation;
+use super::actors::stats::Health;
+use super::actors::EnemyActor;
+use super::cycle::DayClock;
+use super::daily::DailyChallenge;
+use super::difficulty::Difficulty;
+use super::game_mode::GameMode;
+use super::mutators::ActiveMutators;
+use super::resources::{ResourceKind, VillagePopulation, VillageStock};
+
+/// File the high scores are kept in, next to the game.
+#[cfg(not(target_family = "wasm"))]
+const HIGH_SCORES_PATH: &str = "high_scores.json";
+/// Local storage key the high scores are kept under on the web.
+#[cfg(target_family = "wasm")]
+const HIGH_SCORES_KEY: &str = "high_scores";
+/// Runs kept in the high score table.
+pub const MAX_HIGH_SCORES: usize = 10;
+
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>()
+            .insert_resource(HighScores::load())
+            .add_systems(OnEnter(Screen::Playing), |mut stats: ResMut<RunStats>| {
+                *stats = RunStats::default()
+            })
+            .add_systems(OnEnter(Screen::Lost), record_score)
+            .add_systems(Update, count_kills.run_if(in_state(Screen::Playing)));
+    }
+}
+
+/// What happened during the current run, the rest of the score is read off the village.
+#[derive(Resource, Default, Debug)]
+pub struct RunStats {
+    pub kills: u32,
+    /// Entry of the finished run, with its place in the high score table if it made it.
+    pub result: Option<(HighScore, Option<usize>)>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HighScore {
+    pub score: u32,
+    pub nights: u32,
+    pub kills: u32,
+    pub mode: GameMode,
+    pub difficulty: Difficulty,
+    /// Day of the challenge for daily runs.
+    #[serde(default)]
+    pub date: Option<String>,
+    /// Names of the mutators the run was played with.
+    #[serde(default)]
+    pub mutators: Vec<String>,
+}
+
+pub fn score(nights: u32, kills: u32, population: u32, gold: u32) -> u32 {
+    nights * 100 + kills * 25 + population * 2 + gold / 2
+}
+
+/// Best runs first.
+#[derive(Resource, Serialize, Deserialize, Default, Debug)]
+pub struct HighScores(pub Vec<HighScore>);
+
+impl HighScores {
+    /// Add the run to the table, returns its place if it is good enough to be kept.
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        let rank = self.0.partition_point(|other| other.score >= entry.score);
+        if rank >= MAX_HIGH_SCORES {
+            return None;
+        }
+
+        self.0.insert(rank, entry);
+        self.0.truncate(MAX_HIGH_SCORES);
+        Some(rank)
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    fn load() -> Self {
+        let Ok(bytes) = std::fs::read(HIGH_SCORES_PATH) else {
+            return Self::default();
+        };
+        serde_json::from_slice(&bytes).unwrap_or_else(|err| {
+            warn!("Unable to read high scores: {err}");
+            Self::default()
+        })
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    fn save(&self) {
+        let result = serde_json::to_vec_pretty(self)
+            .map_err(std::io::Error::from)
+            .and_then(|bytes| std::fs::write(HIGH_SCORES_PATH, bytes));
+        if let Err(err) = result {
+            warn!("Unable to save high scores: {err}");
+        }
+    }
+
+    #[cfg(target_family = "wasm")]
+    fn load() -> Self {
+        let Some(json) =
+            local_storage().and_then(|storage| storage.get_item(HIGH_SCORES_KEY).ok().flatten())
+        else {
+            return Self::default();
+        };
+        serde_json::from_str(&json).unwrap_or_else(|err| {
+            warn!("Unable to read high scores: {err}");
+            Self::default()
+        })
+    }
+
+    #[cfg(target_family = "wasm")]
+    fn save(&self) {
+        let Some(storage) = local_storage() else {
+            warn!("Unable to save high scores: no local storage");
+            return;
+        };
+        match serde_json::to_string(self) {
+            Ok(json) => {
+                if storage.set_item(HIGH_SCORES_KEY, &json).is_err() {
+                    warn!("Unable to save high scores: local storage is full or blocked");
+                }
+            }
+            Err(err) => warn!("Unable to save high scores: {err}"),
+        }
+    }
+}
+
+#[cfg(target_family = "wasm")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+/// Count every enemy once as it falls, not the ones leaving at dawn.
+fn count_kills(
+    q_enemies: Query<&Health, (With<EnemyActor>, Added<DespawnAnimation>)>,
+    mut stats: ResMut<RunStats>,
+) {
+    for health in q_enemies.iter() {
+        if health.is_empty() {
+            stats.kills += 1;
+        }
+    }
+}
+
+pub fn record_score(
+    mut stats: ResMut<RunStats>,
+    mut high_scores: ResMut<HighScores>,
+    clock: Res<DayClock>,
+    population: Res<VillagePopulation>,
+    stock: Res<VillageStock>,
+    game_mode: Res<GameMode>,
+    difficulty: Res<Difficulty>,
+    challenge: Res<DailyChallenge>,
+    mutators: Res<ActiveMutators>,
+) {
+    let entry = HighScore {
+        score: score(
+            clock.day,
+            stats.kills,
+            population.0,
+            stock.get(ResourceKind::Gold),
+        ),
+        nights: clock.day,
+        kills: stats.kills,
+        mode: *game_mode,
+        difficulty: *difficulty,
+        date: challenge.date.map(|date| date.to_string()),
+        mutators: mutators.names(),
+    };
+
+    let rank = high_scores.insert(entry.clone());
+    if rank.is_some() {
+        high_scores.save();
+    }
+    stats.result = Some((entry, rank));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(score: u32) -> HighScore {
+        HighScore {
+            score,
+            nights: 0,
+            kills: 0,
+            mode: GameMode::Endless,
+            difficulty: Difficulty::Normal,
+            date: None,
+            mutators: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_high_score_ranking() {
+        let mut high_scores = HighScores::default();
+        for score in 1..=MAX_HIGH_SCORES as u32 {
+            assert_eq!(high_scores.insert(entry(score * 10)), Some(0));
+        }
+
+        // Ties go after the runs already in the table
+        assert_eq!(high_scores.insert(entry(50)), Some(6));
+        assert_eq!(high_scores.0.len(), MAX_HIGH_SCORES);
+        assert_eq!(high_scores.insert(entry(5)), None);
+        assert_eq!(high_scores.0.last().map(|entry| entry.score), Some(20));
+    }
+
+    #[test]
+    fn test_kills_counted_once() {
+        let mut world = World::new();
+        world.init_resource::<RunStats>();
+        let count = world.register_system(count_kills);
+
+        let fallen = Health { value: 0, max: 2 };
+        let killed = world
+            .spawn((EnemyActor, fallen, DespawnAnimation::new(Vec3::ZERO)))
+            .id();
+        // Cleared at dawn
+        world.spawn((
+            EnemyActor,
+            Health::new(2),
+            DespawnAnimation::new(Vec3::ZERO),
+        ));
+        world.run_system(count).unwrap();
+        assert_eq!(world.resource::<RunStats>().kills, 1);
+
+        // Hit again before it is gone
+        world
+            .entity_mut(killed)
+            .insert((fallen, DespawnAnimation::new(Vec3::ZERO)));
+        world.run_system(count).unwrap();
+        assert_eq!(world.resource::<RunStats>().kills, 1);
+    }
+
+    #[test]
+    fn test_score() {
+        assert_eq!(score(4, 6, 30, 100), 400 + 150 + 60 + 50);
+    }
+}
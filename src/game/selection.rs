@@ -4,7 +4,8 @@ use bevy::utils::HashSet;
 
 use super::actors::stats::Movement;
 use super::actors::{ActorTurnState, EnemyActor, PlayerActor};
-use super::components::GroundTileLayer;
+use super::components::{Gate, GroundTileLayer};
+use super::constants::{AREA_PREVIEW_COLOR, BLOCKED_TILE_COLOR};
 use super::deployment::deploy_unit;
 use super::map::VillageMap;
 use super::picking::TilePressedEvent;
@@ -61,6 +62,30 @@ impl SelectedActor {
 pub struct SelectedTiles {
     pub color: Color,
     pub tiles: HashSet<Tile>,
+    /// In range but out of sight, drawn apart from [`Self::tiles`].
+    pub blocked: HashSet<Tile>,
+    /// Preview of the tiles an item would affect, drawn over everything else.
+    pub area: HashSet<Tile>,
+}
+
+impl SelectedTiles {
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+        self.blocked.clear();
+        self.area.clear();
+    }
+
+    pub fn tint(&self, tile: &Tile) -> Option<Color> {
+        if self.area.contains(tile) {
+            Some(AREA_PREVIEW_COLOR.into())
+        } else if self.tiles.contains(tile) {
+            Some(self.color)
+        } else if self.blocked.contains(tile) {
+            Some(BLOCKED_TILE_COLOR.into())
+        } else {
+            None
+        }
+    }
 }
 
 fn color_selected_tiles(
@@ -68,11 +93,7 @@ fn color_selected_tiles(
     mut query: Query<(&mut Sprite, &Tile), With<GroundTileLayer>>,
 ) {
     for (mut s, tile) in query.iter_mut() {
-        s.color = if selected_tiles.tiles.contains(tile) {
-            selected_tiles.color
-        } else {
-            Color::WHITE
-        };
+        s.color = selected_tiles.tint(tile).unwrap_or(Color::WHITE);
     }
 }
 
@@ -83,6 +104,7 @@ pub fn show_movement_range(
     mut selected_tiles: ResMut<SelectedTiles>,
     village_map: Res<VillageMap>,
     player_actor_query: Query<Entity, With<PlayerActor>>,
+    gate_query: Query<Entity, With<Gate>>,
 ) {
     let Some(entity) = selected_unit.entity else {
         return;
@@ -93,22 +115,21 @@ pub fn show_movement_range(
         return;
     };
 
-    if turn_state.used_move && turn_state.previous_position.is_none() {
-        selected_tiles.tiles.clear();
+    if turn_state.used_move {
+        selected_tiles.clear();
         return;
     }
 
     let allied_actors: Vec<Entity> = if player_actor_query.contains(entity) {
-        player_actor_query.iter().collect()
+        player_actor_query.iter().chain(gate_query.iter()).collect()
     } else if q_enemies.contains(entity) {
         q_enemies.iter().collect()
     } else {
         vec![]
     };
 
-    let start = turn_state.previous_position.unwrap_or(tile);
-
-    let tiles = village_map.flood(start, movement.0, &TileDir::EDGES, false, &allied_actors);
+    let tiles = village_map.flood(tile, movement.0, &TileDir::EDGES, false, &allied_actors);
+    selected_tiles.clear();
     selected_tiles.tiles = tiles;
     match q_enemies.contains(entity) {
         true => selected_tiles.color = css::INDIAN_RED.into(),
@@ -202,3 +223,45 @@ pub fn dispatch_object_pressed(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use crate::game::components::Wall;
+    use crate::path_finding::tiles::TileDim;
+
+    use super::*;
+
+    /// Tiles the unit can reach with a structure right in front of it.
+    fn movement_range(unit: impl Bundle, structure: impl Bundle) -> HashSet<Tile> {
+        let mut world = World::new();
+        world.insert_resource(VillageMap::new(TileDim(3, 1)));
+        world.init_resource::<SelectedActor>();
+        world.init_resource::<SelectedTiles>();
+
+        let unit = world
+            .spawn((unit, Movement(2), ActorTurnState::default()))
+            .id();
+        let structure = world.spawn(structure).id();
+        let mut village_map = world.resource_mut::<VillageMap>();
+        village_map.actors.set(Tile(0, 0), unit);
+        village_map.actors.set(Tile(1, 0), structure);
+        world.resource_mut::<SelectedActor>().set(unit);
+
+        world.run_system_once(show_movement_range);
+        world.resource::<SelectedTiles>().tiles.clone()
+    }
+
+    #[test]
+    fn test_heroes_pass_gates() {
+        assert!(movement_range(PlayerActor, Gate).contains(&Tile(2, 0)));
+        assert!(!movement_range(PlayerActor, Wall).contains(&Tile(2, 0)));
+    }
+
+    #[test]
+    fn test_enemies_stop_at_gates() {
+        assert!(!movement_range(EnemyActor, Gate).contains(&Tile(2, 0)));
+        assert!(!movement_range(EnemyActor, Wall).contains(&Tile(2, 0)));
+    }
+}
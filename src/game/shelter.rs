@@ -0,0 +1,214 @@
+//! Villagers hide in buildings at night and are lost with the building if it falls.
+
+use bevy::color::palettes::css;
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use sickle_ui::prelude::*;
+
+use crate::screen::Screen;
+use crate::ui::palette::LABEL_SIZE;
+use crate::ui::prelude::InteractionPalette;
+
+use super::actors::stats::Health;
+use super::actors::Structure;
+use super::components::{PopulationCapacity, StructureTier};
+use super::constants::{BIG_TEXT_SIZE, TEXT_SIZE};
+use super::construction::{BuildingSite, StructureType};
+use super::cycle::TimeOfDay;
+use super::ledger::{Ledger, LedgerSource};
+use super::resources::{VillageJobs, VillagePopulation};
+use super::MODAL_Z_LAYER;
+
+pub struct ShelterPlugin;
+
+impl Plugin for ShelterPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NightCasualties>()
+            .add_systems(
+                OnEnter(Screen::Playing),
+                |mut casualties: ResMut<NightCasualties>| {
+                    casualties.0.clear();
+                },
+            )
+            .add_systems(
+                OnEnter(TimeOfDay::Night),
+                shelter_villagers.run_if(in_state(Screen::Playing)),
+            )
+            .add_systems(
+                Update,
+                lose_sheltered_villagers
+                    .run_if(in_state(Screen::Playing).and_then(in_state(TimeOfDay::Night))),
+            )
+            .add_systems(
+                OnEnter(TimeOfDay::Day),
+                casualty_report_layout.run_if(in_state(Screen::Playing)),
+            )
+            .add_systems(
+                Update,
+                dismiss_report_btn_interaction.run_if(in_state(Screen::Playing)),
+            );
+    }
+}
+
+/// Villagers hiding inside a structure for the night.
+#[derive(Component, Debug)]
+pub struct Sheltered(pub u32);
+
+/// Villagers lost with each fallen structure during the night.
+#[derive(Resource, Default, Debug)]
+pub struct NightCasualties(pub Vec<(String, u32)>);
+
+#[derive(Component)]
+pub struct CasualtyReport;
+
+#[derive(Component)]
+pub struct DismissReportButton;
+
+/// Split the population across every standing structure that can house villagers,
+/// the ones that don't fit stay out of harm's way.
+fn shelter_villagers(
+    mut commands: Commands,
+    q_shelters: Query<(Entity, &PopulationCapacity), (With<Structure>, Without<BuildingSite>)>,
+    population: Res<VillagePopulation>,
+    mut casualties: ResMut<NightCasualties>,
+) {
+    casualties.0.clear();
+
+    let mut remaining = population.0;
+    for (entity, capacity) in q_shelters.iter() {
+        let sheltered = remaining.min(capacity.0);
+        if sheltered == 0 {
+            continue;
+        }
+
+        remaining -= sheltered;
+        commands.entity(entity).insert(Sheltered(sheltered));
+    }
+}
+
+fn lose_sheltered_villagers(
+    mut commands: Commands,
+    q_shelters: Query<
+        (
+            Entity,
+            &Sheltered,
+            &Health,
+            Option<&StructureType>,
+            Option<&StructureTier>,
+        ),
+        Changed<Health>,
+    >,
+    mut population: ResMut<VillagePopulation>,
+    mut jobs: ResMut<VillageJobs>,
+    mut casualties: ResMut<NightCasualties>,
+    mut ledger: ResMut<Ledger>,
+) {
+    for (entity, sheltered, health, structure_type, tier) in q_shelters.iter() {
+        if health.value > 0 {
+            continue;
+        }
+
+        let tier = tier.copied().unwrap_or_default().0;
+        let name = structure_type
+            .map(|s| s.tier_name(tier))
+            .unwrap_or("Structure");
+        casualties.0.push((name.to_string(), sheltered.0));
+
+        let lost = sheltered.0.min(population.0);
+        population.0 -= lost;
+        ledger.population(LedgerSource::Casualties, -(lost as i32));
+        jobs.fit_to(population.0);
+        commands.entity(entity).remove::<Sheltered>();
+    }
+}
+
+fn casualty_report_layout(
+    mut commands: Commands,
+    q_sheltered: Query<Entity, With<Sheltered>>,
+    casualties: Res<NightCasualties>,
+) {
+    // Everyone comes out at dawn
+    for entity in q_sheltered.iter() {
+        commands.entity(entity).remove::<Sheltered>();
+    }
+
+    if casualties.0.is_empty() {
+        return;
+    }
+
+    let total: u32 = casualties.0.iter().map(|(_, lost)| lost).sum();
+
+    commands
+        .ui_builder(UiRoot)
+        .column(|ui| {
+            ui.column(|ui| {
+                ui.style()
+                    .padding(UiRect::all(Val::Px(18.)))
+                    .border(UiRect::all(Val::Px(2.)))
+                    .border_color(Color::WHITE)
+                    .border_radius(BorderRadius::all(Val::Px(16.)))
+                    .background_color(Color::BLACK)
+                    .align_items(AlignItems::Center)
+                    .row_gap(Val::Px(10.));
+
+                ui.label(LabelConfig::from("Casualty Report"))
+                    .style()
+                    .font_size(BIG_TEXT_SIZE);
+
+                for (name, lost) in casualties.0.iter() {
+                    ui.label(LabelConfig::from(format!(
+                        "{lost} villagers died when a {name} fell."
+                    )))
+                    .style()
+                    .font_size(TEXT_SIZE);
+                }
+
+                ui.label(LabelConfig::from(format!("{total} villagers lost.")))
+                    .style()
+                    .font_size(LABEL_SIZE);
+
+                ui.container(ButtonBundle::default(), |ui| {
+                    ui.label(LabelConfig::from("Dismiss"))
+                        .style()
+                        .font_size(TEXT_SIZE);
+                })
+                .insert((
+                    DismissReportButton,
+                    InteractionPalette {
+                        none: css::BLACK.into(),
+                        hovered: css::DARK_RED.into(),
+                        pressed: css::INDIAN_RED.into(),
+                    },
+                ))
+                .style()
+                .padding(UiRect::all(Val::Px(4.)))
+                .border(UiRect::all(Val::Px(2.)))
+                .border_color(Color::WHITE);
+            });
+        })
+        .insert((CasualtyReport, StateScoped(Screen::Playing)))
+        .style()
+        .focus_policy(FocusPolicy::Block)
+        .z_index(ZIndex::Global(MODAL_Z_LAYER))
+        .width(Val::Percent(100.))
+        .height(Val::Percent(100.))
+        .background_color(Color::srgba(0.25, 0.25, 0.25, 0.75))
+        .justify_content(JustifyContent::Center)
+        .align_items(AlignItems::Center);
+}
+
+fn dismiss_report_btn_interaction(
+    mut commands: Commands,
+    q_interactions: Query<&Interaction, (Changed<Interaction>, With<DismissReportButton>)>,
+    q_reports: Query<Entity, With<CasualtyReport>>,
+) {
+    for interaction in q_interactions.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        for entity in q_reports.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,543 @@
+//! Demolishing, cancelling and repairing structures during the building turn.
+
+use bevy::color::palettes::css;
+use bevy::prelude::*;
+use sickle_ui::prelude::*;
+
+use crate::screen::playing::GameState;
+use crate::screen::Screen;
+use crate::ui::prelude::InteractionPalette;
+
+use super::actors::spawn::DespawnAnimation;
+use super::actors::stats::Health;
+use super::actors::Structure;
+use super::assets::SfxKey;
+use super::audio::sfx::PlaySfx;
+use super::components::{
+    ConstructionWorkers, Defence, PopulationCapacity, Production, RemainingConstructionTurns,
+    RemainingDemolitionTurns, RemainingUpgradeTurns, RepairWorkers, StructureTier,
+};
+use super::constants::{
+    DEMOLISH_REFUND_PERCENT, REPAIR_COST_PER_HEALTH, REPAIR_WORKERS, TEXT_SIZE,
+};
+use super::construction::{BuildingSite, StructureActions, StructureCosts, StructureType};
+use super::cycle::TimeOfDay;
+use super::ledger::{Ledger, LedgerSource};
+use super::map::VillageMap;
+use super::resources::{
+    ResourceBundle, ResourceKind, SelectedStructueType, VillageJobs, VillagePopulation,
+    VillageStock, VillagerJob,
+};
+use super::selection::{dispatch_object_pressed, ObjectPressedEvent};
+
+pub struct StructureActionsPlugin;
+
+impl Plugin for StructureActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelectedStructure>()
+            .add_systems(
+                Update,
+                (
+                    select_structure.after(dispatch_object_pressed),
+                    structure_action_buttons,
+                    update_structure_actions.run_if(
+                        resource_changed::<SelectedStructure>
+                            .or_else(resource_changed::<VillageStock>),
+                    ),
+                )
+                    .chain()
+                    .run_if(in_state(Screen::Playing).and_then(in_state(GameState::BuildingTurn))),
+            )
+            .add_systems(OnEnter(GameState::BuildingTurn), update_structure_actions)
+            .add_systems(
+                OnEnter(TimeOfDay::Day),
+                (
+                    update_demolition_progress,
+                    update_upgrade_progress,
+                    release_repair_workers,
+                )
+                    .run_if(in_state(Screen::Playing)),
+            )
+            .add_systems(
+                OnExit(TimeOfDay::Day),
+                |mut selected: ResMut<SelectedStructure>| {
+                    selected.0 = None;
+                },
+            );
+    }
+}
+
+/// Structure picked on the map during the building turn.
+#[derive(Resource, Default)]
+pub struct SelectedStructure(pub Option<Entity>);
+
+#[derive(Component, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StructureActionButton {
+    /// Tear down an unfinished [`BuildingSite`] for a full refund.
+    CancelSite,
+    Repair,
+    Upgrade,
+    Demolish,
+    StopDemolition,
+    Deselect,
+}
+
+impl StructureActionButton {
+    fn palette(self) -> InteractionPalette {
+        match self {
+            StructureActionButton::Repair
+            | StructureActionButton::Upgrade
+            | StructureActionButton::StopDemolition => InteractionPalette {
+                none: css::TEAL.into(),
+                hovered: css::DARK_CYAN.into(),
+                pressed: css::DARK_SLATE_GRAY.into(),
+            },
+            _ => InteractionPalette {
+                none: css::RED.into(),
+                hovered: css::INDIAN_RED.into(),
+                pressed: css::DARK_RED.into(),
+            },
+        }
+    }
+}
+
+fn demolish_refund(
+    costs: &StructureCosts,
+    structure_type: Option<&StructureType>,
+) -> ResourceBundle {
+    structure_type
+        .and_then(|structure_type| costs.get(structure_type))
+        .map(|cost| cost.resources.percent(DEMOLISH_REFUND_PERCENT))
+        .unwrap_or_default()
+}
+
+fn repair_cost(health: &Health) -> u32 {
+    (health.max - health.value) * REPAIR_COST_PER_HEALTH
+}
+
+fn select_structure(
+    mut events: EventReader<ObjectPressedEvent>,
+    q_structures: Query<(), With<Structure>>,
+    selected_structure_type: Res<SelectedStructueType>,
+    mut selected: ResMut<SelectedStructure>,
+) {
+    let Some(ObjectPressedEvent(entity)) = events.read().last().copied() else {
+        return;
+    };
+
+    // Pressing a structure while placing a new one shouldn't select it
+    if selected_structure_type.0.is_some() || !q_structures.contains(entity) {
+        return;
+    }
+
+    selected.0 = Some(entity);
+}
+
+fn update_structure_actions(
+    mut commands: Commands,
+    q_structure_actions: Query<Entity, With<StructureActions>>,
+    q_structures: Query<(
+        Option<&StructureType>,
+        Option<&StructureTier>,
+        Option<&Health>,
+        Option<&RemainingConstructionTurns>,
+        Option<&RemainingUpgradeTurns>,
+        Option<&RemainingDemolitionTurns>,
+        Has<RepairWorkers>,
+    )>,
+    q_buildings: Query<
+        Entity,
+        (
+            With<Structure>,
+            Without<Defence>,
+            Without<RemainingDemolitionTurns>,
+        ),
+    >,
+    mut selected: ResMut<SelectedStructure>,
+    costs: Res<StructureCosts>,
+) {
+    let Ok(container) = q_structure_actions.get_single() else {
+        return;
+    };
+    commands.entity(container).despawn_descendants();
+
+    let Some(entity) = selected.0 else {
+        return;
+    };
+
+    let Ok((structure_type, tier, health, construction, upgrade, demolition, is_repairing)) =
+        q_structures.get(entity)
+    else {
+        // Structure was destroyed
+        selected.0 = None;
+        return;
+    };
+
+    let tier = tier.copied().unwrap_or_default().0;
+    let name = structure_type
+        .map(|s| s.tier_name(tier))
+        .unwrap_or("Structure");
+    // Buildings already being demolished don't count
+    let is_last_building = q_buildings.contains(entity) && q_buildings.iter().all(|e| e == entity);
+
+    let mut status = Vec::new();
+    let mut actions = Vec::new();
+
+    if let Some(construction) = construction {
+        status.push(format!("Under construction: {} turns", construction.0));
+        actions.push((
+            StructureActionButton::CancelSite,
+            "Cancel (full refund)".to_string(),
+        ));
+    }
+
+    if let Some(health) = health {
+        status.push(format!("Health: {}/{}", health.value, health.max));
+        if is_repairing {
+            status.push("Being repaired.".to_string());
+        } else if !health.is_full() {
+            actions.push((
+                StructureActionButton::Repair,
+                format!(
+                    "Repair ({} gold, {} workers)",
+                    repair_cost(health),
+                    REPAIR_WORKERS
+                ),
+            ));
+        }
+    }
+
+    let next_upgrade = structure_type.and_then(|s| s.next_upgrade(tier));
+    match (upgrade, next_upgrade) {
+        (Some(upgrade), Some(next_upgrade)) => {
+            status.push(format!(
+                "Upgrading to {}: {} turns",
+                next_upgrade.name, upgrade.0
+            ));
+        }
+        (None, Some(next_upgrade)) if construction.is_none() && demolition.is_none() => {
+            let cost = &next_upgrade.cost;
+            actions.push((
+                StructureActionButton::Upgrade,
+                format!(
+                    "Upgrade to {} ({}, {} workers, {} turns)",
+                    next_upgrade.name, cost.resources, cost.workers, cost.turns
+                ),
+            ));
+        }
+        _ => {}
+    }
+
+    match demolition {
+        Some(demolition) => {
+            status.push(format!("Demolished in {} turns", demolition.0));
+            actions.push((StructureActionButton::StopDemolition, "Stop".to_string()));
+        }
+        None if construction.is_none() && upgrade.is_none() => match is_last_building {
+            true => status.push("Can't demolish the last building.".to_string()),
+            false => actions.push((
+                StructureActionButton::Demolish,
+                match demolish_refund(&costs, structure_type) {
+                    refund if refund.is_empty() => "Demolish".to_string(),
+                    refund => format!("Demolish (refund {refund})"),
+                },
+            )),
+        },
+        None => {}
+    }
+
+    actions.push((StructureActionButton::Deselect, "Close".to_string()));
+
+    commands.ui_builder(container).column(|ui| {
+        ui.style()
+            .padding(UiRect::all(Val::Px(10.)))
+            .border(UiRect::all(Val::Px(2.)))
+            .border_color(Color::WHITE.with_alpha(0.3))
+            .background_color(Color::BLACK.with_alpha(0.3))
+            .row_gap(Val::Px(10.));
+
+        ui.row(|ui| {
+            ui.label(LabelConfig::from(name))
+                .style()
+                .font_size(TEXT_SIZE);
+        });
+
+        for line in status {
+            ui.row(|ui| {
+                ui.label(LabelConfig::from(line))
+                    .style()
+                    .font_size(TEXT_SIZE * 0.8);
+            });
+        }
+
+        for (action, text) in actions {
+            ui.row(|ui| {
+                ui.style().justify_content(JustifyContent::End);
+                ui.container(ButtonBundle::default(), |ui| {
+                    ui.label(LabelConfig::from(text))
+                        .style()
+                        .font_size(TEXT_SIZE);
+                })
+                .insert((action, action.palette()))
+                .style()
+                .padding(UiRect::all(Val::Px(4.)))
+                .border_radius(BorderRadius::all(Val::Px(4.)));
+            });
+        }
+    });
+}
+
+fn structure_action_buttons(
+    mut commands: Commands,
+    q_interactions: Query<(&Interaction, &StructureActionButton), Changed<Interaction>>,
+    mut q_structures: Query<(
+        &Transform,
+        Option<&StructureType>,
+        Option<&StructureTier>,
+        Option<&mut Health>,
+        Option<&ConstructionWorkers>,
+        Has<BuildingSite>,
+        Has<RepairWorkers>,
+        Has<RemainingUpgradeTurns>,
+        Has<RemainingDemolitionTurns>,
+    )>,
+    q_buildings: Query<
+        (Entity, Has<RemainingDemolitionTurns>),
+        (With<Structure>, Without<Defence>),
+    >,
+    mut selected: ResMut<SelectedStructure>,
+    mut village_map: ResMut<VillageMap>,
+    mut stock: ResMut<VillageStock>,
+    mut jobs: ResMut<VillageJobs>,
+    mut ledger: ResMut<Ledger>,
+    population: Res<VillagePopulation>,
+    costs: Res<StructureCosts>,
+) {
+    // Demolitions queued this frame aren't on the buildings yet
+    let mut demolished: Vec<Entity> = Vec::new();
+
+    for (interaction, action) in q_interactions.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        if *action == StructureActionButton::Deselect {
+            selected.0 = None;
+            continue;
+        }
+
+        let Some(entity) = selected.0 else {
+            continue;
+        };
+        let Ok((
+            transform,
+            structure_type,
+            tier,
+            health,
+            workers,
+            is_site,
+            is_repairing,
+            is_upgrading,
+            is_demolishing,
+        )) = q_structures.get_mut(entity)
+        else {
+            continue;
+        };
+
+        match action {
+            StructureActionButton::CancelSite => {
+                if !is_site {
+                    continue;
+                }
+
+                if let Some(cost) = structure_type.and_then(|s| costs.get(s)) {
+                    stock.deposit(&cost.resources);
+                    ledger.gold(
+                        LedgerSource::Construction,
+                        cost.resources.get(ResourceKind::Gold) as i32,
+                    );
+                }
+                if let Some(workers) = workers {
+                    jobs.release(VillagerJob::Construction, workers.0);
+                }
+
+                village_map.actors.remove_entity(entity);
+                commands
+                    .entity(entity)
+                    .insert(DespawnAnimation::new(transform.translation).with_recursive(true));
+                commands.trigger(PlaySfx::Key(SfxKey::CoinPurchase));
+                selected.0 = None;
+            }
+            StructureActionButton::Repair => {
+                let Some(mut health) = health else {
+                    continue;
+                };
+                let cost = repair_cost(&health);
+                if is_repairing
+                    || health.is_full()
+                    || !stock.can_afford(&ResourceBundle::gold(cost))
+                    || jobs.resting(population.0) < REPAIR_WORKERS
+                {
+                    continue;
+                }
+
+                stock.spend(&ResourceBundle::gold(cost));
+                ledger.gold(LedgerSource::Repair, -(cost as i32));
+                jobs.assign(VillagerJob::Construction, REPAIR_WORKERS, population.0);
+                health.value = health.max;
+                commands
+                    .entity(entity)
+                    .insert(RepairWorkers(REPAIR_WORKERS));
+                commands.trigger(PlaySfx::Key(SfxKey::BuildingPlacement));
+                selected.set_changed();
+            }
+            StructureActionButton::Upgrade => {
+                let tier = tier.copied().unwrap_or_default().0;
+                let Some(upgrade) = structure_type.and_then(|s| s.next_upgrade(tier)) else {
+                    continue;
+                };
+                let cost = &upgrade.cost;
+                if is_site
+                    || is_upgrading
+                    || is_demolishing
+                    || !stock.can_afford(&cost.resources)
+                    || jobs.resting(population.0) < cost.workers
+                {
+                    continue;
+                }
+
+                stock.spend(&cost.resources);
+                ledger.gold(
+                    LedgerSource::Upgrade,
+                    -(cost.resources.get(ResourceKind::Gold) as i32),
+                );
+                jobs.assign(VillagerJob::Construction, cost.workers, population.0);
+                commands.entity(entity).insert((
+                    RemainingUpgradeTurns(cost.turns),
+                    ConstructionWorkers(cost.workers),
+                ));
+                commands.trigger(PlaySfx::Key(SfxKey::BuildingPlacement));
+                selected.set_changed();
+            }
+            StructureActionButton::Demolish => {
+                // Walls can always go, the last building has to stay
+                let keeps_a_building = !q_buildings.contains(entity)
+                    || q_buildings.iter().any(|(other, is_demolishing)| {
+                        other != entity && !is_demolishing && !demolished.contains(&other)
+                    });
+                if is_site || is_upgrading || is_demolishing || !keeps_a_building {
+                    continue;
+                }
+                demolished.push(entity);
+
+                // 1 turn per tile
+                let footprint = village_map.actors.footprint(entity);
+                commands.entity(entity).insert(RemainingDemolitionTurns(
+                    (footprint.x() * footprint.y()) as u32,
+                ));
+                selected.set_changed();
+            }
+            StructureActionButton::StopDemolition => {
+                demolished.retain(|other| *other != entity);
+                commands.entity(entity).remove::<RemainingDemolitionTurns>();
+                selected.set_changed();
+            }
+            StructureActionButton::Deselect => {}
+        }
+    }
+}
+
+fn update_demolition_progress(
+    mut commands: Commands,
+    mut q_demolitions: Query<(
+        Entity,
+        &mut RemainingDemolitionTurns,
+        &Transform,
+        Option<&StructureType>,
+    )>,
+    mut village_map: ResMut<VillageMap>,
+    mut stock: ResMut<VillageStock>,
+    mut ledger: ResMut<Ledger>,
+    mut selected: ResMut<SelectedStructure>,
+    costs: Res<StructureCosts>,
+) {
+    for (entity, mut remaining, transform, structure_type) in q_demolitions.iter_mut() {
+        remaining.0 = remaining.0.saturating_sub(1);
+        if remaining.0 > 0 {
+            continue;
+        }
+
+        let refund = demolish_refund(&costs, structure_type);
+        stock.deposit(&refund);
+        ledger.gold(
+            LedgerSource::Demolition,
+            refund.get(ResourceKind::Gold) as i32,
+        );
+        village_map.actors.remove_entity(entity);
+        commands
+            .entity(entity)
+            .remove::<RemainingDemolitionTurns>()
+            .insert(DespawnAnimation::new(transform.translation).with_recursive(true));
+
+        if selected.0 == Some(entity) {
+            selected.0 = None;
+        }
+    }
+}
+
+fn update_upgrade_progress(
+    mut commands: Commands,
+    mut q_upgrades: Query<(
+        Entity,
+        &mut RemainingUpgradeTurns,
+        &ConstructionWorkers,
+        &StructureType,
+        Option<&StructureTier>,
+        &mut Health,
+    )>,
+    mut jobs: ResMut<VillageJobs>,
+    mut selected: ResMut<SelectedStructure>,
+) {
+    for (entity, mut remaining, workers, structure_type, tier, mut health) in q_upgrades.iter_mut()
+    {
+        remaining.0 = remaining.0.saturating_sub(1);
+        if remaining.0 > 0 {
+            continue;
+        }
+
+        jobs.release(VillagerJob::Construction, workers.0);
+        let mut entity_commands = commands.entity(entity);
+        entity_commands.remove::<(RemainingUpgradeTurns, ConstructionWorkers)>();
+
+        let tier = tier.copied().unwrap_or_default().0;
+        let Some(upgrade) = structure_type.next_upgrade(tier) else {
+            continue;
+        };
+
+        // Keep the damage taken, the extra health is gained on top
+        let gained = upgrade.health.saturating_sub(health.max);
+        health.max = upgrade.health.max(health.max);
+        health.value += gained;
+
+        entity_commands.insert((
+            StructureTier(tier + 1),
+            PopulationCapacity(upgrade.population_capacity),
+            Production(upgrade.production),
+        ));
+
+        if selected.0 == Some(entity) {
+            selected.set_changed();
+        }
+    }
+}
+
+fn release_repair_workers(
+    mut commands: Commands,
+    q_repairs: Query<(Entity, &RepairWorkers)>,
+    mut jobs: ResMut<VillageJobs>,
+) {
+    for (entity, workers) in q_repairs.iter() {
+        jobs.release(VillagerJob::Construction, workers.0);
+        commands.entity(entity).remove::<RepairWorkers>();
+    }
+}
@@ -2,6 +2,8 @@ use bevy::prelude::*;
 use bevy::sprite::Anchor;
 use bevy::utils::HashMap;
 
+use crate::path_finding::tiles::{Tile, TileDim};
+
 use super::level::Terrain;
 
 /// Width of a tile.
@@ -54,6 +56,7 @@ fn load_tiles(asset_server: Res<AssetServer>, mut tile_set: ResMut<TileSet>) {
         "ne_corner",
         "se_corner",
         "block_blue",
+        "block_green",
         "block_grey",
         "block_orange",
     ];
@@ -74,6 +77,15 @@ pub fn tile_coord_translation(x: f32, y: f32, layer: f32) -> Vec3 {
     translation
 }
 
+/// Convert a footprint anchored at `tile` to the world translation of its center.
+pub fn footprint_translation(tile: Tile, footprint: TileDim, layer: f32) -> Vec3 {
+    tile_coord_translation(
+        tile.x() as f32 + (footprint.x() - 1) as f32 * 0.5,
+        tile.y() as f32 + (footprint.y() - 1) as f32 * 0.5,
+        layer,
+    )
+}
+
 #[derive(Resource, Default, Debug)]
 pub struct TileSet(HashMap<&'static str, Handle<Image>>);
 
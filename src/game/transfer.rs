@@ -0,0 +1,295 @@
+//! Handing items between heroes, as an action in battle and freely while building.
+
+use bevy::color::palettes::css;
+use bevy::prelude::*;
+
+use crate::screen::playing::GameState;
+use crate::screen::Screen;
+
+use super::actors::{ActorTurnState, PlayerActor};
+use super::actors_list::GiveItemButton;
+use super::inventory::{Inventory, Item};
+use super::item::InventorySelection;
+use super::map::VillageMap;
+use super::selection::{self, SelectedActor, SelectedTiles};
+use super::undo::{TurnCommand, UndoStack};
+
+pub struct TransferPlugin;
+
+impl Plugin for TransferPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ItemTransfer>()
+            .add_systems(
+                OnExit(GameState::BuildingTurn),
+                |mut transfer: ResMut<ItemTransfer>| transfer.0 = None,
+            )
+            .add_systems(
+                OnExit(GameState::BattleTurn),
+                |mut transfer: ResMut<ItemTransfer>| transfer.0 = None,
+            )
+            .add_systems(
+                Update,
+                (
+                    set_give_button_display,
+                    (
+                        give_btn_interaction,
+                        give_item_to_selected
+                            .after(selection::set_selected_unit)
+                            .run_if(resource_changed::<SelectedActor>),
+                    )
+                        .chain()
+                        .run_if(
+                            in_state(GameState::BuildingTurn)
+                                .or_else(in_state(GameState::BattleTurn)),
+                        ),
+                )
+                    .run_if(in_state(Screen::Playing)),
+            );
+    }
+}
+
+/// Hero and inventory slot of an item waiting for someone to receive it.
+#[derive(Resource, Default, Debug)]
+pub struct ItemTransfer(pub Option<(Entity, usize)>);
+
+/// An item handed from a slot of the giver to a slot of the receiver.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ItemMove {
+    pub giver_slot: usize,
+    pub receiver_slot: usize,
+    pub item: Item,
+    /// Item the receiver had to trade back for lack of room.
+    pub traded: Option<Item>,
+}
+
+impl ItemMove {
+    /// Hand the item over again, fails if either slot changed since.
+    pub fn apply(&self, giver: &mut Inventory, receiver: &mut Inventory) -> bool {
+        swap_items(
+            (giver, self.giver_slot, Some(self.item)),
+            (receiver, self.receiver_slot, self.traded),
+        )
+    }
+
+    /// Give the item back, fails if either slot changed since.
+    pub fn revert(&self, giver: &mut Inventory, receiver: &mut Inventory) -> bool {
+        swap_items(
+            (giver, self.giver_slot, self.traded),
+            (receiver, self.receiver_slot, Some(self.item)),
+        )
+    }
+}
+
+/// Swap the content of both slots if they still hold the expected items.
+fn swap_items(
+    (a, a_slot, a_item): (&mut Inventory, usize, Option<Item>),
+    (b, b_slot, b_item): (&mut Inventory, usize, Option<Item>),
+) -> bool {
+    if a_slot >= a.slot_count() || b_slot >= b.slot_count() {
+        return false;
+    }
+    if a.get(a_slot) != a_item || b.get(b_slot) != b_item {
+        return false;
+    }
+
+    a.replace(a_slot, b_item);
+    b.replace(b_slot, a_item);
+    a.selected_item = None;
+    b.selected_item = None;
+    true
+}
+
+/// Move the item in `slot` to the receiver, a receiver without room trades its item in the same slot.
+pub fn give_item(giver: &mut Inventory, slot: usize, receiver: &mut Inventory) -> Option<ItemMove> {
+    let item = giver.get(slot)?;
+
+    let receiver_slot = match receiver.get_empty_slot() {
+        Some(empty) => empty,
+        None if receiver.slot_count() > 0 => slot.min(receiver.slot_count() - 1),
+        None => return None,
+    };
+
+    let item_move = ItemMove {
+        giver_slot: slot,
+        receiver_slot,
+        item,
+        traded: receiver.get(receiver_slot),
+    };
+    item_move.apply(giver, receiver).then_some(item_move)
+}
+
+fn set_give_button_display(
+    game_state: Res<State<GameState>>,
+    mut q_buttons: Query<&mut Style, With<GiveItemButton>>,
+) {
+    let display = match game_state.get() {
+        GameState::BuildingTurn | GameState::BattleTurn => Display::Flex,
+        _ => Display::None,
+    };
+
+    for mut style in q_buttons.iter_mut() {
+        if style.display != display {
+            style.display = display;
+        }
+    }
+}
+
+/// Pick up the selected item, the next hero selected receives it.
+fn give_btn_interaction(
+    q_interactions: Query<&Interaction, (Changed<Interaction>, With<GiveItemButton>)>,
+    q_inventories: Query<(&Inventory, &ActorTurnState), With<PlayerActor>>,
+    selected_actor: Res<SelectedActor>,
+    game_state: Res<State<GameState>>,
+    village_map: Res<VillageMap>,
+    mut transfer: ResMut<ItemTransfer>,
+    mut inventory_selection: ResMut<InventorySelection>,
+    mut selected_tiles: ResMut<SelectedTiles>,
+) {
+    for interaction in q_interactions.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        // Pressing again puts the item back
+        if transfer.0.take().is_some() {
+            selected_tiles.clear();
+            continue;
+        }
+
+        let Some(entity) = selected_actor.entity else {
+            continue;
+        };
+        let Ok((inventory, turn_state)) = q_inventories.get(entity) else {
+            continue;
+        };
+        let Some(slot) = inventory.selected_item else {
+            continue;
+        };
+
+        let in_battle = *game_state.get() == GameState::BattleTurn;
+        if in_battle && turn_state.used_action {
+            continue;
+        }
+
+        transfer.0 = Some((entity, slot));
+        // Giving the item away replaces using it
+        inventory_selection.selection = None;
+
+        selected_tiles.clear();
+        if let (true, Some(tile)) = (in_battle, village_map.actors.locate(entity)) {
+            selected_tiles.tiles.extend(tile.all_adjacent());
+            selected_tiles.color = css::AQUA.into();
+        }
+    }
+}
+
+/// Hand the item over once another hero is selected, adjacent ones only during battle.
+fn give_item_to_selected(
+    mut q_inventories: Query<(&mut Inventory, &mut ActorTurnState), With<PlayerActor>>,
+    selected_actor: Res<SelectedActor>,
+    game_state: Res<State<GameState>>,
+    village_map: Res<VillageMap>,
+    mut transfer: ResMut<ItemTransfer>,
+    mut undo_stack: ResMut<UndoStack>,
+) {
+    let Some((giver, slot)) = transfer.0 else {
+        return;
+    };
+    let Some(receiver) = selected_actor.entity.filter(|entity| *entity != giver) else {
+        return;
+    };
+
+    // Selecting anything else drops the transfer
+    transfer.0 = None;
+
+    let in_battle = *game_state.get() == GameState::BattleTurn;
+    if in_battle {
+        let (Some(giver_tile), Some(receiver_tile)) = (
+            village_map.actors.locate(giver),
+            village_map.actors.locate(receiver),
+        ) else {
+            return;
+        };
+        if !giver_tile.all_adjacent().contains(&receiver_tile) {
+            return;
+        }
+    }
+
+    let Ok([(mut giver_inventory, mut turn_state), (mut receiver_inventory, _)]) =
+        q_inventories.get_many_mut([giver, receiver])
+    else {
+        return;
+    };
+
+    if in_battle && turn_state.used_action {
+        return;
+    }
+
+    let Some(item_move) = give_item(&mut giver_inventory, slot, &mut receiver_inventory) else {
+        return;
+    };
+    if in_battle {
+        turn_state.used_action = true;
+    }
+
+    undo_stack.push(TurnCommand::Transfer {
+        giver,
+        receiver,
+        item_move: Box::new(item_move),
+        used_action: in_battle,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::inventory::ITEM_TEMPLATES;
+
+    use super::*;
+
+    #[test]
+    fn test_give_item_to_empty_slot() {
+        let mut giver = Inventory::default();
+        let mut receiver = Inventory::default();
+        receiver.clear();
+
+        assert!(give_item(&mut giver, 0, &mut receiver).is_some());
+        assert!(giver.get(0).is_none());
+        assert_eq!(receiver.get(0).map(|item| item.name), Some("sword"));
+        assert!(give_item(&mut giver, 0, &mut receiver).is_none());
+    }
+
+    #[test]
+    fn test_give_item_to_full_inventory_trades() {
+        let mut giver = Inventory::default();
+        let mut receiver = Inventory::default();
+        for slot in 0..receiver.slot_count() {
+            receiver.set(slot, ITEM_TEMPLATES[0]);
+        }
+
+        let item_move = give_item(&mut giver, 0, &mut receiver).unwrap();
+        assert_eq!(giver.get(0).map(|item| item.name), Some("axe"));
+        assert_eq!(receiver.get(0).map(|item| item.name), Some("sword"));
+
+        assert!(item_move.revert(&mut giver, &mut receiver));
+        assert_eq!(giver.get(0).map(|item| item.name), Some("sword"));
+        assert_eq!(receiver.get(0).map(|item| item.name), Some("axe"));
+    }
+
+    #[test]
+    fn test_item_move_checks_slots_before_reverting() {
+        let mut giver = Inventory::default();
+        let mut receiver = Inventory::default();
+        receiver.clear();
+        let item_move = give_item(&mut giver, 0, &mut receiver).unwrap();
+
+        // Sold at the merchant before undoing
+        receiver.take(item_move.receiver_slot);
+        assert!(!item_move.revert(&mut giver, &mut receiver));
+        assert!(giver.get(0).is_none());
+
+        receiver.set(item_move.receiver_slot, item_move.item);
+        assert!(item_move.revert(&mut giver, &mut receiver));
+        assert!(!item_move.revert(&mut giver, &mut receiver));
+        assert!(item_move.apply(&mut giver, &mut receiver));
+    }
+}
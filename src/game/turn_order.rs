@@ -0,0 +1,491 @@
+//! Order in which heroes and enemies take their turns during the night.
+
+use bevy::color::palettes::css;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use sickle_ui::prelude::*;
+
+use crate::screen::playing::GameState;
+use crate::screen::Screen;
+
+use super::actors::stats::{ActorName, Speed};
+use super::actors::{ActorTurnState, ClearUndoEvent, EnemyActor, PlayerActor};
+use super::actors_list::PlayerActorList;
+use super::constants::{TEXT_SIZE, UNIT_LIST_ZINDEX};
+use super::cycle::EndTurn;
+use super::map::VillageMap;
+
+pub struct TurnOrderPlugin;
+
+impl Plugin for TurnOrderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TurnOrder>()
+            .init_resource::<TurnOrderSetting>()
+            .add_event::<EndHeroSlot>()
+            .add_systems(OnEnter(Screen::Playing), turn_order_bar_layout)
+            .add_systems(
+                OnEnter(GameState::Deployment),
+                |mut turn_order: ResMut<TurnOrder>| turn_order.clear(),
+            )
+            .add_systems(OnEnter(GameState::BattleTurn), begin_slot)
+            .add_systems(OnExit(GameState::EnemyTurn), end_enemy_slot)
+            .add_systems(
+                Update,
+                (
+                    end_hero_slot.run_if(in_state(GameState::BattleTurn)),
+                    set_turn_order_bar_display,
+                    update_turn_order_bar.run_if(resource_changed::<TurnOrder>),
+                )
+                    .run_if(in_state(Screen::Playing)),
+            );
+    }
+}
+
+/// How heroes and enemies take turns, levels pick one for variety.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TurnOrderMode {
+    /// All heroes act, then all enemies.
+    #[default]
+    Phases,
+    /// Heroes and enemies take turns one unit at a time.
+    Alternating,
+    /// Faster units act first, whichever side they are on.
+    Initiative,
+}
+
+impl std::fmt::Display for TurnOrderMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TurnOrderMode::Phases => "Phases".fmt(f),
+            TurnOrderMode::Alternating => "Alternating".fmt(f),
+            TurnOrderMode::Initiative => "Initiative".fmt(f),
+        }
+    }
+}
+
+/// Turn order picked on the title screen, without one the level decides.
+#[derive(Resource, Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TurnOrderSetting(pub Option<TurnOrderMode>);
+
+impl std::fmt::Display for TurnOrderSetting {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(mode) => write!(f, "Turns: {mode}"),
+            None => "Turns: Level".fmt(f),
+        }
+    }
+}
+
+impl TurnOrderSetting {
+    /// The next mode, wrapping around to the level's own.
+    pub fn next(self) -> Self {
+        Self(match self.0 {
+            None => Some(TurnOrderMode::Phases),
+            Some(TurnOrderMode::Phases) => Some(TurnOrderMode::Alternating),
+            Some(TurnOrderMode::Alternating) => Some(TurnOrderMode::Initiative),
+            Some(TurnOrderMode::Initiative) => None,
+        })
+    }
+
+    /// Mode of a run on a level that asks for `level_mode`.
+    pub fn mode(self, level_mode: TurnOrderMode) -> TurnOrderMode {
+        self.0.unwrap_or(level_mode)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Side {
+    Heroes,
+    Enemies,
+}
+
+impl Side {
+    /// Side of a unit, villagers and buildings are with the heroes.
+    pub fn of(is_enemy: bool) -> Self {
+        match is_enemy {
+            true => Side::Enemies,
+            false => Side::Heroes,
+        }
+    }
+}
+
+/// Units of one side acting together before the other side gets to act.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TurnSlot {
+    pub side: Side,
+    pub units: Vec<Entity>,
+}
+
+/// Plan who acts when this round, units are `(entity, speed)` in their list order.
+pub fn plan_round(
+    mode: TurnOrderMode,
+    heroes: &[(Entity, u32)],
+    enemies: &[(Entity, u32)],
+) -> Vec<TurnSlot> {
+    let tag = |side: Side| move |&(entity, speed): &(Entity, u32)| (side, entity, speed);
+    let heroes = heroes.iter().map(tag(Side::Heroes));
+    let enemies = enemies.iter().map(tag(Side::Enemies));
+
+    let order: Vec<(Side, Entity, u32)> = match mode {
+        TurnOrderMode::Phases => heroes.chain(enemies).collect(),
+        TurnOrderMode::Alternating => {
+            let (mut heroes, mut enemies) = (heroes.peekable(), enemies.peekable());
+            let mut order = Vec::new();
+            while heroes.peek().is_some() || enemies.peek().is_some() {
+                order.extend(heroes.next());
+                order.extend(enemies.next());
+            }
+            order
+        }
+        TurnOrderMode::Initiative => {
+            let mut order: Vec<_> = heroes.chain(enemies).collect();
+            // Stable, so heroes win ties
+            order.sort_by_key(|&(_, _, speed)| std::cmp::Reverse(speed));
+            order
+        }
+    };
+
+    let mut slots: Vec<TurnSlot> = Vec::new();
+    for (side, entity, _) in order {
+        match slots.last_mut() {
+            Some(slot) if slot.side == side => slot.units.push(entity),
+            _ => slots.push(TurnSlot {
+                side,
+                units: vec![entity],
+            }),
+        }
+    }
+    slots
+}
+
+/// Slots of the current round, the first one is acting.
+#[derive(Resource, Default, Debug)]
+pub struct TurnOrder {
+    pub mode: TurnOrderMode,
+    slots: Vec<TurnSlot>,
+    /// Manned towers haven't fired yet this round.
+    tower_volley: bool,
+}
+
+impl TurnOrder {
+    pub fn current(&self) -> Option<&TurnSlot> {
+        self.slots.first()
+    }
+
+    pub fn is_acting(&self, entity: Entity) -> bool {
+        self.current()
+            .is_some_and(|slot| slot.units.contains(&entity))
+    }
+
+    /// Heroes still get another slot after the current one this round.
+    pub fn heroes_act_again(&self) -> bool {
+        self.slots
+            .iter()
+            .skip(1)
+            .any(|slot| slot.side == Side::Heroes)
+    }
+
+    /// Start a new round with these slots, towers get to fire again.
+    pub fn start_round(&mut self, slots: Vec<TurnSlot>) {
+        self.slots = slots;
+        self.tower_volley = true;
+    }
+
+    /// Whether manned towers fire now, only the first time enemies act in a round.
+    pub fn take_tower_volley(&mut self) -> bool {
+        std::mem::take(&mut self.tower_volley)
+    }
+
+    pub fn advance(&mut self) {
+        if !self.slots.is_empty() {
+            self.slots.remove(0);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.tower_volley = false;
+    }
+}
+
+/// Heroes of the current slot are done, but heroes act again later this round.
+#[derive(Event, Debug)]
+pub struct EndHeroSlot;
+
+#[derive(Component)]
+pub struct TurnOrderBar;
+
+/// Start the next slot, planning a new round when the last one is over.
+fn begin_slot(
+    mut turn_order: ResMut<TurnOrder>,
+    mut q_heroes: Query<(Entity, &mut ActorTurnState), With<PlayerActor>>,
+    q_enemies: Query<(Entity, Option<&Speed>), With<EnemyActor>>,
+    q_speeds: Query<&Speed>,
+    player_unit_list: Res<PlayerActorList>,
+    village_map: Res<VillageMap>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    if turn_order.current().is_none() {
+        let speed = |speed: Option<&Speed>| speed.map_or(0, |speed| speed.0);
+        let heroes: Vec<(Entity, u32)> = player_unit_list
+            .0
+            .iter()
+            .filter(|&&hero| village_map.actors.locate(hero).is_some())
+            .map(|&hero| (hero, speed(q_speeds.get(hero).ok())))
+            .collect();
+        let mut enemies: Vec<(Entity, u32)> = q_enemies
+            .iter()
+            .filter(|(enemy, _)| village_map.actors.locate(*enemy).is_some())
+            .map(|(enemy, enemy_speed)| (enemy, speed(enemy_speed)))
+            .collect();
+        enemies.sort_by_key(|(enemy, _)| *enemy);
+
+        let mode = turn_order.mode;
+        turn_order.start_round(plan_round(mode, &heroes, &enemies));
+        // Without heroes on the field the player still ends the round
+        if !turn_order
+            .slots
+            .iter()
+            .any(|slot| slot.side == Side::Heroes)
+        {
+            turn_order.slots.insert(
+                0,
+                TurnSlot {
+                    side: Side::Heroes,
+                    units: Vec::new(),
+                },
+            );
+        }
+    }
+
+    if turn_order
+        .current()
+        .is_some_and(|slot| slot.side == Side::Enemies)
+    {
+        next_game_state.set(GameState::EnemyTurn);
+        return;
+    }
+
+    // Heroes waiting for a later slot can't act yet
+    for (hero, mut turn_state) in q_heroes.iter_mut() {
+        if turn_order.is_acting(hero) {
+            turn_state.reset();
+        } else if village_map.actors.locate(hero).is_some() {
+            turn_state.used_move = true;
+            turn_state.used_action = true;
+        }
+    }
+}
+
+/// Hand over to the enemies, ending the round when heroes don't act again.
+fn end_hero_slot(
+    mut end_slot_events: EventReader<EndHeroSlot>,
+    mut end_turn_events: EventReader<EndTurn>,
+    mut turn_order: ResMut<TurnOrder>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut clear_undo_event: EventWriter<ClearUndoEvent>,
+) {
+    if end_slot_events.read().count() > 0 {
+        turn_order.advance();
+        clear_undo_event.send(ClearUndoEvent);
+        next_game_state.set(GameState::EnemyTurn);
+    } else if end_turn_events.read().count() > 0 {
+        turn_order.advance();
+    }
+}
+
+fn end_enemy_slot(mut turn_order: ResMut<TurnOrder>) {
+    if turn_order
+        .current()
+        .is_some_and(|slot| slot.side == Side::Enemies)
+    {
+        turn_order.advance();
+    }
+}
+
+fn turn_order_bar_layout(mut commands: Commands) {
+    commands
+        .ui_builder(UiRoot)
+        .row(|_| {})
+        .insert((TurnOrderBar, StateScoped(Screen::Playing)))
+        .style()
+        .z_index(UNIT_LIST_ZINDEX)
+        .position_type(PositionType::Absolute)
+        .top(Val::Px(70.))
+        .width(Val::Percent(100.))
+        .justify_content(JustifyContent::Center)
+        .column_gap(Val::Px(4.));
+}
+
+fn set_turn_order_bar_display(
+    game_state: Res<State<GameState>>,
+    mut q_bars: Query<&mut Style, With<TurnOrderBar>>,
+) {
+    let display = match game_state.get() {
+        GameState::BattleTurn | GameState::EnemyTurn => Display::Flex,
+        _ => Display::None,
+    };
+
+    for mut style in q_bars.iter_mut() {
+        if style.display != display {
+            style.display = display;
+        }
+    }
+}
+
+/// Show the units still to act this round, acting ones first.
+fn update_turn_order_bar(
+    mut commands: Commands,
+    turn_order: Res<TurnOrder>,
+    q_names: Query<&ActorName>,
+    q_bars: Query<Entity, With<TurnOrderBar>>,
+) {
+    for bar in q_bars.iter() {
+        commands.entity(bar).despawn_descendants();
+
+        let mut ui = commands.ui_builder(bar);
+        for (i, slot) in turn_order.slots.iter().enumerate() {
+            let color = match slot.side {
+                Side::Heroes => css::TEAL,
+                Side::Enemies => css::DARK_RED,
+            };
+            let border = match i {
+                0 => Color::WHITE,
+                _ => Color::NONE,
+            };
+
+            for name in slot.units.iter().filter_map(|unit| q_names.get(*unit).ok()) {
+                ui.container(NodeBundle::default(), |ui| {
+                    ui.label(LabelConfig::from(name.0.clone()))
+                        .style()
+                        .font_size(TEXT_SIZE);
+                })
+                .style()
+                .padding(UiRect::all(Val::Px(4.)))
+                .border(UiRect::all(Val::Px(2.)))
+                .border_color(border)
+                .border_radius(BorderRadius::all(Val::Px(4.)))
+                .background_color(color.into());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn units(ids: &[(u32, u32)]) -> Vec<(Entity, u32)> {
+        ids.iter()
+            .map(|&(id, speed)| (Entity::from_raw(id), speed))
+            .collect()
+    }
+
+    fn sides(slots: &[TurnSlot]) -> Vec<(Side, usize)> {
+        slots
+            .iter()
+            .map(|slot| (slot.side, slot.units.len()))
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_round_modes() {
+        let heroes = units(&[(1, 3), (2, 2)]);
+        let enemies = units(&[(10, 1), (11, 4), (12, 2)]);
+
+        let phases = plan_round(TurnOrderMode::Phases, &heroes, &enemies);
+        assert_eq!(sides(&phases), [(Side::Heroes, 2), (Side::Enemies, 3)]);
+
+        let alternating = plan_round(TurnOrderMode::Alternating, &heroes, &enemies);
+        assert_eq!(
+            sides(&alternating),
+            [
+                (Side::Heroes, 1),
+                (Side::Enemies, 1),
+                (Side::Heroes, 1),
+                (Side::Enemies, 2)
+            ]
+        );
+
+        // 11 (4), 1 (3), 2 and 12 (2) with the hero first, 10 (1)
+        let initiative = plan_round(TurnOrderMode::Initiative, &heroes, &enemies);
+        assert_eq!(
+            sides(&initiative),
+            [(Side::Enemies, 1), (Side::Heroes, 2), (Side::Enemies, 2)]
+        );
+        assert_eq!(initiative[0].units, [Entity::from_raw(11)]);
+    }
+
+    #[test]
+    fn test_setting_reaches_every_mode() {
+        let mut setting = TurnOrderSetting::default();
+        assert_eq!(
+            setting.mode(TurnOrderMode::Initiative),
+            TurnOrderMode::Initiative
+        );
+
+        let mut modes = Vec::new();
+        loop {
+            setting = setting.next();
+            let Some(mode) = setting.0 else {
+                break;
+            };
+            modes.push(setting.mode(TurnOrderMode::Phases));
+            assert_eq!(setting.mode(TurnOrderMode::Phases), mode);
+        }
+        assert_eq!(
+            modes,
+            [
+                TurnOrderMode::Phases,
+                TurnOrderMode::Alternating,
+                TurnOrderMode::Initiative
+            ]
+        );
+    }
+
+    #[test]
+    fn test_turn_order_advances() {
+        let heroes = units(&[(1, 1), (2, 1)]);
+        let enemies = units(&[(10, 1)]);
+        let mut turn_order = TurnOrder {
+            mode: TurnOrderMode::Alternating,
+            ..default()
+        };
+        turn_order.start_round(plan_round(TurnOrderMode::Alternating, &heroes, &enemies));
+
+        assert!(turn_order.is_acting(Entity::from_raw(1)));
+        assert!(turn_order.heroes_act_again());
+        turn_order.advance();
+        assert!(turn_order.is_acting(Entity::from_raw(10)));
+        turn_order.advance();
+        assert!(!turn_order.heroes_act_again());
+        turn_order.advance();
+        assert!(turn_order.current().is_none());
+    }
+
+    #[test]
+    fn test_towers_fire_once_per_round() {
+        let heroes = units(&[(1, 1), (2, 1), (3, 1)]);
+        let enemies = units(&[(10, 1), (11, 1), (12, 1)]);
+        let mut turn_order = TurnOrder {
+            mode: TurnOrderMode::Alternating,
+            ..default()
+        };
+
+        for _ in 0..2 {
+            turn_order.start_round(plan_round(TurnOrderMode::Alternating, &heroes, &enemies));
+            let mut enemy_slots = 0;
+            let mut volleys = 0;
+            while let Some(slot) = turn_order.current() {
+                if slot.side == Side::Enemies {
+                    enemy_slots += 1;
+                    volleys += turn_order.take_tower_volley() as u32;
+                }
+                turn_order.advance();
+            }
+            assert_eq!(enemy_slots, 3);
+            assert_eq!(volleys, 1);
+        }
+    }
+}
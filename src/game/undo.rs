@@ -0,0 +1,399 @@
+//! Undo and redo of the player's turn, kept until something irreversible happens.
+
+use bevy::color::palettes::css;
+use bevy::prelude::*;
+use sickle_ui::prelude::*;
+
+use crate::path_finding::tiles::Tile;
+use crate::screen::playing::GameState;
+use crate::screen::Screen;
+use crate::ui::palette::LABEL_SIZE;
+use crate::ui::prelude::InteractionPalette;
+
+use super::actors::{ActorTurnState, ClearUndoEvent, PlayerActor};
+use super::constants::UNIT_LIST_ZINDEX;
+use super::construction::{BuildingPlacement, StructureType};
+use super::cycle::EndTurn;
+use super::inventory::Inventory;
+use super::selection::SelectedActor;
+use super::tile_set::tile_coord_translation;
+use super::transfer::ItemMove;
+
+pub struct UndoPlugin;
+
+impl Plugin for UndoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UndoStack>()
+            .add_event::<UndoEvent>()
+            .add_systems(
+                OnEnter(Screen::Playing),
+                (undo_bar_layout, |mut undo_stack: ResMut<UndoStack>| {
+                    undo_stack.clear();
+                }),
+            )
+            .add_systems(OnEnter(GameState::Merchant), clear_on_modal)
+            .add_systems(OnEnter(GameState::Blacksmith), clear_on_modal)
+            .add_systems(OnEnter(GameState::Tavern), clear_on_modal)
+            .add_systems(
+                Update,
+                (
+                    clear_undo_stack,
+                    set_undo_bar_display,
+                    update_undo_buttons.run_if(resource_changed::<UndoStack>),
+                    (undo_keys, undo_btn_interaction, apply_undo_events)
+                        .chain()
+                        .run_if(
+                            in_state(GameState::BuildingTurn)
+                                .or_else(in_state(GameState::BattleTurn)),
+                        ),
+                )
+                    .run_if(in_state(Screen::Playing)),
+            );
+    }
+}
+
+/// A step of the player's turn that can be taken back.
+#[derive(Debug, Clone)]
+pub enum TurnCommand {
+    Move {
+        hero: Entity,
+        from: Tile,
+        to: Tile,
+    },
+    /// An item changing hands, only the slots involved are recorded.
+    Transfer {
+        giver: Entity,
+        receiver: Entity,
+        item_move: Box<ItemMove>,
+        /// Giving items takes the giver's action in battle.
+        used_action: bool,
+    },
+    PlaceBuilding {
+        site: Entity,
+        structure_type: StructureType,
+        tile: Tile,
+    },
+}
+
+/// Commands done this turn and the ones undone since, newest last.
+#[derive(Resource, Default, Debug)]
+pub struct UndoStack {
+    done: Vec<TurnCommand>,
+    undone: Vec<TurnCommand>,
+}
+
+impl UndoStack {
+    /// Record a new command, anything undone before can't be redone anymore.
+    pub fn push(&mut self, command: TurnCommand) {
+        self.done.push(command);
+        self.undone.clear();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    /// Take the last command to undo, hand it back with [`Self::undone`] once reverted.
+    pub fn pop_undo(&mut self) -> Option<TurnCommand> {
+        self.done.pop()
+    }
+
+    /// Take the last undone command to redo, hand it back with [`Self::redone`] once applied.
+    pub fn pop_redo(&mut self) -> Option<TurnCommand> {
+        self.undone.pop()
+    }
+
+    pub fn undone(&mut self, command: TurnCommand) {
+        self.undone.push(command);
+    }
+
+    pub fn redone(&mut self, command: TurnCommand) {
+        self.done.push(command);
+    }
+
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+}
+
+#[derive(Event, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum UndoEvent {
+    Undo,
+    Redo,
+}
+
+#[derive(Component)]
+pub struct UndoBar;
+
+#[derive(Component)]
+pub struct UndoButton(pub UndoEvent);
+
+/// Information was revealed or the turn is over, nothing done so far can be taken back.
+fn clear_undo_stack(
+    mut clear_undo_events: EventReader<ClearUndoEvent>,
+    mut end_turn_events: EventReader<EndTurn>,
+    mut undo_stack: ResMut<UndoStack>,
+) {
+    let cleared = clear_undo_events.read().count() + end_turn_events.read().count();
+    if cleared > 0 && (undo_stack.can_undo() || undo_stack.can_redo()) {
+        undo_stack.clear();
+    }
+}
+
+/// Modals trade and change items behind the history's back.
+fn clear_on_modal(mut clear_undo_event: EventWriter<ClearUndoEvent>) {
+    clear_undo_event.send(ClearUndoEvent);
+}
+
+fn undo_bar_layout(mut commands: Commands) {
+    commands
+        .ui_builder(UiRoot)
+        .row(|ui| {
+            for (event, text) in [(UndoEvent::Undo, "Undo"), (UndoEvent::Redo, "Redo")] {
+                ui.container(ButtonBundle::default(), |ui| {
+                    ui.label(LabelConfig::from(text))
+                        .style()
+                        .font_size(LABEL_SIZE);
+                })
+                .insert((
+                    UndoButton(event),
+                    InteractionPalette {
+                        none: css::TEAL.into(),
+                        hovered: css::DARK_CYAN.into(),
+                        pressed: css::DARK_SLATE_GRAY.into(),
+                    },
+                ))
+                .style()
+                .padding(UiRect::all(Val::Px(6.)))
+                .border_radius(BorderRadius::all(Val::Px(4.)));
+            }
+        })
+        .insert((UndoBar, StateScoped(Screen::Playing)))
+        .style()
+        .z_index(UNIT_LIST_ZINDEX)
+        .position_type(PositionType::Absolute)
+        .right(Val::Px(20.))
+        .bottom(Val::Px(70.))
+        .column_gap(Val::Px(4.));
+}
+
+fn set_undo_bar_display(
+    game_state: Res<State<GameState>>,
+    mut q_bars: Query<&mut Style, With<UndoBar>>,
+) {
+    let display = match game_state.get() {
+        GameState::BuildingTurn | GameState::BattleTurn => Display::Flex,
+        _ => Display::None,
+    };
+
+    for mut style in q_bars.iter_mut() {
+        if style.display != display {
+            style.display = display;
+        }
+    }
+}
+
+/// Dim the buttons with nothing to undo or redo.
+fn update_undo_buttons(
+    undo_stack: Res<UndoStack>,
+    q_buttons: Query<(&UndoButton, &Children)>,
+    mut q_texts: Query<&mut Text>,
+) {
+    for (button, children) in q_buttons.iter() {
+        let available = match button.0 {
+            UndoEvent::Undo => undo_stack.can_undo(),
+            UndoEvent::Redo => undo_stack.can_redo(),
+        };
+        let color = match available {
+            true => Color::WHITE,
+            false => Color::WHITE.with_alpha(0.4),
+        };
+
+        let mut texts = q_texts.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].style.color = color;
+        }
+    }
+}
+
+/// Ctrl+Z to undo, Ctrl+Y or Ctrl+Shift+Z to redo.
+fn undo_keys(keyboard_input: Res<ButtonInput<KeyCode>>, mut undo_events: EventWriter<UndoEvent>) {
+    if !keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+
+    let shift = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if keyboard_input.just_pressed(KeyCode::KeyY)
+        || (shift && keyboard_input.just_pressed(KeyCode::KeyZ))
+    {
+        undo_events.send(UndoEvent::Redo);
+    } else if keyboard_input.just_pressed(KeyCode::KeyZ) {
+        undo_events.send(UndoEvent::Undo);
+    }
+}
+
+fn undo_btn_interaction(
+    q_interactions: Query<(&Interaction, &UndoButton), Changed<Interaction>>,
+    mut undo_events: EventWriter<UndoEvent>,
+) {
+    for (interaction, button) in q_interactions.iter() {
+        if *interaction == Interaction::Pressed {
+            undo_events.send(button.0);
+        }
+    }
+}
+
+fn apply_undo_events(
+    mut undo_events: EventReader<UndoEvent>,
+    mut undo_stack: ResMut<UndoStack>,
+    mut placement: BuildingPlacement,
+    mut q_heroes: Query<(&mut ActorTurnState, &mut Transform, &mut Inventory), With<PlayerActor>>,
+    mut selected_actor: ResMut<SelectedActor>,
+) {
+    for event in undo_events.read() {
+        let forward = *event == UndoEvent::Redo;
+        let command = match forward {
+            true => undo_stack.pop_redo(),
+            false => undo_stack.pop_undo(),
+        };
+        let Some(command) = command else {
+            continue;
+        };
+
+        match run_command(command, forward, &mut placement, &mut q_heroes) {
+            Some(command) if forward => undo_stack.redone(command),
+            Some(command) => undo_stack.undone(command),
+            // The turn went somewhere the history no longer fits
+            None => undo_stack.clear(),
+        }
+        // Refresh the movement range of the selected hero
+        selected_actor.set_changed();
+    }
+}
+
+/// Apply the command, or revert it when not going `forward`.
+/// Returns the command to keep in the history if it could be done.
+fn run_command(
+    command: TurnCommand,
+    forward: bool,
+    placement: &mut BuildingPlacement,
+    q_heroes: &mut Query<(&mut ActorTurnState, &mut Transform, &mut Inventory), With<PlayerActor>>,
+) -> Option<TurnCommand> {
+    match &command {
+        &TurnCommand::Move { hero, from, to } => {
+            let target = match forward {
+                true => to,
+                false => from,
+            };
+            if placement
+                .game
+                .map
+                .actors
+                .get(target)
+                .is_some_and(|entity| entity != hero)
+            {
+                return None;
+            }
+            let (mut turn_state, mut transform, _) = q_heroes.get_mut(hero).ok()?;
+
+            placement.game.map.actors.set(target, hero);
+            transform.translation =
+                tile_coord_translation(target.x() as f32, target.y() as f32, 2.);
+            turn_state.used_move = forward;
+        }
+        TurnCommand::Transfer {
+            giver,
+            receiver,
+            item_move,
+            used_action,
+        } => {
+            let [(mut giver_state, _, mut giver_inventory), (_, _, mut receiver_inventory)] =
+                q_heroes.get_many_mut([*giver, *receiver]).ok()?;
+            // The items may have been sold, used or upgraded since
+            let done = match forward {
+                true => item_move.apply(&mut giver_inventory, &mut receiver_inventory),
+                false => item_move.revert(&mut giver_inventory, &mut receiver_inventory),
+            };
+            if !done {
+                return None;
+            }
+            if *used_action {
+                giver_state.used_action = forward;
+            }
+        }
+        &TurnCommand::PlaceBuilding {
+            site,
+            structure_type,
+            tile,
+        } => {
+            if forward {
+                // Placed anew as a different entity
+                let site = placement.place(structure_type, tile)?;
+                return Some(TurnCommand::PlaceBuilding {
+                    site,
+                    structure_type,
+                    tile,
+                });
+            }
+            if !placement.remove(site, structure_type) {
+                return None;
+            }
+        }
+    }
+    Some(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(hero: u32, to: i32) -> TurnCommand {
+        TurnCommand::Move {
+            hero: Entity::from_raw(hero),
+            from: Tile(0, 0),
+            to: Tile(to, 0),
+        }
+    }
+
+    fn destination(command: &TurnCommand) -> Option<Tile> {
+        match command {
+            TurnCommand::Move { to, .. } => Some(*to),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_undo_redo_order() {
+        let mut stack = UndoStack::default();
+        stack.push(step(1, 1));
+        stack.push(step(2, 2));
+
+        let last = stack.pop_undo().unwrap();
+        assert_eq!(destination(&last), Some(Tile(2, 0)));
+        stack.undone(last);
+        assert!(stack.can_undo() && stack.can_redo());
+
+        let redo = stack.pop_redo().unwrap();
+        assert_eq!(destination(&redo), Some(Tile(2, 0)));
+        stack.redone(redo);
+        assert!(!stack.can_redo());
+    }
+
+    #[test]
+    fn test_new_command_drops_redo() {
+        let mut stack = UndoStack::default();
+        stack.push(step(1, 1));
+        let undone = stack.pop_undo().unwrap();
+        stack.undone(undone);
+
+        stack.push(step(1, 3));
+        assert!(!stack.can_redo());
+        assert_eq!(destination(&stack.pop_undo().unwrap()), Some(Tile(3, 0)));
+        assert!(!stack.can_undo());
+    }
+}
@@ -2,6 +2,7 @@ use std::ops::{Index, IndexMut};
 
 use bevy::prelude::*;
 use bevy_enoki::prelude::*;
+use serde::Deserialize;
 use strum::{EnumCount, IntoEnumIterator};
 use strum_macros::{AsRefStr, EnumCount, EnumIter};
 
@@ -10,7 +11,8 @@ use crate::ui::icon_set::IconSet;
 
 use super::assets::SfxKey;
 use super::audio::sfx::PlaySfx;
-use super::cycle::Season;
+use super::cycle::calendar::Calendar;
+use super::cycle::{DayClock, Season};
 
 pub(super) struct VfxPlugin;
 
@@ -56,9 +58,12 @@ fn fire_oneshot_vfx(
 fn update_environment_vfx(
     mut q_states: Query<&mut ParticleSpawnerState>,
     env_vfx_map: Res<EnvironmentVfxMap>,
-    season: Res<Season>,
+    calendar: Res<Calendar>,
+    clock: Res<DayClock>,
 ) {
-    let vfx = EnvironmentVfx::from_season(*season);
+    let Some(vfx) = calendar.vfx(clock.day) else {
+        return;
+    };
     if let Ok(mut state) = q_states.get_mut(env_vfx_map[vfx]) {
         state.active = true;
     }
@@ -197,8 +202,9 @@ impl IndexMut<EnvironmentVfx> for EnvironmentVfxMap {
     }
 }
 
-#[derive(EnumCount, EnumIter, AsRefStr, Clone, Copy)]
+#[derive(EnumCount, EnumIter, AsRefStr, Deserialize, Clone, Copy, Debug)]
 #[strum(prefix = "enoki/")]
+#[serde(rename_all = "snake_case")]
 pub enum EnvironmentVfx {
     Summer,
     Autumn,
@@ -212,12 +218,4 @@ impl EnvironmentVfx {
             _ => "leaf",
         }
     }
-
-    pub fn from_season(season: Season) -> Self {
-        match season {
-            Season::Summer => Self::Summer,
-            Season::Autumn => Self::Autumn,
-            Season::Winter => Self::Winter,
-        }
-    }
 }
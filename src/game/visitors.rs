@@ -0,0 +1,355 @@
+//! Visitors that may arrive at dawn and the deals they offer.
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::screen::Screen;
+
+use super::actors::enemy::{NightWave, WaveBudget};
+use super::actors::stats::Health;
+use super::actors::AvailableActorNames;
+use super::actors_list::PlayerActorList;
+use super::constants::{MAX_HEROES, VISITOR_CHANCE_PERCENT};
+use super::contracts::MercenaryTemplate;
+use super::cycle::{DayCycle, Season, TimeOfDay, Turn};
+use super::ledger::{Ledger, LedgerSource};
+use super::resources::{
+    ResourceBundle, ResourceKind, VillageJobs, VillagePopulation, VillageStock,
+};
+use super::rng::{GameRng, RngStream};
+
+pub struct VisitorsPlugin;
+
+impl Plugin for VisitorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DawnVisitor>()
+            .init_resource::<NightModifiers>()
+            .add_systems(
+                OnEnter(Screen::Playing),
+                |mut visitor: ResMut<DawnVisitor>, mut modifiers: ResMut<NightModifiers>| {
+                    visitor.0 = None;
+                    *modifiers = NightModifiers::default();
+                },
+            )
+            .add_systems(
+                OnEnter(TimeOfDay::Day),
+                roll_dawn_visitor.run_if(|turn: Res<Turn>| turn.0 != 0),
+            );
+    }
+}
+
+/// Changes to the coming night, used up when the enemies spawn.
+#[derive(Resource, Default, Debug, Clone, PartialEq)]
+pub struct NightModifiers {
+    /// Added to the hit points of every enemy.
+    pub enemy_health: i32,
+}
+
+/// Visitor waiting to be received during the building turn.
+#[derive(Resource, Default, Debug)]
+pub struct DawnVisitor(pub Option<&'static VisitorTemplate>);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VisitorEffect {
+    /// Turns added to tonight's night.
+    NightLength(i32),
+    /// Hit points added to tonight's enemies.
+    EnemyHealth(i32),
+    /// Health restored to every hero.
+    HealHeroes(u32),
+    /// Fully heals the hero missing the most health.
+    HealMostWounded,
+    /// Tells the player what attacks tonight.
+    RevealWave,
+    Villagers(i32),
+    /// Hires the mercenary with this title, under contract like at the tavern.
+    HireMercenary(&'static str),
+}
+
+#[derive(Debug)]
+pub struct VisitorChoice {
+    pub label: &'static str,
+    pub cost: ResourceBundle,
+    pub effects: &'static [VisitorEffect],
+}
+
+#[derive(Debug)]
+pub struct VisitorTemplate {
+    pub name: &'static str,
+    pub icon: &'static str,
+    pub greeting: &'static str,
+    /// Relative chance of this visitor showing up.
+    pub weight: u32,
+    /// Declining is always possible and isn't listed here.
+    pub choices: &'static [VisitorChoice],
+}
+
+pub const VISITORS: &[VisitorTemplate] = &[
+    VisitorTemplate {
+        name: "Necromancer",
+        icon: "icons/claw_mark.png",
+        greeting: "Give me some of your people and the dead will rot before they reach you.",
+        weight: 2,
+        choices: &[VisitorChoice {
+            label: "Hand over 10 villagers",
+            cost: ResourceBundle::EMPTY,
+            effects: &[
+                VisitorEffect::Villagers(-10),
+                VisitorEffect::EnemyHealth(-1),
+            ],
+        }],
+    },
+    VisitorTemplate {
+        name: "Warlock",
+        icon: "icons/hourglass.png",
+        greeting: "Night is but a spell, and spells can be bent.",
+        weight: 2,
+        choices: &[
+            VisitorChoice {
+                label: "Shorten the night",
+                cost: ResourceBundle::gold(60),
+                effects: &[VisitorEffect::NightLength(-1)],
+            },
+            VisitorChoice {
+                label: "Lengthen the night for his favour",
+                cost: ResourceBundle::EMPTY,
+                effects: &[
+                    VisitorEffect::NightLength(1),
+                    VisitorEffect::EnemyHealth(-1),
+                ],
+            },
+        ],
+    },
+    VisitorTemplate {
+        name: "Surgeon",
+        icon: "icons/bandage.png",
+        greeting: "Bring me your most wounded, I will stitch them up.",
+        weight: 3,
+        choices: &[VisitorChoice {
+            label: "Treat the most wounded hero",
+            cost: ResourceBundle::gold(30),
+            effects: &[VisitorEffect::HealMostWounded],
+        }],
+    },
+    VisitorTemplate {
+        name: "Priest",
+        icon: "icons/heart.png",
+        greeting: "A small donation and I will bless every one of your heroes.",
+        weight: 3,
+        choices: &[VisitorChoice {
+            label: "Donate",
+            cost: ResourceBundle::gold(20),
+            effects: &[VisitorEffect::HealHeroes(1)],
+        }],
+    },
+    VisitorTemplate {
+        name: "Soothsayer",
+        icon: "icons/leaf.png",
+        greeting: "Cross my palm with gold and I will tell you what comes tonight.",
+        weight: 3,
+        choices: &[VisitorChoice {
+            label: "Hear the prophecy",
+            cost: ResourceBundle::gold(15),
+            effects: &[VisitorEffect::RevealWave],
+        }],
+    },
+    VisitorTemplate {
+        name: "Refugees",
+        icon: "icons/population.png",
+        greeting: "Our village burned, please let us in.",
+        weight: 3,
+        choices: &[VisitorChoice {
+            label: "Take them in",
+            cost: ResourceBundle::EMPTY.with(ResourceKind::Food, 10),
+            effects: &[VisitorEffect::Villagers(15)],
+        }],
+    },
+    VisitorTemplate {
+        name: "Mercenaries",
+        icon: "icons/warrior.png",
+        greeting: "Our swords are for hire, if your coin is good.",
+        weight: 2,
+        choices: &[VisitorChoice {
+            label: "Hire a sellsword",
+            cost: ResourceBundle::gold(60),
+            effects: &[VisitorEffect::HireMercenary("Sellsword")],
+        }],
+    },
+];
+
+/// Pick a visitor by weight, if anyone shows up at all.
+pub fn roll_visitor(rng: &mut impl Rng) -> Option<&'static VisitorTemplate> {
+    if !rng.gen_ratio(VISITOR_CHANCE_PERCENT, 100) {
+        return None;
+    }
+    VISITORS.choose_weighted(rng, |visitor| visitor.weight).ok()
+}
+
+fn roll_dawn_visitor(mut visitor: ResMut<DawnVisitor>, mut rng: ResMut<GameRng>) {
+    visitor.0 = roll_visitor(rng.stream(RngStream::Visitors));
+}
+
+/// Everything a visitor's deal can touch.
+#[derive(SystemParam)]
+pub struct VisitorDeal<'w, 's> {
+    commands: Commands<'w, 's>,
+    stock: ResMut<'w, VillageStock>,
+    population: ResMut<'w, VillagePopulation>,
+    jobs: ResMut<'w, VillageJobs>,
+    ledger: ResMut<'w, Ledger>,
+    day_cycle: ResMut<'w, DayCycle>,
+    night_modifiers: ResMut<'w, NightModifiers>,
+    season: Res<'w, Season>,
+    night_wave: ResMut<'w, NightWave>,
+    wave_budget: WaveBudget<'w>,
+    rng: ResMut<'w, GameRng>,
+    player_unit_list: ResMut<'w, PlayerActorList>,
+    names: ResMut<'w, AvailableActorNames>,
+    q_health: Query<'w, 's, &'static mut Health>,
+}
+
+impl VisitorDeal<'_, '_> {
+    pub fn can_accept(&self, choice: &VisitorChoice) -> bool {
+        self.stock.can_afford(&choice.cost)
+            && choice.effects.iter().all(|effect| match effect {
+                VisitorEffect::Villagers(count) if *count < 0 => {
+                    self.population.0 >= count.unsigned_abs()
+                }
+                VisitorEffect::HireMercenary(title) => {
+                    self.player_unit_list.0.len() < MAX_HEROES
+                        && MercenaryTemplate::find(title).is_some()
+                }
+                _ => true,
+            })
+    }
+
+    /// Pay for the choice and apply its effects, returns what happened.
+    pub fn accept(&mut self, choice: &VisitorChoice) -> Vec<String> {
+        if !self.stock.spend(&choice.cost) {
+            return Vec::new();
+        }
+        self.ledger.gold(
+            LedgerSource::Visitor,
+            -(choice.cost.get(ResourceKind::Gold) as i32),
+        );
+
+        choice
+            .effects
+            .iter()
+            .map(|effect| self.apply(*effect))
+            .collect()
+    }
+
+    fn apply(&mut self, effect: VisitorEffect) -> String {
+        match effect {
+            VisitorEffect::NightLength(turns) => {
+                self.day_cycle.night = self.day_cycle.night.saturating_add_signed(turns).max(1);
+                format!("Tonight lasts {} turns.", self.day_cycle.night)
+            }
+            VisitorEffect::EnemyHealth(amount) => {
+                self.night_modifiers.enemy_health += amount;
+                format!("Enemies have {amount:+} health tonight.")
+            }
+            VisitorEffect::HealHeroes(amount) => {
+                let mut iter = self.q_health.iter_many_mut(&self.player_unit_list.0);
+                while let Some(mut health) = iter.fetch_next() {
+                    // Fallen heroes stay down
+                    if health.value > 0 {
+                        health.value = (health.value + amount).min(health.max);
+                    }
+                }
+                format!("Every hero regains {amount} health.")
+            }
+            VisitorEffect::HealMostWounded => {
+                let wounded = self
+                    .player_unit_list
+                    .0
+                    .iter()
+                    .filter_map(|entity| Some((*entity, self.q_health.get(*entity).ok()?)))
+                    .max_by_key(|(_, health)| health.max - health.value)
+                    .map(|(entity, _)| entity);
+                if let Some(mut health) = wounded.and_then(|e| self.q_health.get_mut(e).ok()) {
+                    health.value = health.max;
+                }
+                "The most wounded hero is fully healed.".to_string()
+            }
+            VisitorEffect::RevealWave => {
+                let mut wave: Vec<(&str, u32)> = Vec::new();
+                let enemies = self.night_wave.get_or_roll(
+                    self.rng.stream(RngStream::Enemies),
+                    *self.season,
+                    &self.wave_budget,
+                );
+                for enemy in enemies {
+                    match wave.iter_mut().find(|(name, _)| *name == enemy.name) {
+                        Some((_, count)) => *count += 1,
+                        None => wave.push((enemy.name, 1)),
+                    }
+                }
+                let wave: Vec<_> = wave
+                    .iter()
+                    .map(|(name, count)| format!("{count} {name}"))
+                    .collect();
+                format!("Tonight: {}.", wave.join(", "))
+            }
+            VisitorEffect::Villagers(count) => {
+                let before = self.population.0;
+                self.population.0 = before.saturating_add_signed(count);
+                self.jobs.fit_to(self.population.0);
+                self.ledger.population(
+                    LedgerSource::Visitor,
+                    self.population.0 as i32 - before as i32,
+                );
+                match count < 0 {
+                    true => format!("{} villagers leave.", count.unsigned_abs()),
+                    false => format!("{count} villagers join the village."),
+                }
+            }
+            VisitorEffect::HireMercenary(title) => {
+                let Some(mercenary) = MercenaryTemplate::find(title) else {
+                    return format!("The {title} has moved on.");
+                };
+                let (_, name) = mercenary.hire(
+                    &mut self.commands,
+                    &mut self.names,
+                    &mut self.player_unit_list,
+                );
+                format!("{name} the {} joins your heroes.", mercenary.title)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn test_visitors_are_valid() {
+        for visitor in VISITORS {
+            assert!(visitor.weight > 0, "{} never shows up", visitor.name);
+            assert!(!visitor.choices.is_empty(), "{} has no deal", visitor.name);
+            for effect in visitor.choices.iter().flat_map(|choice| choice.effects) {
+                if let VisitorEffect::HireMercenary(title) = effect {
+                    assert!(
+                        MercenaryTemplate::find(title).is_some(),
+                        "no {title} to hire"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_roll_visitor_sometimes_skips() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let rolls: Vec<_> = (0..100).map(|_| roll_visitor(&mut rng)).collect();
+        assert!(rolls.iter().any(|visitor| visitor.is_none()));
+        assert!(rolls.iter().any(|visitor| visitor.is_some()));
+    }
+}
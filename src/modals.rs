@@ -1,5 +1,7 @@
+pub mod blacksmith;
 pub mod merchant;
 pub mod tavern;
+pub mod visitor;
 
 use bevy::prelude::*;
 use sickle_ui::prelude::*;
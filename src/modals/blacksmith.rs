@@ -0,0 +1,437 @@
+use bevy::color::palettes::css;
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use sickle_ui::prelude::*;
+
+use crate::game::actors::stats::ActorName;
+use crate::game::actors::ClearUndoEvent;
+use crate::game::actors_list::PlayerActorList;
+use crate::game::assets::SfxKey;
+use crate::game::audio::sfx::PlaySfx;
+use crate::game::components::Blacksmith;
+use crate::game::constants::{
+    BIG_TEXT_SIZE, ITEM_RECHARGE_COST, ITEM_UPGRADE_COST, TAVERN_FONT_SIZE,
+};
+use crate::game::inventory::{Inventory, MAX_ITEM_UPGRADES};
+use crate::game::ledger::{Ledger, LedgerSource};
+use crate::game::resources::{ResourceBundle, VillageStock};
+use crate::game::selection::{dispatch_object_pressed, ObjectPressedEvent};
+use crate::game::MODAL_Z_LAYER;
+use crate::screen::playing::GameState;
+use crate::ui::icon_set::IconSet;
+use crate::ui::palette::LABEL_SIZE;
+use crate::ui::prelude::*;
+
+const PLACEHOLDER_DESCRIPTION: &str = "Select an item to work on.";
+
+pub struct BlacksmithModalPlugin;
+
+impl Plugin for BlacksmithModalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BlacksmithSubject>()
+            .add_systems(OnEnter(GameState::Blacksmith), blacksmith_modal_layout)
+            .add_systems(
+                Update,
+                enter_blacksmith_modal.after(dispatch_object_pressed),
+            )
+            .add_systems(
+                Update,
+                (
+                    exit_blacksmith_btn_interaction,
+                    hero_btn_interaction,
+                    slot_btn_interaction,
+                    blacksmith_action_buttons,
+                    update_slot_list,
+                    update_item_label,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Blacksmith)),
+            );
+    }
+}
+
+/// Hero and item slot currently at the anvil.
+#[derive(Resource)]
+pub struct BlacksmithSubject {
+    pub actor: Entity,
+    pub slot: Option<usize>,
+}
+
+impl Default for BlacksmithSubject {
+    fn default() -> Self {
+        Self {
+            actor: Entity::PLACEHOLDER,
+            slot: None,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct ExitBlacksmithButton;
+
+#[derive(Component)]
+pub struct BlacksmithHeroButton(pub Entity);
+
+#[derive(Component)]
+pub struct BlacksmithSlotButton(pub usize);
+
+#[derive(Component)]
+pub struct BlacksmithSlotList;
+
+#[derive(Component)]
+pub struct BlacksmithItemLabel;
+
+#[derive(Component, Copy, Clone)]
+pub enum BlacksmithAction {
+    /// Restore the item to its full number of uses.
+    Recharge,
+    UpgradeDamage,
+    UpgradeRange,
+}
+
+impl BlacksmithAction {
+    fn label(&self) -> String {
+        match self {
+            BlacksmithAction::Recharge => format!("Recharge {ITEM_RECHARGE_COST} gold/use"),
+            BlacksmithAction::UpgradeDamage => format!("+damage {ITEM_UPGRADE_COST} gold"),
+            BlacksmithAction::UpgradeRange => format!("+range {ITEM_UPGRADE_COST} gold"),
+        }
+    }
+}
+
+fn blacksmith_modal_layout(
+    mut commands: Commands,
+    player_unit_list: Res<PlayerActorList>,
+    q_names: Query<&ActorName>,
+    mut subject: ResMut<BlacksmithSubject>,
+) {
+    *subject = BlacksmithSubject {
+        actor: player_unit_list
+            .0
+            .first()
+            .copied()
+            .unwrap_or(Entity::PLACEHOLDER),
+        slot: None,
+    };
+
+    commands
+        .ui_builder(UiRoot)
+        .column(|ui| {
+            ui.row(|ui| {
+                ui.style()
+                    .padding(UiRect::all(Val::Px(18.)))
+                    .border(UiRect::all(Val::Px(2.)))
+                    .border_color(Color::WHITE)
+                    .border_radius(BorderRadius::all(Val::Px(16.)))
+                    .background_color(Color::BLACK)
+                    .width(Val::Px(480.))
+                    .height(Val::Px(600.))
+                    .justify_content(JustifyContent::Center);
+                ui.column(|ui| {
+                    ui.style()
+                        .align_items(AlignItems::Center)
+                        .row_gap(Val::Px(20.));
+                    ui.label(LabelConfig::from("Blacksmith"))
+                        .style()
+                        .margin(UiRect::all(Val::Px(16.)))
+                        .font_size(BIG_TEXT_SIZE);
+                    ui.icon("icons/blacksmith.png")
+                        .style()
+                        .width(Val::Px(64.))
+                        .height(Val::Px(64.));
+
+                    ui.row(|ui| {
+                        ui.style().column_gap(Val::Px(4.));
+                        for entity in player_unit_list.0.iter() {
+                            let Ok(name) = q_names.get(*entity) else {
+                                continue;
+                            };
+
+                            ui.container(ButtonBundle::default(), |ui| {
+                                ui.label(LabelConfig::from(name.0.clone()))
+                                    .style()
+                                    .font_size(TAVERN_FONT_SIZE);
+                            })
+                            .insert((
+                                BlacksmithHeroButton(*entity),
+                                InteractionPalette {
+                                    none: css::BLACK.into(),
+                                    hovered: css::DARK_RED.into(),
+                                    pressed: css::INDIAN_RED.into(),
+                                },
+                            ))
+                            .style()
+                            .padding(UiRect::all(Val::Px(2.)))
+                            .border(UiRect::all(Val::Px(2.)))
+                            .border_color(Color::WHITE);
+                        }
+                    });
+
+                    ui.row(|ui| {
+                        ui.insert(BlacksmithSlotList)
+                            .style()
+                            .column_gap(Val::Px(8.));
+                    });
+
+                    ui.label(LabelConfig::from(PLACEHOLDER_DESCRIPTION))
+                        .insert(BlacksmithItemLabel)
+                        .style()
+                        .font_size(TAVERN_FONT_SIZE);
+
+                    ui.column(|ui| {
+                        ui.style().row_gap(Val::Px(10.));
+                        for action in [
+                            BlacksmithAction::Recharge,
+                            BlacksmithAction::UpgradeDamage,
+                            BlacksmithAction::UpgradeRange,
+                        ] {
+                            ui.container(ButtonBundle::default(), |ui| {
+                                ui.label(LabelConfig::from(action.label()))
+                                    .style()
+                                    .font_size(TAVERN_FONT_SIZE);
+                            })
+                            .insert((
+                                action,
+                                InteractionPalette {
+                                    none: css::BLACK.into(),
+                                    hovered: css::DARK_RED.into(),
+                                    pressed: css::INDIAN_RED.into(),
+                                },
+                            ))
+                            .style()
+                            .padding(UiRect::all(Val::Px(2.)))
+                            .border(UiRect::all(Val::Px(2.)))
+                            .border_color(Color::WHITE);
+                        }
+                    });
+                });
+                // Close button
+                ui.container(ButtonBundle::default(), |ui| {
+                    ui.label(LabelConfig::from("x"))
+                        .style()
+                        .font_size(LABEL_SIZE);
+                })
+                .insert((
+                    InteractionPalette {
+                        none: Color::BLACK.with_alpha(0.0),
+                        hovered: Color::BLACK.with_alpha(0.0),
+                        pressed: Color::BLACK.with_alpha(0.0),
+                    },
+                    ExitBlacksmithButton,
+                ))
+                .style()
+                .position_type(PositionType::Absolute)
+                .top(Val::Px(16.))
+                .right(Val::Px(16.));
+            });
+        })
+        .insert(StateScoped(GameState::Blacksmith))
+        .style()
+        .focus_policy(FocusPolicy::Block)
+        .z_index(ZIndex::Global(MODAL_Z_LAYER))
+        .width(Val::Percent(100.))
+        .height(Val::Percent(100.))
+        .background_color(Color::srgba(0.25, 0.25, 0.25, 0.75))
+        .justify_content(JustifyContent::Center)
+        .align_items(AlignItems::Center);
+}
+
+fn enter_blacksmith_modal(
+    mut events: EventReader<ObjectPressedEvent>,
+    q_blacksmiths: Query<(), With<Blacksmith>>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(ObjectPressedEvent(entity)) = events.read().last().copied() else {
+        return;
+    };
+
+    if q_blacksmiths.contains(entity) && *state.get() == GameState::BuildingTurn {
+        next_state.set(GameState::Blacksmith);
+    }
+}
+
+fn exit_blacksmith_btn_interaction(
+    q_interactions: Query<&Interaction, (Changed<Interaction>, With<ExitBlacksmithButton>)>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    for interaction in q_interactions.iter() {
+        if let Interaction::Pressed = interaction {
+            next_game_state.set(GameState::BuildingTurn);
+        }
+    }
+}
+
+fn hero_btn_interaction(
+    q_interactions: Query<(&Interaction, &BlacksmithHeroButton), Changed<Interaction>>,
+    mut subject: ResMut<BlacksmithSubject>,
+) {
+    for (_, button) in q_interactions
+        .iter()
+        .filter(|(&i, _)| i == Interaction::Pressed)
+    {
+        subject.actor = button.0;
+        subject.slot = None;
+    }
+}
+
+fn slot_btn_interaction(
+    q_interactions: Query<(&Interaction, &BlacksmithSlotButton), Changed<Interaction>>,
+    mut subject: ResMut<BlacksmithSubject>,
+) {
+    for (_, button) in q_interactions
+        .iter()
+        .filter(|(&i, _)| i == Interaction::Pressed)
+    {
+        subject.slot = Some(button.0);
+    }
+}
+
+fn blacksmith_action_buttons(
+    mut commands: Commands,
+    q_interactions: Query<(&Interaction, &BlacksmithAction), Changed<Interaction>>,
+    subject: Res<BlacksmithSubject>,
+    mut q_inventories: Query<&mut Inventory>,
+    mut stock: ResMut<VillageStock>,
+    mut ledger: ResMut<Ledger>,
+    mut clear_undo_event: EventWriter<ClearUndoEvent>,
+) {
+    let Some(slot) = subject.slot else {
+        return;
+    };
+    let Ok(mut inventory) = q_inventories.get_mut(subject.actor) else {
+        return;
+    };
+
+    for (_, action) in q_interactions
+        .iter()
+        .filter(|(&i, _)| i == Interaction::Pressed)
+    {
+        let Some(mut item) = inventory.get(slot) else {
+            continue;
+        };
+
+        let cost = match action {
+            BlacksmithAction::Recharge => {
+                let missing = item.max_count().saturating_sub(item.item_count);
+                if missing == 0 {
+                    continue;
+                }
+                missing * ITEM_RECHARGE_COST
+            }
+            BlacksmithAction::UpgradeDamage | BlacksmithAction::UpgradeRange => {
+                if item.can_upgrade() == false {
+                    continue;
+                }
+                ITEM_UPGRADE_COST
+            }
+        };
+
+        if !stock.can_afford(&ResourceBundle::gold(cost)) {
+            continue;
+        }
+
+        match action {
+            BlacksmithAction::Recharge => item.item_count = item.max_count(),
+            BlacksmithAction::UpgradeDamage => item.upgrade_effect(),
+            BlacksmithAction::UpgradeRange => item.upgrade_range(),
+        }
+
+        stock.spend(&ResourceBundle::gold(cost));
+        ledger.gold(LedgerSource::Blacksmith, -(cost as i32));
+        inventory.set(slot, item);
+        clear_undo_event.send(ClearUndoEvent);
+        commands.trigger(PlaySfx::Key(SfxKey::CoinPurchase));
+    }
+}
+
+/// Rebuild the item slot buttons when the subject or its inventory changes.
+fn update_slot_list(
+    mut commands: Commands,
+    subject: Res<BlacksmithSubject>,
+    q_inventories: Query<Ref<Inventory>>,
+    q_slot_lists: Query<Entity, With<BlacksmithSlotList>>,
+    icon_set: Res<IconSet>,
+) {
+    let Ok(inventory) = q_inventories.get(subject.actor) else {
+        return;
+    };
+
+    if subject.is_changed() == false && inventory.is_changed() == false {
+        return;
+    }
+
+    for entity in q_slot_lists.iter() {
+        commands.entity(entity).despawn_descendants();
+        let mut ui = commands.ui_builder(entity);
+
+        for slot in 0..inventory.slot_count() {
+            let Some(item) = inventory.get(slot) else {
+                continue;
+            };
+
+            let border_color = match subject.slot == Some(slot) {
+                true => css::YELLOW.into(),
+                false => Color::WHITE,
+            };
+
+            ui.container(
+                ButtonBundle {
+                    image: UiImage::new(icon_set.get(item.icon)),
+                    ..default()
+                },
+                |_| {},
+            )
+            .insert((
+                BlacksmithSlotButton(slot),
+                InteractionPalette {
+                    none: Color::BLACK,
+                    hovered: Color::BLACK.lighter(0.4),
+                    pressed: Color::BLACK.lighter(0.2),
+                },
+            ))
+            .style()
+            .border(UiRect::all(Val::Px(2.0)))
+            .border_color(border_color)
+            .border_radius(BorderRadius::all(Val::Px(8.0)))
+            .width(Val::Px(48.))
+            .height(Val::Px(48.));
+        }
+    }
+}
+
+fn update_item_label(
+    subject: Res<BlacksmithSubject>,
+    q_inventories: Query<Ref<Inventory>>,
+    mut q_labels: Query<&mut Text, With<BlacksmithItemLabel>>,
+) {
+    let Ok(inventory) = q_inventories.get(subject.actor) else {
+        return;
+    };
+
+    if subject.is_changed() == false && inventory.is_changed() == false {
+        return;
+    }
+
+    let description = match subject.slot.and_then(|slot| inventory.get(slot)) {
+        Some(item) => {
+            let effect = match item.health_effect < 0 {
+                true => "Damage",
+                false => "Heal",
+            };
+            format!(
+                "{effect} {}, range {}, uses {}/{}, upgrades {}/{MAX_ITEM_UPGRADES}",
+                item.health_effect.unsigned_abs(),
+                item.range,
+                item.item_count,
+                item.max_count(),
+                item.upgrades,
+            )
+        }
+        None => PLACEHOLDER_DESCRIPTION.to_string(),
+    };
+
+    for mut text in q_labels.iter_mut() {
+        text.sections[0].value.clone_from(&description);
+    }
+}
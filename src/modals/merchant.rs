@@ -1,21 +1,38 @@
 use bevy::color::palettes::css;
 use bevy::prelude::*;
 use bevy::ui::FocusPolicy;
+use rand::seq::SliceRandom;
+use rand::Rng;
 use sickle_ui::prelude::*;
 
-use crate::game::actors_list::SellItemButton;
+use crate::game::actors::stats::ActorName;
+use crate::game::actors::ClearUndoEvent;
+use crate::game::actors_list::{PlayerActorList, SellItemButton};
 use crate::game::assets::SfxKey;
 use crate::game::audio::sfx::PlaySfx;
-use crate::game::constants::TEXT_SIZE;
+use crate::game::components::Blacksmith;
+use crate::game::constants::{
+    MERCHANT_PRICE_FLUCTUATION_PERCENT, MERCHANT_REROLL_COST, MERCHANT_STAY_DAYS,
+    MERCHANT_VISIT_INTERVAL, TEXT_SIZE, VILLAGE_STASH_SIZE,
+};
+use crate::game::cycle::calendar::{Calendar, SpecialDayKind};
+use crate::game::cycle::{DayClock, Season, TimeOfDay, Turn};
+use crate::game::daily::{ChallengeRule, DailyChallenge};
+use crate::game::inventory::available_items;
 use crate::game::inventory::Inventory;
 use crate::game::inventory::Item;
-use crate::game::inventory::ITEM_TEMPLATES;
-use crate::game::resources::VillageGold;
+use crate::game::inventory::VillageStash;
+use crate::game::inventory::BASE_ITEM_TIER;
+use crate::game::ledger::Ledger;
+use crate::game::ledger::LedgerSource;
+use crate::game::resources::{ResourceBundle, VillageStock};
+use crate::game::rng::{GameRng, RngStream};
 use crate::game::selection::SelectedActor;
 use crate::game::MODAL_Z_LAYER;
 use crate::screen::playing::hide_all_with;
 use crate::screen::playing::show_all_with;
 use crate::screen::playing::GameState;
+use crate::screen::Screen;
 use crate::ui::icon_set::IconSet;
 use crate::ui::palette::HEADER_SIZE;
 use crate::ui::prelude::InteractionPalette;
@@ -27,6 +44,13 @@ pub struct MerchantModalPlugin;
 impl Plugin for MerchantModalPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<MerchantItems>()
+            .init_resource::<MerchantVisit>()
+            .init_resource::<MerchantBuyer>()
+            .add_systems(OnEnter(Screen::Playing), merchant_arrives)
+            .add_systems(
+                OnEnter(TimeOfDay::Day),
+                update_merchant_visit.run_if(|turn: Res<Turn>| turn.0 != 0),
+            )
             .add_systems(
                 OnEnter(GameState::Merchant),
                 (merchant_modal_layout, show_all_with::<SellItemButton>),
@@ -38,12 +62,21 @@ impl Plugin for MerchantModalPlugin {
                     exit_mechant_btn_interaction,
                     sell_btn_interaction,
                     (
+                        reroll_btn_interaction,
                         item_btn_interaction,
-                        item_btn_highlight.run_if(resource_changed::<MerchantItems>),
+                        (item_btn_highlight, update_stock_display)
+                            .run_if(resource_changed::<MerchantItems>),
+                    )
+                        .chain(),
+                    (
+                        buyer_btn_interaction,
+                        buy_btn_interaction,
+                        stash_item_btn_interaction,
+                        update_buyer_list,
                     )
                         .chain(),
-                    buy_btn_interaction,
-                ),
+                )
+                    .run_if(in_state(GameState::Merchant)),
             );
     }
 }
@@ -69,23 +102,224 @@ pub struct SellButton;
 #[derive(Component)]
 pub struct ItemBorder(pub usize);
 
+#[derive(Component)]
+pub struct RerollButton;
+
+#[derive(Component)]
+pub struct RerollLabel;
+
+/// Tells the player what happened to their last purchase.
+#[derive(Component)]
+pub struct FeedbackLabel;
+
+/// Hero that will receive the next purchase.
+#[derive(Component)]
+pub struct BuyerButton(pub Entity);
+
+/// Index into the village stash, pressing it hands the item to the buyer.
+#[derive(Component)]
+pub struct StashItemButton(pub usize);
+
+#[derive(Component)]
+pub struct BuyerList;
+
+/// Hero receiving purchases, overflow goes to the village stash.
+#[derive(Resource)]
+pub struct MerchantBuyer(pub Entity);
+
+impl Default for MerchantBuyer {
+    fn default() -> Self {
+        Self(Entity::PLACEHOLDER)
+    }
+}
+
+/// Where a purchased item ended up.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Delivery {
+    Hero(usize),
+    Stash,
+}
+
+/// Give the item to the buyer, or keep it in the stash when the buyer's hands are full.
+pub fn deliver(
+    item: Item,
+    inventory: Option<&mut Inventory>,
+    stash: &mut VillageStash,
+) -> Option<Delivery> {
+    if let Some(inventory) = inventory {
+        if let Some(slot) = inventory.get_empty_slot() {
+            inventory.set(slot, item);
+            return Some(Delivery::Hero(slot));
+        }
+    }
+
+    stash.store(item).ok().map(|_| Delivery::Stash)
+}
+
+/// An item for sale at today's price.
+#[derive(Debug, Copy, Clone)]
+pub struct MerchantOffer {
+    pub item: &'static Item,
+    pub price: u32,
+}
+
+/// Stock of the current visit, bought items leave an empty slot.
 #[derive(Resource, Default, Debug)]
 pub struct MerchantItems {
-    pub items: [Option<&'static Item>; 3],
+    pub items: Vec<Option<MerchantOffer>>,
     pub selection: Option<usize>,
+    /// Rerolls paid for during the current visit.
+    pub rerolls: u32,
+}
+
+impl MerchantItems {
+    pub fn reroll_cost(&self) -> u32 {
+        MERCHANT_REROLL_COST * (self.rerolls + 1)
+    }
+}
+
+/// Days left before the merchant leaves, the merchant is away at zero.
+#[derive(Resource, Default, Debug)]
+pub struct MerchantVisit(pub u32);
+
+impl MerchantVisit {
+    pub fn is_present(&self) -> bool {
+        self.0 > 0
+    }
+}
+
+/// The merchant comes by at a fixed interval, and always after a full moon
+/// or the last night of a season.
+pub fn is_merchant_day(day: u32, calendar: &Calendar) -> bool {
+    let after_full_moon = day.checked_sub(1).is_some_and(|last_day| {
+        calendar
+            .special_day(last_day)
+            .is_some_and(|special| special.kind == SpecialDayKind::FullMoon)
+    });
+    day.is_multiple_of(MERCHANT_VISIT_INTERVAL) || after_full_moon || calendar.is_season_start(day)
+}
+
+/// Number of items on sale and the weight of each item tier, starting from tier 1.
+pub fn season_stock(season: Season) -> (usize, &'static [u32]) {
+    match season {
+        Season::Summer => (3, &[4, 1]),
+        Season::Autumn => (4, &[3, 2]),
+        Season::Winter => (5, &[2, 3]),
+    }
+}
+
+/// Fill the stock from the items up to `max_tier`, rarer tiers show up more later in the year.
+pub fn roll_stock(
+    rng: &mut impl Rng,
+    season: Season,
+    items: &[&'static Item],
+) -> Vec<Option<MerchantOffer>> {
+    let (size, weights) = season_stock(season);
+
+    (0..size)
+        .map(|_| {
+            let item = items
+                .choose_weighted(&mut *rng, |item| {
+                    weights
+                        .get(item.tier as usize - 1)
+                        .copied()
+                        .unwrap_or_default()
+                })
+                .ok()?;
+            let fluctuation = rng.gen_range(
+                -MERCHANT_PRICE_FLUCTUATION_PERCENT..=MERCHANT_PRICE_FLUCTUATION_PERCENT,
+            );
+            let price = (item.cost as i32 * (100 + fluctuation) / 100).max(1) as u32;
+            Some(MerchantOffer { item, price })
+        })
+        .collect()
+}
+
+/// A blacksmith in the village unlocks the next tier of items.
+fn max_item_tier(q_blacksmiths: &Query<(), With<Blacksmith>>) -> u32 {
+    match q_blacksmiths.is_empty() {
+        true => BASE_ITEM_TIER,
+        false => BASE_ITEM_TIER + 1,
+    }
+}
+
+/// Items the merchant can bring along.
+fn stock_items(
+    q_blacksmiths: &Query<(), With<Blacksmith>>,
+    challenge: &DailyChallenge,
+) -> Vec<&'static Item> {
+    available_items(max_item_tier(q_blacksmiths))
+        .filter(|item| !challenge.has(ChallengeRule::OnlyBows) || item.name == "bow")
+        .collect()
+}
+
+fn merchant_arrives(
+    mut visit: ResMut<MerchantVisit>,
+    mut merchant_items: ResMut<MerchantItems>,
+    season: Res<Season>,
+    q_blacksmiths: Query<(), With<Blacksmith>>,
+    challenge: Res<DailyChallenge>,
+    mut rng: ResMut<GameRng>,
+) {
+    if challenge.has(ChallengeRule::NoMerchant) {
+        visit.0 = 0;
+        *merchant_items = MerchantItems::default();
+        return;
+    }
+
+    visit.0 = MERCHANT_STAY_DAYS;
+    let items = stock_items(&q_blacksmiths, &challenge);
+    *merchant_items = MerchantItems {
+        items: roll_stock(rng.stream(RngStream::Merchant), *season, &items),
+        ..default()
+    };
+}
+
+fn update_merchant_visit(
+    mut visit: ResMut<MerchantVisit>,
+    mut merchant_items: ResMut<MerchantItems>,
+    clock: Res<DayClock>,
+    calendar: Res<Calendar>,
+    season: Res<Season>,
+    q_blacksmiths: Query<(), With<Blacksmith>>,
+    challenge: Res<DailyChallenge>,
+    rng: ResMut<GameRng>,
+) {
+    visit.0 = visit.0.saturating_sub(1);
+    if visit.is_present() {
+        return;
+    }
+
+    match is_merchant_day(clock.day, &calendar) {
+        true => merchant_arrives(visit, merchant_items, season, q_blacksmiths, challenge, rng),
+        // The unsold stock leaves with the merchant
+        false => *merchant_items = MerchantItems::default(),
+    }
 }
 
 fn merchant_modal_layout(
     mut commands: Commands,
     icon_set: Res<IconSet>,
-    mut merchant_items: ResMut<MerchantItems>,
+    merchant_items: Res<MerchantItems>,
+    player_unit_list: Res<PlayerActorList>,
+    selected_actor: Res<SelectedActor>,
+    q_inventories: Query<&Inventory>,
+    mut buyer: ResMut<MerchantBuyer>,
 ) {
-    if merchant_items.items.iter().any(|item| item.is_none()) {
-        for item in merchant_items.items.iter_mut() {
-            let index = rand::random::<usize>() % ITEM_TEMPLATES.len();
-            *item = Some(&ITEM_TEMPLATES[index]);
-        }
-    }
+    // Shop for the selected hero, otherwise the first one with room to carry something
+    let heroes = &player_unit_list.0;
+    buyer.0 = selected_actor
+        .entity
+        .filter(|entity| heroes.contains(entity))
+        .or_else(|| {
+            heroes.iter().copied().find(|entity| {
+                q_inventories
+                    .get(*entity)
+                    .is_ok_and(|inventory| inventory.get_empty_slot().is_some())
+            })
+        })
+        .or_else(|| heroes.first().copied())
+        .unwrap_or(Entity::PLACEHOLDER);
 
     commands
         .ui_builder(UiRoot)
@@ -98,6 +332,7 @@ fn merchant_modal_layout(
                     .background_color(Color::BLACK.with_alpha(0.8))
                     .width(Val::Vh(80.))
                     .height(Val::Vh(80.))
+                    .column_gap(Val::Px(24.))
                     .justify_content(JustifyContent::Center);
 
                 ui.column(|ui| {
@@ -117,10 +352,10 @@ fn merchant_modal_layout(
                             .justify_content(JustifyContent::Center)
                             .padding(UiRect::all(Val::Px(6.0)));
 
-                        for (i, item) in merchant_items.items.iter().enumerate() {
+                        for (i, offer) in merchant_items.items.iter().enumerate() {
                             let mut image = UiImage::default();
-                            if let Some(item) = item {
-                                image.texture = icon_set.get(item.name);
+                            if let Some(offer) = offer {
+                                image.texture = icon_set.get(offer.item.icon);
                             }
 
                             ui.container(ButtonBundle { image, ..default() }, |_| {})
@@ -174,6 +409,41 @@ fn merchant_modal_layout(
                     ))
                     .style()
                     .padding(UiRect::all(Val::Px(6.0)));
+
+                    ui.label(LabelConfig::from(""))
+                        .insert(FeedbackLabel)
+                        .style()
+                        .margin(UiRect::top(Val::Px(6.0)))
+                        .font_size(TEXT_SIZE);
+
+                    ui.container(ButtonBundle::default(), |ui| {
+                        ui.label(LabelConfig::from(format!(
+                            "Reroll stock for {} coin(s).",
+                            merchant_items.reroll_cost()
+                        )))
+                        .insert(RerollLabel)
+                        .style()
+                        .font_size(TEXT_SIZE);
+                    })
+                    .insert((
+                        InteractionPalette {
+                            none: css::TEAL.into(),
+                            hovered: css::DARK_CYAN.into(),
+                            pressed: css::DARK_SLATE_GRAY.into(),
+                        },
+                        RerollButton,
+                    ))
+                    .style()
+                    .margin(UiRect::top(Val::Px(6.0)))
+                    .padding(UiRect::all(Val::Px(6.0)));
+                });
+
+                // Heroes and the stash, filled in by update_buyer_list
+                ui.column(|ui| {
+                    ui.insert(BuyerList)
+                        .style()
+                        .justify_content(JustifyContent::Center)
+                        .row_gap(Val::Px(8.));
                 });
 
                 // Close button
@@ -201,6 +471,64 @@ fn merchant_modal_layout(
         .align_items(AlignItems::Center);
 }
 
+fn update_stock_display(
+    mut q_item_buttons: Query<(&ItemButton, &mut UiImage)>,
+    mut q_reroll_label: Query<&mut Text, With<RerollLabel>>,
+    merchant_items: Res<MerchantItems>,
+    icon_set: Res<IconSet>,
+) {
+    for (button, mut image) in q_item_buttons.iter_mut() {
+        image.texture = match merchant_items.items.get(button.0).copied().flatten() {
+            Some(offer) => icon_set.get(offer.item.icon),
+            None => Handle::default(),
+        };
+    }
+
+    for mut text in q_reroll_label.iter_mut() {
+        text.sections[0].value =
+            format!("Reroll stock for {} coin(s).", merchant_items.reroll_cost());
+    }
+}
+
+fn reroll_btn_interaction(
+    mut commands: Commands,
+    q_interactions: Query<&Interaction, (Changed<Interaction>, With<RerollButton>)>,
+    q_blacksmiths: Query<(), With<Blacksmith>>,
+    mut q_cost_label: Query<&mut Text, (With<CostLabel>, Without<DescriptionLabel>)>,
+    mut q_description_label: Query<&mut Text, (With<DescriptionLabel>, Without<CostLabel>)>,
+    mut merchant_items: ResMut<MerchantItems>,
+    mut stock: ResMut<VillageStock>,
+    mut ledger: ResMut<Ledger>,
+    season: Res<Season>,
+    challenge: Res<DailyChallenge>,
+    mut rng: ResMut<GameRng>,
+) {
+    for interaction in q_interactions.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        let cost = merchant_items.reroll_cost();
+        if !stock.spend(&ResourceBundle::gold(cost)) {
+            continue;
+        }
+
+        ledger.gold(LedgerSource::MerchantPurchase, -(cost as i32));
+        let items = stock_items(&q_blacksmiths, &challenge);
+        merchant_items.items = roll_stock(rng.stream(RngStream::Merchant), *season, &items);
+        merchant_items.selection = None;
+        merchant_items.rerolls += 1;
+
+        for mut text in q_cost_label.iter_mut() {
+            text.sections[0].value = "Select an item to buy.".to_string();
+        }
+        for mut text in q_description_label.iter_mut() {
+            text.sections[0].value = PLACEHOLDER_DESCRIPTION.to_string();
+        }
+        commands.trigger(PlaySfx::Key(SfxKey::CoinPurchase));
+    }
+}
+
 fn item_btn_highlight(
     mut q_interactions: Query<(&ItemButton, &mut BorderColor)>,
     merchant_items: Res<MerchantItems>,
@@ -231,10 +559,10 @@ fn item_btn_interaction(
         if let Interaction::Pressed = interaction {
             merchant_items.selection = Some(button.0);
 
-            let item = merchant_items.items[button.0];
-            if let Some(item) = item {
+            let offer = merchant_items.items[button.0];
+            if let Some(MerchantOffer { item, price }) = offer {
                 cost.sections[0] = TextSection::new(
-                    format!("Buy for {} coin(s).", item.cost),
+                    format!("Buy for {price} coin(s)."),
                     TextStyle {
                         font_size: TEXT_SIZE,
                         ..default()
@@ -252,40 +580,218 @@ fn item_btn_interaction(
     }
 }
 
+fn buyer_btn_interaction(
+    q_interactions: Query<(&Interaction, &BuyerButton), Changed<Interaction>>,
+    mut buyer: ResMut<MerchantBuyer>,
+) {
+    for (_, button) in q_interactions
+        .iter()
+        .filter(|(&i, _)| i == Interaction::Pressed)
+    {
+        buyer.0 = button.0;
+    }
+}
+
 fn buy_btn_interaction(
     mut commands: Commands,
-    selected_unit: Res<SelectedActor>,
-    mut iq: Query<&mut Inventory>,
+    buyer: Res<MerchantBuyer>,
+    mut q_inventories: Query<&mut Inventory>,
+    q_names: Query<&ActorName>,
     q_interactions: Query<&Interaction, (Changed<Interaction>, With<BuyButton>)>,
+    mut q_feedback_label: Query<&mut Text, With<FeedbackLabel>>,
     mut merchant_items: ResMut<MerchantItems>,
-    mut next_game_state: ResMut<NextState<GameState>>,
-    mut gold: ResMut<VillageGold>,
+    mut stash: ResMut<VillageStash>,
+    mut stock: ResMut<VillageStock>,
+    mut ledger: ResMut<Ledger>,
+    mut clear_undo_event: EventWriter<ClearUndoEvent>,
 ) {
-    let Some(entity) = selected_unit.entity else {
-        return;
-    };
-    let Ok(mut inventory) = iq.get_mut(entity) else {
+    for interaction in q_interactions.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        let Some(i) = merchant_items.selection else {
+            continue;
+        };
+        let Some(offer) = merchant_items.items.get(i).copied().flatten() else {
+            continue;
+        };
+        let name = q_names
+            .get(buyer.0)
+            .map_or("The village".to_string(), |name| name.0.clone());
+
+        let feedback = if !stock.can_afford(&ResourceBundle::gold(offer.price)) {
+            format!(
+                "Not enough gold, the {} costs {} coin(s).",
+                offer.item.name, offer.price
+            )
+        } else {
+            match deliver(
+                *offer.item,
+                q_inventories.get_mut(buyer.0).ok().as_deref_mut(),
+                &mut stash,
+            ) {
+                None => format!("{name} has no room and the village stash is full."),
+                Some(delivery) => {
+                    merchant_items.items[i] = None;
+                    merchant_items.selection = None;
+                    stock.spend(&ResourceBundle::gold(offer.price));
+                    ledger.gold(LedgerSource::MerchantPurchase, -(offer.price as i32));
+                    commands.trigger(PlaySfx::Key(SfxKey::CoinPurchase));
+                    clear_undo_event.send(ClearUndoEvent);
+
+                    match delivery {
+                        Delivery::Hero(_) => format!("{name} takes the {}.", offer.item.name),
+                        Delivery::Stash => format!(
+                            "{name} has no room, the {} goes to the village stash.",
+                            offer.item.name
+                        ),
+                    }
+                }
+            }
+        };
+
+        for mut text in q_feedback_label.iter_mut() {
+            text.sections[0].value = feedback.clone();
+        }
+    }
+}
+
+/// Hand an item from the village stash to the buyer.
+fn stash_item_btn_interaction(
+    q_interactions: Query<(&Interaction, &StashItemButton), Changed<Interaction>>,
+    mut q_feedback_label: Query<&mut Text, With<FeedbackLabel>>,
+    mut q_inventories: Query<&mut Inventory>,
+    buyer: Res<MerchantBuyer>,
+    mut stash: ResMut<VillageStash>,
+    mut clear_undo_event: EventWriter<ClearUndoEvent>,
+) {
+    let Ok(mut inventory) = q_inventories.get_mut(buyer.0) else {
         return;
     };
-    let Some(slot) = inventory.get_empty_slot() else {
+
+    for (_, button) in q_interactions
+        .iter()
+        .filter(|(&i, _)| i == Interaction::Pressed)
+    {
+        let Some(slot) = inventory.get_empty_slot() else {
+            for mut text in q_feedback_label.iter_mut() {
+                text.sections[0].value = "No room to take that from the stash.".to_string();
+            }
+            continue;
+        };
+
+        if let Some(item) = stash.take(button.0) {
+            inventory.set(slot, item);
+            clear_undo_event.send(ClearUndoEvent);
+        }
+    }
+}
+
+/// Rebuild the heroes' inventories and the stash when any of them changes.
+fn update_buyer_list(
+    mut commands: Commands,
+    q_buyer_lists: Query<Entity, With<BuyerList>>,
+    q_inventories: Query<Ref<Inventory>>,
+    q_names: Query<&ActorName>,
+    player_unit_list: Res<PlayerActorList>,
+    buyer: Res<MerchantBuyer>,
+    stash: Res<VillageStash>,
+    icon_set: Res<IconSet>,
+) {
+    let inventory_changed = q_inventories
+        .iter_many(&player_unit_list.0)
+        .any(|inventory| inventory.is_changed());
+    if !buyer.is_changed() && !stash.is_changed() && !inventory_changed {
         return;
+    }
+
+    let slot_style = |ui: &mut UiBuilder<Entity>| {
+        ui.style()
+            .border(UiRect::all(Val::Px(2.0)))
+            .border_color(Color::WHITE.with_alpha(0.3))
+            .border_radius(BorderRadius::all(Val::Px(6.0)))
+            .width(Val::Px(36.))
+            .height(Val::Px(36.));
     };
 
-    for interaction in q_interactions.iter() {
-        if let Interaction::Pressed = interaction {
-            if let Some(selected_item) = merchant_items
-                .selection
-                .and_then(|i| merchant_items.items[i].take())
-            {
-                if selected_item.cost <= gold.0 {
-                    gold.0 = gold.0.saturating_sub(selected_item.cost);
-                    inventory.set(slot, *selected_item);
-                    next_game_state.set(GameState::BuildingTurn);
-                }
-            }
+    for entity in q_buyer_lists.iter() {
+        commands.entity(entity).despawn_descendants();
+        let mut ui = commands.ui_builder(entity);
 
-            commands.trigger(PlaySfx::Key(SfxKey::CoinPurchase));
+        ui.label(LabelConfig::from("Buy for"))
+            .style()
+            .font_size(TEXT_SIZE);
+
+        for hero in player_unit_list.0.iter() {
+            let (Ok(name), Ok(inventory)) = (q_names.get(*hero), q_inventories.get(*hero)) else {
+                continue;
+            };
+
+            let border_color = match buyer.0 == *hero {
+                true => css::YELLOW.into(),
+                false => Color::WHITE,
+            };
+
+            ui.row(|ui| {
+                ui.style()
+                    .column_gap(Val::Px(4.))
+                    .align_items(AlignItems::Center);
+
+                ui.container(ButtonBundle::default(), |ui| {
+                    ui.label(LabelConfig::from(name.0.clone()))
+                        .style()
+                        .font_size(TEXT_SIZE);
+                })
+                .insert((
+                    BuyerButton(*hero),
+                    InteractionPalette {
+                        none: css::BLACK.into(),
+                        hovered: css::DARK_RED.into(),
+                        pressed: css::INDIAN_RED.into(),
+                    },
+                ))
+                .style()
+                .width(Val::Px(120.))
+                .padding(UiRect::all(Val::Px(2.)))
+                .border(UiRect::all(Val::Px(2.)))
+                .border_color(border_color);
+
+                for slot in 0..inventory.slot_count() {
+                    let mut image = UiImage::default();
+                    if let Some(item) = inventory.get(slot) {
+                        image.texture = icon_set.get(item.icon);
+                    }
+                    slot_style(&mut ui.container(ImageBundle { image, ..default() }, |_| {}));
+                }
+            });
         }
+
+        ui.label(LabelConfig::from(format!(
+            "Village stash ({}/{VILLAGE_STASH_SIZE})",
+            stash.0.len()
+        )))
+        .style()
+        .margin(UiRect::top(Val::Px(10.)))
+        .font_size(TEXT_SIZE);
+
+        ui.row(|ui| {
+            ui.style().column_gap(Val::Px(4.));
+
+            for (i, item) in stash.0.iter().enumerate() {
+                let image = UiImage::new(icon_set.get(item.icon));
+                let mut button = ui.container(ButtonBundle { image, ..default() }, |_| {});
+                button.insert((
+                    StashItemButton(i),
+                    InteractionPalette {
+                        none: Color::BLACK,
+                        hovered: Color::BLACK.lighter(0.4),
+                        pressed: Color::BLACK.lighter(0.2),
+                    },
+                ));
+                slot_style(&mut button);
+            }
+        });
     }
 }
 
@@ -294,7 +800,9 @@ fn sell_btn_interaction(
     selected: Res<SelectedActor>,
     q_interactions: Query<&Interaction, (Changed<Interaction>, With<SellItemButton>)>,
     mut iq: Query<&mut Inventory>,
-    mut gold: ResMut<VillageGold>,
+    mut stock: ResMut<VillageStock>,
+    mut ledger: ResMut<Ledger>,
+    mut clear_undo_event: EventWriter<ClearUndoEvent>,
 ) {
     let Some(entity) = selected.entity else {
         return;
@@ -310,7 +818,9 @@ fn sell_btn_interaction(
     for interaction in q_interactions.iter() {
         if let Interaction::Pressed = interaction {
             if let Some(item) = inventory.take(i) {
-                gold.0 += item.cost / 2;
+                stock.deposit(&ResourceBundle::gold(item.cost / 2));
+                ledger.gold(LedgerSource::Sell, (item.cost / 2) as i32);
+                clear_undo_event.send(ClearUndoEvent);
             }
 
             commands.trigger(PlaySfx::Key(SfxKey::CoinPurchase));
@@ -328,3 +838,86 @@ fn exit_mechant_btn_interaction(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn test_merchant_schedule() {
+        let calendar: Calendar = serde_json::from_str(
+            r#"{
+                "seasons": [
+                    {
+                        "season": "summer",
+                        "days": 10,
+                        "day_turns": 6,
+                        "night_turns": 4,
+                        "background": [0.0, 1.0, 0.0],
+                        "vfx": "summer"
+                    },
+                    {
+                        "season": "winter",
+                        "days": 10,
+                        "day_turns": 4,
+                        "night_turns": 6,
+                        "background": [1.0, 1.0, 1.0],
+                        "vfx": "winter"
+                    }
+                ],
+                "special_days": [{ "kind": "full_moon", "every": 5 }]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(is_merchant_day(MERCHANT_VISIT_INTERVAL, &calendar));
+        // Winter starts on day 10
+        assert!(is_merchant_day(10, &calendar));
+        // The first full moon is on the 5th day of summer, day 4
+        assert!(is_merchant_day(5, &calendar));
+        assert!(!is_merchant_day(1, &calendar));
+        assert!(!is_merchant_day(7, &calendar));
+    }
+
+    #[test]
+    fn test_deliver_overflows_to_stash() {
+        let item = crate::game::inventory::ITEM_TEMPLATES[0];
+        let mut inventory = Inventory::default();
+        let mut stash = VillageStash::default();
+
+        assert_eq!(
+            deliver(item, Some(&mut inventory), &mut stash),
+            Some(Delivery::Hero(1))
+        );
+        assert_eq!(
+            deliver(item, Some(&mut inventory), &mut stash),
+            Some(Delivery::Hero(2))
+        );
+        for _ in 0..VILLAGE_STASH_SIZE {
+            assert_eq!(
+                deliver(item, Some(&mut inventory), &mut stash),
+                Some(Delivery::Stash)
+            );
+        }
+        assert_eq!(deliver(item, Some(&mut inventory), &mut stash), None);
+        assert_eq!(stash.0.len(), VILLAGE_STASH_SIZE);
+    }
+
+    #[test]
+    fn test_roll_stock_respects_season_and_tier() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let items: Vec<_> = available_items(BASE_ITEM_TIER).collect();
+        let stock = roll_stock(&mut rng, Season::Winter, &items);
+        assert_eq!(stock.len(), season_stock(Season::Winter).0);
+
+        for offer in stock.iter().flatten() {
+            assert!(offer.item.tier <= BASE_ITEM_TIER);
+            let max_price =
+                offer.item.cost * (100 + MERCHANT_PRICE_FLUCTUATION_PERCENT as u32) / 100;
+            assert!(offer.price <= max_price);
+        }
+    }
+}
@@ -3,14 +3,19 @@ use bevy::prelude::*;
 use bevy::ui::FocusPolicy;
 use sickle_ui::prelude::*;
 
-use crate::game::actors::player::spawn_player_unit;
+use crate::game::actors::class::{ClassAbility, Experience};
+use crate::game::actors::player::PlayerSprite;
 use crate::game::actors::stats::{ActorName, Health, Movement};
 use crate::game::actors::AvailableActorNames;
 use crate::game::actors_list::PlayerActorList;
 use crate::game::components::Tavern;
-use crate::game::constants::{BIG_TEXT_SIZE, RECRUIT_COST, TAVERN_FONT_SIZE, UPGRADE_COST};
+use crate::game::constants::{
+    BIG_TEXT_SIZE, MAX_HEROES, MAX_LOYALTY, MAX_UPGRADED_STAT, TAVERN_FONT_SIZE,
+};
+use crate::game::contracts::{Contract, Desertions, MercenaryTemplate, TavernRoster};
 use crate::game::inventory::{Inventory, MaxInventorySize};
-use crate::game::resources::VillageGold;
+use crate::game::ledger::{Ledger, LedgerSource};
+use crate::game::resources::{ResourceBundle, VillageStock};
 use crate::game::selection::ObjectPressedEvent;
 use crate::game::MODAL_Z_LAYER;
 use crate::screen::playing::GameState;
@@ -29,7 +34,7 @@ pub struct ExitTavernButton;
 #[derive(Component)]
 pub struct TavernButton(pub Entity);
 
-/// Each can be bought twice
+/// Ways to spend a level up, each stat tops out at [`MAX_UPGRADED_STAT`]
 #[derive(Component)]
 pub enum TavernUpgrade {
     AddMovement,
@@ -49,8 +54,15 @@ pub struct ItemSlotLabel;
 #[derive(Component)]
 pub struct NameLabel;
 
+/// Index into the tavern roster.
 #[derive(Component)]
-pub struct RecruitButton;
+pub struct HireButton(pub usize);
+
+#[derive(Component)]
+pub struct ContractLabel;
+
+#[derive(Component)]
+pub struct ClassLabel;
 
 #[derive(Component)]
 pub struct TavernActorList;
@@ -76,11 +88,28 @@ pub fn spawn_hero_button(ui: &mut UiBuilder<Entity>, entity: Entity, name: Strin
     });
 }
 
+fn hire_label(mercenary: &MercenaryTemplate) -> String {
+    let wage = match mercenary.wage {
+        0 => "fights for free".to_string(),
+        wage => format!("{wage} gold/night"),
+    };
+    format!(
+        "{} ({}): {} health, {} movement. Hire {} gold, {wage}",
+        mercenary.title,
+        mercenary.sprite.class().name,
+        mercenary.health,
+        mercenary.movement,
+        mercenary.hire_cost
+    )
+}
+
 pub fn tavern_modal_layout(
     mut commands: Commands,
     player_unit_list: Res<PlayerActorList>,
     unit_query: Query<(Entity, &ActorName)>,
     mut subject: ResMut<TavernSubject>,
+    roster: Res<TavernRoster>,
+    desertions: Res<Desertions>,
 ) {
     subject.0 = player_unit_list
         .0
@@ -123,10 +152,24 @@ pub fn tavern_modal_layout(
                         }
                     });
 
-                    if player_unit_list.0.len() <= 5 {
-                        ui.row(|ui| {
+                    if !desertions.0.is_empty() {
+                        ui.label(LabelConfig::from(format!(
+                            "Left unpaid last night: {}",
+                            desertions.0.join(", ")
+                        )))
+                        .style()
+                        .font_size(TAVERN_FONT_SIZE)
+                        .font_color(css::INDIAN_RED.into());
+                    }
+
+                    ui.column(|ui| {
+                        for (i, mercenary) in roster.0.iter().enumerate() {
+                            let Some(mercenary) = mercenary else {
+                                continue;
+                            };
+
                             ui.container(ButtonBundle::default(), |ui| {
-                                ui.insert(RecruitButton);
+                                ui.insert(HireButton(i));
 
                                 ui.style()
                                     .margin(UiRect::all(Val::Px(2.)))
@@ -134,17 +177,17 @@ pub fn tavern_modal_layout(
                                     .border(UiRect::all(Val::Px(2.)))
                                     .border_color(Color::WHITE)
                                     .justify_content(JustifyContent::Start);
-                                ui.label(LabelConfig::from(format!("Recruit {RECRUIT_COST} gold")))
+                                ui.label(LabelConfig::from(hire_label(mercenary)))
                                     .style()
-                                    .font_size(TAVERN_FONT_SIZE);
+                                    .font_size(TAVERN_FONT_SIZE * 0.8);
                             })
                             .insert(InteractionPalette {
                                 none: css::BLACK.into(),
                                 hovered: css::DARK_RED.into(),
                                 pressed: css::INDIAN_RED.into(),
                             });
-                        });
-                    }
+                        }
+                    });
 
                     ui.row(|ui| {
                         ui.column(|ui| {
@@ -153,6 +196,10 @@ pub fn tavern_modal_layout(
                                 .insert(NameLabel)
                                 .style()
                                 .font_size(TAVERN_FONT_SIZE);
+                            ui.label(LabelConfig::from(""))
+                                .insert(ClassLabel)
+                                .style()
+                                .font_size(TAVERN_FONT_SIZE * 0.8);
                             ui.row(|ui| {
                                 ui.label(LabelConfig::from("Movement:"))
                                     .style()
@@ -170,11 +217,9 @@ pub fn tavern_modal_layout(
                                         .border(UiRect::all(Val::Px(2.)))
                                         .border_color(Color::WHITE)
                                         .justify_content(JustifyContent::Start);
-                                    ui.label(LabelConfig::from(format!(
-                                        "+movement {UPGRADE_COST} gold"
-                                    )))
-                                    .style()
-                                    .font_size(TAVERN_FONT_SIZE);
+                                    ui.label(LabelConfig::from("+movement"))
+                                        .style()
+                                        .font_size(TAVERN_FONT_SIZE);
                                 })
                                 .insert(InteractionPalette {
                                     none: css::BLACK.into(),
@@ -200,11 +245,9 @@ pub fn tavern_modal_layout(
                                         .border(UiRect::all(Val::Px(2.)))
                                         .border_color(Color::WHITE)
                                         .justify_content(JustifyContent::Start);
-                                    ui.label(LabelConfig::from(format!(
-                                        "+health {UPGRADE_COST} gold"
-                                    )))
-                                    .style()
-                                    .font_size(TAVERN_FONT_SIZE);
+                                    ui.label(LabelConfig::from("+health"))
+                                        .style()
+                                        .font_size(TAVERN_FONT_SIZE);
                                 })
                                 .insert(InteractionPalette {
                                     none: css::BLACK.into(),
@@ -230,11 +273,9 @@ pub fn tavern_modal_layout(
                                         .border(UiRect::all(Val::Px(2.)))
                                         .border_color(Color::WHITE)
                                         .justify_content(JustifyContent::Start);
-                                    ui.label(LabelConfig::from(format!(
-                                        "+item slot {UPGRADE_COST} gold"
-                                    )))
-                                    .style()
-                                    .font_size(TAVERN_FONT_SIZE);
+                                    ui.label(LabelConfig::from("+item slot"))
+                                        .style()
+                                        .font_size(TAVERN_FONT_SIZE);
                                 })
                                 .insert(InteractionPalette {
                                     none: css::BLACK.into(),
@@ -242,6 +283,11 @@ pub fn tavern_modal_layout(
                                     pressed: css::INDIAN_RED.into(),
                                 });
                             });
+
+                            ui.label(LabelConfig::from(""))
+                                .insert(ContractLabel)
+                                .style()
+                                .font_size(TAVERN_FONT_SIZE);
                         });
                     });
                 });
@@ -369,33 +415,40 @@ pub fn update_slot_labels(
 }
 
 pub fn upgrade_buttons(
-    mut gold: ResMut<VillageGold>,
     subject: Res<TavernSubject>,
     upgrade_query: Query<(&Interaction, &TavernUpgrade), Changed<Interaction>>,
-    mut stats_query: Query<(&mut Movement, &mut Health, &mut Inventory)>,
+    mut stats_query: Query<(
+        &mut Movement,
+        &mut Health,
+        &mut Inventory,
+        &mut Experience,
+        &PlayerSprite,
+    )>,
 ) {
-    let Ok((mut m, mut h, mut s)) = stats_query.get_mut(subject.0) else {
+    let Ok((mut m, mut h, mut s, mut experience, sprite)) = stats_query.get_mut(subject.0) else {
         return;
     };
 
-    fn upgrade(value: &mut u32) -> bool {
+    fn upgrade(value: &mut u32, cap: u32) -> bool {
         let n = *value;
-        *value = (*value + 1).min(5);
-        n == *value
+        *value = (*value + 1).min(cap).max(n);
+        n != *value
     }
 
+    let max_health = sprite.class().max_health();
+
     for (_, u) in upgrade_query
         .iter()
         .filter(|(&i, _)| i == Interaction::Pressed)
     {
-        if UPGRADE_COST > gold.0 {
+        if experience.unspent == 0 {
             continue;
         }
 
         if match u {
-            TavernUpgrade::AddMovement => upgrade(&mut m.0),
+            TavernUpgrade::AddMovement => upgrade(&mut m.0, MAX_UPGRADED_STAT),
             TavernUpgrade::AddHealth => {
-                if upgrade(&mut h.max) {
+                if upgrade(&mut h.max, max_health) {
                     h.value = (h.value + 1).min(h.max);
                     true
                 } else {
@@ -403,7 +456,7 @@ pub fn upgrade_buttons(
                 }
             }
             TavernUpgrade::AddItemSlot => {
-                if s.slot_count() < 5 {
+                if (s.slot_count() as u32) < MAX_UPGRADED_STAT {
                     s.add_slot();
                     true
                 } else {
@@ -411,11 +464,42 @@ pub fn upgrade_buttons(
                 }
             }
         } {
-            gold.0 -= UPGRADE_COST;
+            experience.unspent -= 1;
+            if sprite.class().ability == ClassAbility::BattleHardened
+                && upgrade(&mut h.max, max_health)
+            {
+                h.value = (h.value + 1).min(h.max);
+            }
         }
     }
 }
 
+pub fn update_class_label(
+    subject: Res<TavernSubject>,
+    query: Query<(&PlayerSprite, &Experience)>,
+    mut label_query: Query<&mut Text, With<ClassLabel>>,
+) {
+    let Ok((sprite, experience)) = query.get(subject.0) else {
+        return;
+    };
+
+    let class = sprite.class();
+    let mut text = format!(
+        "{}, level {} ({}/{} xp)\n{}",
+        class.name,
+        experience.level + 1,
+        experience.xp,
+        experience.next_level_xp(),
+        class.ability.description()
+    );
+    if experience.unspent > 0 {
+        text.push_str(&format!("\n{} level up(s) to spend", experience.unspent));
+    }
+    for mut t in label_query.iter_mut() {
+        t.sections[0].value.clone_from(&text);
+    }
+}
+
 pub fn tavern_button(
     mut subject: ResMut<TavernSubject>,
     i_q: Query<(&Interaction, &TavernButton)>,
@@ -427,27 +511,77 @@ pub fn tavern_button(
     }
 }
 
-pub fn recruit_button(
-    r_q: Query<&Interaction, (With<RecruitButton>, Changed<Interaction>)>,
+pub fn update_contract_label(
+    subject: Res<TavernSubject>,
+    query: Query<Option<&Contract>>,
+    mut label_query: Query<&mut Text, With<ContractLabel>>,
+) {
+    let Ok(contract) = query.get(subject.0) else {
+        return;
+    };
+
+    let text = match contract {
+        Some(Contract { wage: 0, .. }) => "Fights for free".to_string(),
+        Some(contract) => format!(
+            "Wage {} gold/night, loyalty {}/{MAX_LOYALTY}",
+            contract.wage, contract.loyalty
+        ),
+        None => "Stays for good".to_string(),
+    };
+    for mut t in label_query.iter_mut() {
+        t.sections[0].value.clone_from(&text);
+    }
+}
+
+pub fn hire_button(
+    r_q: Query<(Entity, &Interaction, &HireButton), Changed<Interaction>>,
     mut commands: Commands,
-    mut gold: ResMut<VillageGold>,
+    mut stock: ResMut<VillageStock>,
+    mut ledger: ResMut<Ledger>,
+    mut roster: ResMut<TavernRoster>,
     mut player_unit_list: ResMut<PlayerActorList>,
     mut names: ResMut<AvailableActorNames>,
     t_q: Query<Entity, With<TavernActorList>>,
 ) {
-    if player_unit_list.0.len() < 5 {
-        for _ in r_q.iter().filter(|&&i| i == Interaction::Pressed) {
-            if RECRUIT_COST > gold.0 {
-                continue;
-            }
+    for (button, _, hire) in r_q.iter().filter(|(_, &i, _)| i == Interaction::Pressed) {
+        if player_unit_list.0.len() >= MAX_HEROES {
+            continue;
+        }
+        let Some(mercenary) = roster.0.get(hire.0).copied().flatten() else {
+            continue;
+        };
+        if !stock.spend(&ResourceBundle::gold(mercenary.hire_cost)) {
+            continue;
+        }
 
-            gold.0 -= RECRUIT_COST;
-            let name = names.next_name();
-            let id = spawn_player_unit(&mut commands, name.clone());
-            player_unit_list.0.push(id);
-            for entity in t_q.iter() {
-                spawn_hero_button(&mut commands.ui_builder(entity), id, name.clone())
-            }
+        ledger.gold(LedgerSource::Recruit, -(mercenary.hire_cost as i32));
+        roster.0[hire.0] = None;
+        commands.entity(button).despawn_recursive();
+
+        let (id, name) = mercenary.hire(&mut commands, &mut names, &mut player_unit_list);
+        for entity in t_q.iter() {
+            spawn_hero_button(&mut commands.ui_builder(entity), id, name.clone())
         }
     }
 }
+
+/// Hiring stops once there is no room for another hero.
+pub fn disable_hire_buttons(
+    mut commands: Commands,
+    player_unit_list: Res<PlayerActorList>,
+    q_buttons: Query<Entity, (With<HireButton>, With<Interaction>)>,
+) {
+    if player_unit_list.0.len() < MAX_HEROES {
+        return;
+    }
+
+    for button in q_buttons.iter() {
+        commands
+            .entity(button)
+            .remove::<(Interaction, InteractionPalette)>()
+            .insert((
+                BackgroundColor(css::DARK_SLATE_GRAY.into()),
+                BorderColor(css::GRAY.into()),
+            ));
+    }
+}
@@ -0,0 +1,215 @@
+use bevy::color::palettes::css;
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use sickle_ui::prelude::*;
+
+use crate::game::assets::SfxKey;
+use crate::game::audio::sfx::PlaySfx;
+use crate::game::constants::{BIG_TEXT_SIZE, TAVERN_FONT_SIZE};
+use crate::game::visitors::{DawnVisitor, VisitorDeal, VisitorTemplate};
+use crate::game::MODAL_Z_LAYER;
+use crate::screen::playing::GameState;
+use crate::ui::prelude::*;
+
+pub struct VisitorModalPlugin;
+
+impl Plugin for VisitorModalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<VisitorSubject>()
+            .add_systems(OnEnter(GameState::BuildingTurn), enter_visitor_modal)
+            .add_systems(OnEnter(GameState::Visitor), visitor_modal_layout)
+            .add_systems(
+                Update,
+                (visitor_choice_buttons, leave_visitor_btn_interaction)
+                    .chain()
+                    .run_if(in_state(GameState::Visitor)),
+            );
+    }
+}
+
+/// Visitor currently being received.
+#[derive(Resource, Default)]
+pub struct VisitorSubject(pub Option<&'static VisitorTemplate>);
+
+/// Index into the visitor's choices.
+#[derive(Component)]
+pub struct VisitorChoiceButton(pub usize);
+
+#[derive(Component)]
+pub struct LeaveVisitorButton;
+
+#[derive(Component)]
+pub struct LeaveVisitorLabel;
+
+#[derive(Component)]
+pub struct VisitorOutcomeLabel;
+
+#[derive(Component)]
+pub struct VisitorChoiceList;
+
+fn enter_visitor_modal(
+    mut visitor: ResMut<DawnVisitor>,
+    mut subject: ResMut<VisitorSubject>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(template) = visitor.0.take() else {
+        return;
+    };
+
+    subject.0 = Some(template);
+    next_state.set(GameState::Visitor);
+}
+
+fn visitor_modal_layout(mut commands: Commands, subject: Res<VisitorSubject>) {
+    let Some(visitor) = subject.0 else {
+        return;
+    };
+
+    commands
+        .ui_builder(UiRoot)
+        .column(|ui| {
+            ui.column(|ui| {
+                ui.style()
+                    .padding(UiRect::all(Val::Px(18.)))
+                    .border(UiRect::all(Val::Px(2.)))
+                    .border_color(Color::WHITE)
+                    .border_radius(BorderRadius::all(Val::Px(16.)))
+                    .background_color(Color::BLACK)
+                    .width(Val::Px(480.))
+                    .align_items(AlignItems::Center)
+                    .row_gap(Val::Px(16.));
+
+                ui.label(LabelConfig::from(visitor.name))
+                    .style()
+                    .margin(UiRect::all(Val::Px(16.)))
+                    .font_size(BIG_TEXT_SIZE);
+                ui.icon(visitor.icon)
+                    .style()
+                    .width(Val::Px(64.))
+                    .height(Val::Px(64.));
+                ui.label(LabelConfig::from(visitor.greeting))
+                    .style()
+                    .font_size(TAVERN_FONT_SIZE);
+
+                ui.column(|ui| {
+                    ui.insert(VisitorChoiceList).style().row_gap(Val::Px(10.));
+
+                    for (i, choice) in visitor.choices.iter().enumerate() {
+                        let text = match choice.cost.is_empty() {
+                            true => choice.label.to_string(),
+                            false => format!("{} ({})", choice.label, choice.cost),
+                        };
+                        ui.container(ButtonBundle::default(), |ui| {
+                            ui.label(LabelConfig::from(text))
+                                .style()
+                                .font_size(TAVERN_FONT_SIZE);
+                        })
+                        .insert((
+                            VisitorChoiceButton(i),
+                            InteractionPalette {
+                                none: css::BLACK.into(),
+                                hovered: css::DARK_RED.into(),
+                                pressed: css::INDIAN_RED.into(),
+                            },
+                        ))
+                        .style()
+                        .padding(UiRect::all(Val::Px(2.)))
+                        .border(UiRect::all(Val::Px(2.)))
+                        .border_color(Color::WHITE);
+                    }
+                });
+
+                ui.label(LabelConfig::from(""))
+                    .insert(VisitorOutcomeLabel)
+                    .style()
+                    .font_size(TAVERN_FONT_SIZE);
+
+                ui.container(ButtonBundle::default(), |ui| {
+                    ui.label(LabelConfig::from("Send them away"))
+                        .insert(LeaveVisitorLabel)
+                        .style()
+                        .font_size(TAVERN_FONT_SIZE);
+                })
+                .insert((
+                    LeaveVisitorButton,
+                    InteractionPalette {
+                        none: css::BLACK.into(),
+                        hovered: css::DARK_RED.into(),
+                        pressed: css::INDIAN_RED.into(),
+                    },
+                ))
+                .style()
+                .padding(UiRect::all(Val::Px(2.)))
+                .border(UiRect::all(Val::Px(2.)))
+                .border_color(Color::WHITE);
+            });
+        })
+        .insert(StateScoped(GameState::Visitor))
+        .style()
+        .focus_policy(FocusPolicy::Block)
+        .z_index(ZIndex::Global(MODAL_Z_LAYER))
+        .width(Val::Percent(100.))
+        .height(Val::Percent(100.))
+        .background_color(Color::srgba(0.25, 0.25, 0.25, 0.75))
+        .justify_content(JustifyContent::Center)
+        .align_items(AlignItems::Center);
+}
+
+fn visitor_choice_buttons(
+    mut commands: Commands,
+    q_interactions: Query<(&Interaction, &VisitorChoiceButton), Changed<Interaction>>,
+    q_choice_list: Query<Entity, With<VisitorChoiceList>>,
+    mut q_outcome_label: Query<&mut Text, (With<VisitorOutcomeLabel>, Without<LeaveVisitorLabel>)>,
+    mut q_leave_label: Query<&mut Text, (With<LeaveVisitorLabel>, Without<VisitorOutcomeLabel>)>,
+    subject: Res<VisitorSubject>,
+    mut deal: VisitorDeal,
+) {
+    let Some(visitor) = subject.0 else {
+        return;
+    };
+
+    for (_, button) in q_interactions
+        .iter()
+        .filter(|(&i, _)| i == Interaction::Pressed)
+    {
+        let Some(choice) = visitor.choices.get(button.0) else {
+            continue;
+        };
+
+        let outcome = match deal.can_accept(choice) {
+            true => deal.accept(choice),
+            false => Vec::new(),
+        };
+        let Ok(mut outcome_label) = q_outcome_label.get_single_mut() else {
+            continue;
+        };
+
+        if outcome.is_empty() {
+            outcome_label.sections[0].value = "You can't accept that deal.".to_string();
+            continue;
+        }
+
+        // Only one deal per visit
+        outcome_label.sections[0].value = outcome.join("\n");
+        for entity in q_choice_list.iter() {
+            commands.entity(entity).despawn_descendants();
+        }
+        for mut leave_label in q_leave_label.iter_mut() {
+            leave_label.sections[0].value = "Farewell".to_string();
+        }
+        commands.trigger(PlaySfx::Key(SfxKey::CoinPurchase));
+    }
+}
+
+fn leave_visitor_btn_interaction(
+    q_interactions: Query<&Interaction, (Changed<Interaction>, With<LeaveVisitorButton>)>,
+    mut subject: ResMut<VisitorSubject>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    for interaction in q_interactions.iter() {
+        if let Interaction::Pressed = interaction {
+            subject.0 = None;
+            next_game_state.set(GameState::BuildingTurn);
+        }
+    }
+}
@@ -278,13 +278,30 @@ impl Tile {
         // })
     }
 
-    pub fn get_line_between(mut self, other: Tile) -> Option<impl Iterator<Item = Tile>> {
-        let edge = self.find_direction_edge(other)?;
-        let direction = edge.direction();
+    /// Tiles strictly between `self` and `other` along a Bresenham line, in any direction.
+    pub fn get_line_between(self, other: Tile) -> Option<impl Iterator<Item = Tile>> {
+        if self == other {
+            return None;
+        }
+
+        let dx = (other.x() - self.x()).abs();
+        let dy = -(other.y() - self.y()).abs();
+        let sx = (other.x() - self.x()).signum();
+        let sy = (other.y() - self.y()).signum();
+        let mut error = dx + dy;
+        let mut cursor = self;
 
         Some(std::iter::from_fn(move || {
-            self = self.step(direction);
-            Some(self).filter(|cursor| *cursor == other)
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                cursor.0 += sx;
+            }
+            if doubled <= dx {
+                error += dx;
+                cursor.1 += sy;
+            }
+            Some(cursor).filter(|cursor| *cursor != other)
         }))
     }
 
@@ -542,6 +559,14 @@ impl Sub<TileDim> for Tile {
 pub struct TileRect(pub Tile, pub Tile);
 
 impl TileRect {
+    /// Rectangle of `size` tiles with `origin` as its minimum corner.
+    pub fn from_origin(origin: Tile, size: TileDim) -> Self {
+        Self(
+            origin,
+            Tile(origin.x() + size.x() - 1, origin.y() + size.y() - 1),
+        )
+    }
+
     pub fn min(self) -> Tile {
         self.0.to_ivec2().min(self.1.to_ivec2()).into()
     }
@@ -692,6 +717,27 @@ mod tests {
         assert_eq!(tile1.max(tile2), Tile(2, 3));
     }
 
+    #[test]
+    fn test_tile_line_between() {
+        let line = |a: Tile, b: Tile| a.get_line_between(b).map(|line| line.collect::<Vec<_>>());
+
+        assert_eq!(
+            line(Tile(0, 0), Tile(3, 0)),
+            Some(vec![Tile(1, 0), Tile(2, 0)])
+        );
+        assert_eq!(
+            line(Tile(3, 0), Tile(0, 0)),
+            Some(vec![Tile(2, 0), Tile(1, 0)])
+        );
+        assert_eq!(line(Tile(0, 0), Tile(2, 2)), Some(vec![Tile(1, 1)]));
+        assert_eq!(
+            line(Tile(0, 0), Tile(3, 1)),
+            Some(vec![Tile(1, 0), Tile(2, 1)])
+        );
+        assert_eq!(line(Tile(1, 1), Tile(1, 2)), Some(vec![]));
+        assert_eq!(line(Tile(1, 1), Tile(1, 1)), None);
+    }
+
     #[test]
     fn test_tile_distance() {
         let tile1 = Tile(1, 2);
@@ -3,8 +3,10 @@
 pub mod playing;
 
 mod credits;
+mod high_scores;
 mod loading;
 mod lost;
+mod mutators;
 mod splash;
 mod title;
 
@@ -19,6 +21,8 @@ pub(super) fn plugin(app: &mut App) {
         loading::plugin,
         title::plugin,
         credits::plugin,
+        high_scores::plugin,
+        mutators::plugin,
         playing::plugin,
         lost::plugin,
     ));
@@ -32,6 +36,8 @@ pub enum Screen {
     Loading,
     Title,
     Credits,
+    HighScores,
+    Mutators,
     Playing,
     Lost,
 }
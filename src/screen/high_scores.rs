@@ -0,0 +1,67 @@
+//! The local high score table, accessed from the title screen.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{game::score::HighScores, ui::prelude::*};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::HighScores), enter_high_scores);
+
+    app.add_systems(
+        Update,
+        handle_high_scores_action.run_if(in_state(Screen::HighScores)),
+    );
+    app.register_type::<HighScoresAction>();
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum HighScoresAction {
+    Back,
+}
+
+fn enter_high_scores(mut commands: Commands, high_scores: Res<HighScores>) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::HighScores))
+        .with_children(|children| {
+            children.header("High Scores");
+            if high_scores.0.is_empty() {
+                children.label("No runs yet.");
+            }
+            for (rank, entry) in high_scores.0.iter().enumerate() {
+                let mut mode = match &entry.date {
+                    Some(date) => format!("{} {date}", entry.mode),
+                    None => entry.mode.to_string(),
+                };
+                if !entry.mutators.is_empty() {
+                    mode = format!("{mode}, {}", entry.mutators.join(", "));
+                }
+                children.label(format!(
+                    "{}. {} - {} nights, {} kills ({}, {})",
+                    rank + 1,
+                    entry.score,
+                    entry.nights,
+                    entry.kills,
+                    mode,
+                    entry.difficulty
+                ));
+            }
+
+            children.button("Back").insert(HighScoresAction::Back);
+        });
+}
+
+fn handle_high_scores_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut button_query: InteractionQuery<&HighScoresAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                HighScoresAction::Back => next_screen.set(Screen::Title),
+            }
+        }
+    }
+}
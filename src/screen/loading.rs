@@ -5,7 +5,10 @@ use bevy::prelude::*;
 
 use super::Screen;
 use crate::{
-    game::assets::{HandleMap, ImageKey, SfxKey, SoundtrackKey},
+    game::{
+        assets::{HandleMap, ImageKey, SfxKey, SoundtrackKey},
+        cycle::calendar::CalendarHandle,
+    },
     ui::prelude::*,
 };
 
@@ -33,6 +36,7 @@ fn all_assets_loaded(
     image_handles: Res<HandleMap<ImageKey>>,
     sfx_handles: Res<HandleMap<SfxKey>>,
     soundtrack_handles: Res<HandleMap<SoundtrackKey>>,
+    calendar_handle: Res<CalendarHandle>,
 ) -> bool {
     *elapsed += time.delta();
     // if loading takes too long, start
@@ -40,6 +44,7 @@ fn all_assets_loaded(
         || image_handles.all_loaded(&asset_server)
             && sfx_handles.all_loaded(&asset_server)
             && soundtrack_handles.all_loaded(&asset_server)
+            && asset_server.is_loaded_with_dependencies(&calendar_handle.0)
 }
 
 fn continue_to_title(mut next_screen: ResMut<NextState<Screen>>) {
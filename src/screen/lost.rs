@@ -13,13 +13,15 @@ use sickle_ui::widgets::layout::label::{LabelConfig, UiLabelExt};
 use sickle_ui::widgets::layout::row::UiRowExt;
 
 use crate::game::actors::Structure;
+use crate::game::components::Defence;
+use crate::game::score::{record_score, RunStats};
 use crate::ui::interaction::InteractionPalette;
 use crate::ui::palette::{HEADER_SIZE, LABEL_SIZE};
 
 use super::Screen;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(OnEnter(Screen::Lost), show_lost_scren)
+    app.add_systems(OnEnter(Screen::Lost), show_lost_scren.after(record_score))
         .add_systems(
             PostUpdate,
             check_lost_status.run_if(in_state(Screen::Playing)),
@@ -30,7 +32,7 @@ pub(super) fn plugin(app: &mut App) {
 #[derive(Component)]
 struct ReturnToMenuButton;
 
-fn show_lost_scren(mut commands: Commands) {
+fn show_lost_scren(mut commands: Commands, stats: Res<RunStats>) {
     commands
         .ui_builder(UiRoot)
         .column(|ui| {
@@ -54,6 +56,23 @@ fn show_lost_scren(mut commands: Commands) {
                             .style()
                             .font_size(HEADER_SIZE);
 
+                        if let Some((entry, rank)) = &stats.result {
+                            ui.label(LabelConfig::from(format!(
+                                "Score: {} ({} nights, {} kills)",
+                                entry.score, entry.nights, entry.kills
+                            )))
+                            .style()
+                            .font_size(LABEL_SIZE);
+                            if let Some(rank) = rank {
+                                ui.label(LabelConfig::from(format!(
+                                    "New high score, #{} in the table!",
+                                    rank + 1
+                                )))
+                                .style()
+                                .font_size(LABEL_SIZE);
+                            }
+                        }
+
                         ui.column(|_| {}).style().height(Val::Px(40.0));
 
                         ui.container(ButtonBundle::default(), |ui| {
@@ -89,7 +108,7 @@ fn show_lost_scren(mut commands: Commands) {
 }
 
 fn check_lost_status(
-    q_structures: Query<(), With<Structure>>,
+    q_structures: Query<(), (With<Structure>, Without<Defence>)>,
     mut next_screen: ResMut<NextState<Screen>>,
 ) {
     if q_structures.is_empty() {
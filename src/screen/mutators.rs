@@ -0,0 +1,83 @@
+//! Pick the mutators of the next run before it starts.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::game::mutators::{ActiveMutators, MutatorRegistry};
+use crate::ui::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Mutators), enter_mutators);
+
+    app.add_systems(
+        Update,
+        handle_mutators_action.run_if(in_state(Screen::Mutators)),
+    );
+    app.register_type::<MutatorsAction>();
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum MutatorsAction {
+    /// Turn the mutator at this index of the registry on or off.
+    Toggle(usize),
+    Start,
+    Back,
+}
+
+fn toggle_label(name: &str, is_active: bool) -> String {
+    match is_active {
+        true => format!("{name}: On"),
+        false => format!("{name}: Off"),
+    }
+}
+
+fn enter_mutators(
+    mut commands: Commands,
+    registry: Res<MutatorRegistry>,
+    mutators: Res<ActiveMutators>,
+) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Mutators))
+        .with_children(|children| {
+            children.header("Mutators");
+            for (index, mutator) in registry.iter().enumerate() {
+                children
+                    .button(toggle_label(mutator.name, mutators.is_active(mutator)))
+                    .insert(MutatorsAction::Toggle(index));
+                children.label(mutator.description);
+            }
+
+            children.button("Start").insert(MutatorsAction::Start);
+            children.button("Back").insert(MutatorsAction::Back);
+        });
+}
+
+fn handle_mutators_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut button_query: InteractionQuery<(&MutatorsAction, &Children)>,
+    mut q_texts: Query<&mut Text>,
+    registry: Res<MutatorRegistry>,
+    mut mutators: ResMut<ActiveMutators>,
+) {
+    for (interaction, (action, children)) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                MutatorsAction::Toggle(index) => {
+                    let Some(mutator) = registry.get(*index) else {
+                        continue;
+                    };
+                    mutators.toggle(*mutator);
+                    let mut texts = q_texts.iter_many_mut(children);
+                    while let Some(mut text) = texts.fetch_next() {
+                        text.sections[0].value =
+                            toggle_label(mutator.name, mutators.is_active(mutator));
+                    }
+                }
+                MutatorsAction::Start => next_screen.set(Screen::Playing),
+                MutatorsAction::Back => next_screen.set(Screen::Title),
+            }
+        }
+    }
+}
@@ -4,10 +4,11 @@ use bevy::color::palettes::css;
 use bevy::ecs::entity::EntityHashMap;
 use bevy::prelude::*;
 use sickle_ui::prelude::*;
+use strum::IntoEnumIterator;
 
 use super::Screen;
 use crate::game::actors::AvailableActorNames;
-use crate::game::constants::{INITIAL_GOLD, INITIAL_POPULATION, UNIT_LIST_ZINDEX};
+use crate::game::constants::UNIT_LIST_ZINDEX;
 use crate::game::construction::{
     build_btn_interaction, building_panel_layout, cancel_build_btn_interaction,
     spawn_in_progress_building, update_build_panel, update_building_progress,
@@ -19,8 +20,9 @@ use crate::game::deployment::{
 };
 use crate::game::events::{EndDayTurn, SelectStructureTypeEvent};
 use crate::game::resources::{
-    SelectedStructueType, VillageEmployment, VillageGold, VillagePopulation,
+    ResourceKind, SelectedStructueType, VillageJobs, VillagePopulation, VillageStock,
 };
+use crate::game::turn_order::{EndHeroSlot, TurnOrder};
 
 use crate::game::actors::player::{add_starting_player_units, move_unit, reset_unit_turn_states};
 use crate::game::selection::{dispatch_object_pressed, SelectedTiles};
@@ -34,18 +36,22 @@ use crate::game::WatchRes;
 use crate::game::{assets::SoundtrackKey, audio::soundtrack::PlaySoundtrack};
 
 use crate::modals::tavern::{
-    enter_tavern_modal, exit_tavern_btn_interaction, recruit_button, tavern_button,
-    tavern_modal_layout, update_slot_labels, upgrade_buttons, TavernSubject,
+    disable_hire_buttons, enter_tavern_modal, exit_tavern_btn_interaction, hire_button,
+    tavern_button, tavern_modal_layout, update_class_label, update_contract_label,
+    update_slot_labels, upgrade_buttons, TavernSubject,
 };
 
-use crate::modals::merchant::MerchantModalPlugin;
+use crate::modals::blacksmith::BlacksmithModalPlugin;
+use crate::modals::merchant::{MerchantModalPlugin, MerchantVisit};
+use crate::modals::visitor::VisitorModalPlugin;
 use crate::ui::icon_set::IconSet;
 
 use crate::ui::interaction::apply_interaction_palette;
 use crate::ui::{palette::*, prelude::*};
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins(MerchantModalPlugin)
+    app.add_plugins((MerchantModalPlugin, BlacksmithModalPlugin))
+        .add_plugins(VisitorModalPlugin)
         .init_state::<GameState>()
         .enable_state_scoped_entities::<GameState>()
         .init_resource::<DisplayCache>()
@@ -76,13 +82,13 @@ pub(super) fn plugin(app: &mut App) {
         .add_systems(
             OnExit(GameState::Deployment),
             |mut selected_tiles: ResMut<SelectedTiles>| {
-                selected_tiles.tiles.clear();
+                selected_tiles.clear();
             },
         )
         .add_systems(
             OnEnter(TimeOfDay::Day),
             |mut selected_tiles: ResMut<SelectedTiles>| {
-                selected_tiles.tiles.clear();
+                selected_tiles.clear();
             },
         )
         .add_systems(
@@ -152,7 +158,10 @@ pub(super) fn plugin(app: &mut App) {
                 tavern_button,
                 upgrade_buttons,
                 update_slot_labels,
-                recruit_button,
+                update_contract_label,
+                update_class_label,
+                hire_button,
+                disable_hire_buttons,
             )
                 .chain()
                 .run_if(in_state(GameState::Tavern)),
@@ -195,18 +204,21 @@ fn economy_status_layout(ui: &mut UiBuilder<Entity>) {
         ui.style().justify_content(JustifyContent::Center);
         ui.row(|ui| {
             ui.style().column_gap(Val::Px(40.));
-            ui.row(|ui| {
-                ui.style().column_gap(Val::Px(4.));
-                ui.icon("icons/gold_coins.png")
-                    .style()
-                    .width(Val::Px(32.))
-                    .height(Val::Px(32.));
+            for kind in ResourceKind::iter() {
+                ui.row(|ui| {
+                    ui.style().column_gap(Val::Px(4.));
+                    ui.icon(kind.icon_path())
+                        .style()
+                        .width(Val::Px(32.))
+                        .height(Val::Px(32.));
 
-                ui.label(LabelConfig::from(INITIAL_GOLD.to_string()))
-                    .insert(WatchRes::<VillageGold>::default())
-                    .style()
-                    .font_size(LABEL_SIZE);
-            });
+                    // Filled in with the starting resources of the difficulty
+                    ui.label(LabelConfig::default())
+                        .insert((WatchRes::<VillageStock>::default(), kind))
+                        .style()
+                        .font_size(LABEL_SIZE);
+                });
+            }
 
             ui.row(|ui| {
                 ui.style().column_gap(Val::Px(4.));
@@ -215,14 +227,14 @@ fn economy_status_layout(ui: &mut UiBuilder<Entity>) {
                     .width(Val::Px(32.))
                     .height(Val::Px(32.));
 
-                ui.label(LabelConfig::from(INITIAL_POPULATION.to_string()))
-                    .insert(WatchRes::<VillageEmployment>::default())
+                ui.label(LabelConfig::from("0"))
+                    .insert(WatchRes::<VillageJobs>::default())
                     .style()
                     .font_size(LABEL_SIZE);
                 ui.label(LabelConfig::from("/"))
                     .style()
                     .font_size(LABEL_SIZE);
-                ui.label(LabelConfig::from(INITIAL_POPULATION.to_string()))
+                ui.label(LabelConfig::default())
                     .insert(WatchRes::<VillagePopulation>::default())
                     .style()
                     .font_size(LABEL_SIZE);
@@ -370,10 +382,17 @@ fn end_turn_btn_interaction(
     q_interactions: Query<&Interaction, (Changed<Interaction>, With<EndTurnButton>)>,
     mut next_turn_evt: EventWriter<EndTurn>,
     mut day_turn_evt: EventWriter<EndDayTurn>,
+    mut end_slot_evt: EventWriter<EndHeroSlot>,
     state: Res<State<TimeOfDay>>,
+    turn_order: Res<TurnOrder>,
 ) {
     for interaction in q_interactions.iter() {
         if let Interaction::Pressed = interaction {
+            // The round goes on, heroes act again after the enemies
+            if *state.get() == TimeOfDay::Night && turn_order.heroes_act_again() {
+                end_slot_evt.send(EndHeroSlot);
+                continue;
+            }
             next_turn_evt.send(EndTurn);
             if *state.get() == TimeOfDay::Day {
                 day_turn_evt.send(EndDayTurn);
@@ -396,11 +415,12 @@ fn fight_btn_interaction(
 fn open_merchant_btn_interaction(
     q_interactions: Query<&Interaction, (Changed<Interaction>, With<OpenMerchantButton>)>,
     state: Res<State<TimeOfDay>>,
+    visit: Res<MerchantVisit>,
     mut next_game_state: ResMut<NextState<GameState>>,
 ) {
     for interaction in q_interactions.iter() {
         if let Interaction::Pressed = interaction {
-            if *state.get() == TimeOfDay::Day {
+            if *state.get() == TimeOfDay::Day && visit.is_present() {
                 next_game_state.set(GameState::Merchant);
             }
         }
@@ -417,6 +437,9 @@ fn exit_playing(mut commands: Commands) {
 pub enum GameState {
     Merchant,
     Tavern,
+    Blacksmith,
+    /// Receiving a dawn visitor.
+    Visitor,
     #[default]
     BuildingTurn,
     Deployment,
@@ -464,10 +487,11 @@ pub fn show_all_with<T: Component>(
 
 pub fn set_merchant_button_display(
     game_state: Res<State<GameState>>,
+    visit: Res<MerchantVisit>,
     mut vis_query: Query<&mut Style, With<OpenMerchantButton>>,
 ) {
     for mut style in vis_query.iter_mut() {
-        let display = if *game_state.get() == GameState::BuildingTurn {
+        let display = if *game_state.get() == GameState::BuildingTurn && visit.is_present() {
             Display::Flex
         } else {
             Display::None
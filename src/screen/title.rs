@@ -5,26 +5,51 @@ use bevy::prelude::*;
 use super::Screen;
 use crate::game::assets::SoundtrackKey;
 use crate::game::audio::soundtrack::PlaySoundtrack;
+use crate::game::daily::{today, DailyChallenge};
+use crate::game::difficulty::Difficulty;
+use crate::game::game_mode::GameMode;
+use crate::game::turn_order::TurnOrderSetting;
 use crate::ui::prelude::*;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Title), enter_title);
 
     app.register_type::<TitleAction>();
-    app.add_systems(Update, handle_title_action.run_if(in_state(Screen::Title)));
+    app.add_systems(
+        Update,
+        (
+            handle_title_action,
+            update_difficulty_label.run_if(resource_changed::<GameMode>),
+        )
+            .chain()
+            .run_if(in_state(Screen::Title)),
+    );
 }
 
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
 #[reflect(Component)]
 enum TitleAction {
     Play,
+    /// Cycle through the difficulties.
+    Difficulty,
+    /// Cycle through campaign, endless and daily runs.
+    GameMode,
+    /// Cycle through the turn orders, or leave it to the level.
+    TurnOrder,
+    HighScores,
     Credits,
     /// Exit doesn't work well with embedded applications.
     #[cfg(not(target_family = "wasm"))]
     Exit,
 }
 
-fn enter_title(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn enter_title(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    difficulty: Res<Difficulty>,
+    game_mode: Res<GameMode>,
+    turn_order: Res<TurnOrderSetting>,
+) {
     // Load the poster texture
     let poster_handle = asset_server.load("images/poster.png");
 
@@ -63,6 +88,18 @@ fn enter_title(mut commands: Commands, asset_server: Res<AssetServer>) {
             // Add the title and buttons
             children.title("Cycle of Valor");
             children.title_button("Play").insert(TitleAction::Play);
+            children
+                .title_button(difficulty_label(*difficulty, *game_mode))
+                .insert(TitleAction::Difficulty);
+            children
+                .title_button(game_mode_label(*game_mode))
+                .insert(TitleAction::GameMode);
+            children
+                .title_button(turn_order.to_string())
+                .insert(TitleAction::TurnOrder);
+            children
+                .title_button("Scores")
+                .insert(TitleAction::HighScores);
             children
                 .title_button("Credits")
                 .insert(TitleAction::Credits);
@@ -71,15 +108,85 @@ fn enter_title(mut commands: Commands, asset_server: Res<AssetServer>) {
         });
 }
 
+/// Daily runs are always played on normal.
+fn difficulty_label(difficulty: Difficulty, game_mode: GameMode) -> String {
+    match game_mode {
+        GameMode::Daily => format!("{}: {game_mode}", Difficulty::Normal),
+        _ => difficulty.to_string(),
+    }
+}
+
+fn update_difficulty_label(
+    q_buttons: Query<(&TitleAction, &Children)>,
+    mut q_texts: Query<&mut Text>,
+    difficulty: Res<Difficulty>,
+    game_mode: Res<GameMode>,
+) {
+    for (_, children) in q_buttons
+        .iter()
+        .filter(|(action, _)| **action == TitleAction::Difficulty)
+    {
+        let mut texts = q_texts.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value = difficulty_label(*difficulty, *game_mode);
+        }
+    }
+}
+
+/// Daily runs show today's rules.
+fn game_mode_label(game_mode: GameMode) -> String {
+    if game_mode != GameMode::Daily {
+        return game_mode.to_string();
+    }
+
+    let rules: Vec<_> = DailyChallenge::for_day(today())
+        .rules
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    format!("{game_mode}: {}", rules.join(", "))
+}
+
 fn handle_title_action(
     mut next_screen: ResMut<NextState<Screen>>,
-    mut button_query: InteractionQuery<&TitleAction>,
+    mut button_query: InteractionQuery<(&TitleAction, &Children)>,
+    mut q_texts: Query<&mut Text>,
+    mut difficulty: ResMut<Difficulty>,
+    mut game_mode: ResMut<GameMode>,
+    mut turn_order: ResMut<TurnOrderSetting>,
     #[cfg(not(target_family = "wasm"))] mut app_exit: EventWriter<AppExit>,
 ) {
-    for (interaction, action) in &mut button_query {
+    for (interaction, (action, children)) in &mut button_query {
         if matches!(interaction, Interaction::Pressed) {
             match action {
-                TitleAction::Play => next_screen.set(Screen::Playing),
+                // Daily challenges come with their own rules
+                TitleAction::Play => next_screen.set(match *game_mode {
+                    GameMode::Daily => Screen::Playing,
+                    _ => Screen::Mutators,
+                }),
+                TitleAction::Difficulty if *game_mode == GameMode::Daily => {}
+                TitleAction::Difficulty => {
+                    *difficulty = difficulty.next();
+                    let mut texts = q_texts.iter_many_mut(children);
+                    while let Some(mut text) = texts.fetch_next() {
+                        text.sections[0].value = difficulty.to_string();
+                    }
+                }
+                TitleAction::GameMode => {
+                    *game_mode = game_mode.next();
+                    let mut texts = q_texts.iter_many_mut(children);
+                    while let Some(mut text) = texts.fetch_next() {
+                        text.sections[0].value = game_mode_label(*game_mode);
+                    }
+                }
+                TitleAction::TurnOrder => {
+                    *turn_order = turn_order.next();
+                    let mut texts = q_texts.iter_many_mut(children);
+                    while let Some(mut text) = texts.fetch_next() {
+                        text.sections[0].value = turn_order.to_string();
+                    }
+                }
+                TitleAction::HighScores => next_screen.set(Screen::HighScores),
                 TitleAction::Credits => next_screen.set(Screen::Credits),
 
                 #[cfg(not(target_family = "wasm"))]
@@ -28,6 +28,7 @@ fn load_icon_set(asset_server: Res<AssetServer>, mut icon_set: ResMut<IconSet>)
         "claw_mark",
         // Potions
         "fire_potion",
+        "healing_potion",
         "health_potion",
         "speed_potion",
         "strength_potion",